@@ -0,0 +1,77 @@
+use crate::challenge::ChallengeStatus;
+use crate::color::paint;
+use crate::config::data_dir;
+use chrono::{Local, NaiveDate};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// A finished or abandoned challenge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedChallenge {
+    pub start: NaiveDate,
+    pub target: NaiveDate,
+    pub ended: NaiveDate,
+    /// Days the challenge lasted
+    pub days: i64,
+    pub total_days: i64,
+    /// "completed" or "reset"
+    pub outcome: String,
+}
+
+fn archive_path() -> PathBuf {
+    data_dir().join("challenges.json")
+}
+
+fn load_archive() -> Vec<ArchivedChallenge> {
+    fs::read_to_string(archive_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Add a challenge to the archive as it ends
+pub fn record(status: &ChallengeStatus, outcome: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = load_archive();
+    archive.push(ArchivedChallenge {
+        start: status.install_dt.date_naive(),
+        target: status.target_dt.date_naive(),
+        // A challenge can't end later than its target date
+        ended: status.target_dt.date_naive().min(Local::now().date_naive()),
+        days: status.days_old.min(status.total_days),
+        total_days: status.total_days,
+        outcome: outcome.to_string(),
+    });
+
+    fs::create_dir_all(data_dir())?;
+    fs::write(archive_path(), serde_json::to_string_pretty(&archive)?)?;
+    Ok(())
+}
+
+/// Print every archived challenge, oldest first
+pub fn show_challenges() {
+    let archive = load_archive();
+    if archive.is_empty() {
+        println!("No past challenges yet");
+        println!("Challenges show up here once they are completed or reset");
+        return;
+    }
+
+    println!(
+        "{} {}\n",
+        paint("Past challenges:".cyan()),
+        paint(archive.len().to_string().bold())
+    );
+    for challenge in &archive {
+        let outcome = if challenge.outcome == "completed" {
+            paint(challenge.outcome.as_str().green().bold())
+        } else {
+            paint(challenge.outcome.as_str().yellow())
+        };
+        println!(
+            "  {} → {}  {:>5} of {} days  {}",
+            challenge.start, challenge.ended, challenge.days, challenge.total_days, outcome
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// The `backup` field: when the newest backup from the sources in [backup]
+// was taken, like "2 days ago", in red once it is older than stale_after
+use crate::config::BackupConfig;
+use crate::error::{self, CollectError};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::Deserialize;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+/// Where timeshift keeps its snapshots, when its backup device is mounted
+const TIMESHIFT_DIRS: [&str; 3] = [
+    "/timeshift/snapshots",
+    "/run/timeshift/backup/timeshift/snapshots",
+    "/run/timeshift/backup/timeshift-btrfs/snapshots",
+];
+
+/// Repository locations that need the network, for restic and borg
+const REMOTE_PREFIXES: [&str; 9] = [
+    "sftp:", "ssh://", "rest:", "s3:", "b2:", "azure:", "gs:", "swift:", "rclone:",
+];
+
+/// ssh for remote repositories, failing instead of asking for a password
+const BATCH_SSH: &str = "ssh -o BatchMode=yes";
+
+#[derive(Deserialize)]
+struct ResticSnapshot {
+    time: String,
+}
+
+#[derive(Deserialize)]
+struct BorgList {
+    archives: Vec<BorgArchive>,
+}
+
+#[derive(Deserialize)]
+struct BorgArchive {
+    time: String,
+}
+
+/// Age of the newest backup, and whether it is older than stale_after
+pub fn status(config: &BackupConfig) -> Result<(String, bool), CollectError> {
+    let mut latest = Vec::new();
+    if !config.restic_repo.is_empty() {
+        latest.push(("restic", reachable(&config.restic_repo).and_then(restic)));
+    }
+    if !config.borg_repo.is_empty() {
+        latest.push(("borg", reachable(&config.borg_repo).and_then(borg)));
+    }
+    if config.timeshift {
+        latest.push(("timeshift", timeshift()));
+    }
+    if !config.file.is_empty() {
+        latest.push(("file", stamp_file(&config.file)));
+    }
+    if latest.is_empty() {
+        return Err(CollectError::Failed(
+            "no backup source is set in [backup]".to_string(),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let mut newest: Option<DateTime<Local>> = None;
+    for (name, result) in latest {
+        match result {
+            Ok(time) => newest = newest.max(Some(time)),
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+    let Some(newest) = newest else {
+        return Err(CollectError::Failed(errors.join("; ")));
+    };
+
+    let age = (Local::now() - newest).to_std().unwrap_or_default();
+    let stale = age > Duration::from_millis(config.stale_after.0);
+    Ok((ago(age), stale))
+}
+
+/// The repository, unless it is remote and huginn is offline
+fn reachable(repo: &str) -> Result<&str, CollectError> {
+    if crate::network::offline() && REMOTE_PREFIXES.iter().any(|p| repo.starts_with(p)) {
+        return Err(CollectError::Failed(
+            "remote repository skipped offline".to_string(),
+        ));
+    }
+    Ok(repo)
+}
+
+fn restic(repo: &str) -> Result<DateTime<Local>, CollectError> {
+    let output = run(Command::new("restic").args([
+        "-r",
+        repo,
+        "snapshots",
+        "--latest",
+        "1",
+        "--json",
+        "--no-lock",
+        "-o",
+        "sftp.args=-o BatchMode=yes",
+    ]))?;
+    let snapshots: Vec<ResticSnapshot> = serde_json::from_slice(&output)
+        .map_err(|e| CollectError::Failed(format!("invalid JSON: {}", e)))?;
+    snapshots
+        .iter()
+        .filter_map(|snapshot| DateTime::parse_from_rfc3339(&snapshot.time).ok())
+        .map(|time| time.with_timezone(&Local))
+        .max()
+        .ok_or_else(|| CollectError::Failed("no snapshots yet".to_string()))
+}
+
+fn borg(repo: &str) -> Result<DateTime<Local>, CollectError> {
+    let mut command = Command::new("borg");
+    command
+        .args(["list", "--bypass-lock", "--last", "1", "--json", repo])
+        // Answer borg's questions about moved or unencrypted repositories
+        // with no rather than asking
+        .env("BORG_RELOCATED_REPO_ACCESS_IS_OK", "no")
+        .env("BORG_UNKNOWN_UNENCRYPTED_REPO_ACCESS_IS_OK", "no");
+    if std::env::var_os("BORG_RSH").is_none() {
+        command.env("BORG_RSH", BATCH_SSH);
+    }
+    let output = run(&mut command)?;
+    let list: BorgList = serde_json::from_slice(&output)
+        .map_err(|e| CollectError::Failed(format!("invalid JSON: {}", e)))?;
+    // Borg writes local time without an offset
+    list.archives
+        .iter()
+        .filter_map(|archive| {
+            let time = NaiveDateTime::parse_from_str(&archive.time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+            Local.from_local_datetime(&time).earliest()
+        })
+        .max()
+        .ok_or_else(|| CollectError::Failed("no archives yet".to_string()))
+}
+
+/// Snapshots are named after when they were taken, like 2024-05-01_12-00-01,
+/// both as directories and in `timeshift --list`
+fn timeshift() -> Result<DateTime<Local>, CollectError> {
+    let mut names: Vec<String> = TIMESHIFT_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    // Listing mounts the backup device, which needs root
+    if names.is_empty() {
+        let output = run(Command::new("timeshift").arg("--list"))?;
+        names = String::from_utf8_lossy(&output)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+    }
+    names
+        .iter()
+        .filter_map(|name| NaiveDateTime::parse_from_str(name, "%Y-%m-%d_%H-%M-%S").ok())
+        .filter_map(|time| Local.from_local_datetime(&time).earliest())
+        .max()
+        .ok_or_else(|| CollectError::Failed("no snapshots found".to_string()))
+}
+
+/// A file the backup job touches when it finishes
+fn stamp_file(path: &str) -> Result<DateTime<Local>, CollectError> {
+    let path = crate::expand_home(path);
+    fs::metadata(&path)
+        .and_then(|meta| meta.modified())
+        .map(DateTime::from)
+        .map_err(|e| CollectError::Failed(format!("{}: {}", path, e)))
+}
+
+/// Stdout of a program that must succeed
+fn run(command: &mut Command) -> Result<Vec<u8>, CollectError> {
+    let output = error::output(command)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().last().unwrap_or_default().trim();
+        return Err(CollectError::Failed(if reason.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            reason.to_string()
+        }));
+    }
+    Ok(output.stdout)
+}
+
+/// "just now", "5 hours ago", "1 day ago"
+fn ago(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
@@ -0,0 +1,57 @@
+// The `bluetooth` field: connected Bluetooth devices from bluez, with the
+// battery level of those that report one, like "WH-1000XM4 80%, MX Master 3"
+use crate::error::CollectError;
+
+/// Connected devices, or None when there are none
+#[cfg(target_os = "linux")]
+pub fn connected() -> Result<Option<String>, CollectError> {
+    use zbus::blocking::fdo::ObjectManagerProxy;
+    use zbus::blocking::Connection;
+    use zbus::zvariant::OwnedValue;
+
+    let failed = |e: zbus::Error| CollectError::Failed(e.to_string());
+    let connection = Connection::system().map_err(failed)?;
+    let objects = ObjectManagerProxy::builder(&connection)
+        .destination("org.bluez")
+        .and_then(|builder| builder.path("/"))
+        .and_then(|builder| builder.build())
+        .map_err(failed)?
+        .get_managed_objects()
+        .map_err(|e| CollectError::Failed(e.to_string()))?;
+
+    let mut devices: Vec<(String, String)> = objects
+        .iter()
+        .filter_map(|(path, interfaces)| {
+            let device = interfaces.get("org.bluez.Device1")?;
+            let connected = device.get("Connected").map(OwnedValue::try_clone)?.ok()?;
+            if !bool::try_from(connected).ok()? {
+                return None;
+            }
+            let name = ["Alias", "Name"]
+                .iter()
+                .find_map(|key| String::try_from(device.get(*key)?.try_clone().ok()?).ok())
+                .unwrap_or_else(|| "unknown device".to_string());
+            let battery = interfaces
+                .get("org.bluez.Battery1")
+                .and_then(|battery| battery.get("Percentage")?.try_clone().ok())
+                .and_then(|percentage| u8::try_from(percentage).ok());
+            let label = match battery {
+                Some(percentage) => format!("{} {}%", name, percentage),
+                None => name,
+            };
+            Some((path.to_string(), label))
+        })
+        .collect();
+
+    // Object paths follow the adapter and address, so the order is stable
+    devices.sort();
+    let labels: Vec<String> = devices.into_iter().map(|(_, label)| label).collect();
+    Ok((!labels.is_empty()).then(|| labels.join(", ")))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn connected() -> Result<Option<String>, CollectError> {
+    Err(CollectError::Failed(
+        "only supported on Linux, through bluez".to_string(),
+    ))
+}
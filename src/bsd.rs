@@ -0,0 +1,56 @@
+// Collectors for the BSDs, where sysinfo knows less than on Linux and
+// packages live in pkg or ports databases
+use crate::error::command_output;
+
+fn sysctl(name: &str) -> Option<String> {
+    command_output("sysctl", &["-n", name])
+}
+
+/// Installed packages, whether from binary packages or built from ports
+pub fn package_count() -> Option<usize> {
+    // FreeBSD and DragonFly register both in pkg
+    if let Some(packages) = command_output("pkg", &["info", "-q"]) {
+        return Some(packages.lines().count());
+    }
+    // OpenBSD and NetBSD keep one directory per package
+    ["/var/db/pkg", "/usr/pkg/pkgdb"]
+        .iter()
+        .find_map(|dir| std::fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .count()
+        })
+}
+
+pub fn cpu_model() -> Option<String> {
+    sysctl("hw.model")
+}
+
+/// Memory in use as a percentage, for when sysinfo can't tell
+pub fn memory_usage() -> Option<i32> {
+    let total: u64 = sysctl("hw.physmem64")
+        .or_else(|| sysctl("hw.physmem"))?
+        .parse()
+        .ok()?;
+    // The "fre" column of vmstat is free memory in KiB
+    let stats = command_output("vmstat", &[])?;
+    let mut lines = stats.lines().skip(1);
+    let header = lines.next()?.split_whitespace().position(|c| c == "fre")?;
+    let free: u64 = lines
+        .next()?
+        .split_whitespace()
+        .nth(header)?
+        .trim_end_matches('K')
+        .parse()
+        .ok()?;
+    let used = total.saturating_sub(free * 1024);
+    Some((used as f64 / total.max(1) as f64 * 100.0) as i32)
+}
+
+pub fn get_init() -> Option<String> {
+    std::path::Path::new("/etc/rc")
+        .exists()
+        .then(|| "rc".to_string())
+}
@@ -1,10 +1,22 @@
-use crate::{draw_progress, ProgressColorScheme};
-use chrono::{DateTime, Duration, Utc};
-use crossterm::style::Stylize;
-use std::fs;
+use crate::archive;
+use crate::color::paint;
+use crate::i18n::tr;
+use crate::screen;
+use crate::{draw_progress, progress_width, ProgressColorScheme};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Utc};
+use crossterm::style::{ContentStyle, Stylize};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::config::DisplayConfig;
+use crate::config::{
+    data_dir, BarsConfig, ChallengeConfig, Config, CountdownConfig, DisplayConfig,
+};
+
+/// Progress percentages worth a notification
+const MILESTONES: [i32; 4] = [25, 50, 75, 100];
 
 fn get_install_time(display_config: &DisplayConfig) -> SystemTime {
     use std::path::Path;
@@ -31,78 +43,616 @@ fn get_install_time(display_config: &DisplayConfig) -> SystemTime {
         .unwrap_or(UNIX_EPOCH)
 }
 
-pub fn run_challenge_countdown(
-    years: i64,
-    months: i64,
-    start_row: u16,
-    display_config: &DisplayConfig,
-) -> u16 {
-    use crossterm::{cursor, execute};
-    use std::io;
+/// A challenge's own start date, falling back to the install time when
+/// there is none or it doesn't parse
+fn get_start_time(start_date: Option<&str>, display_config: &DisplayConfig) -> SystemTime {
+    if let Some(start_date) = start_date {
+        match NaiveDate::parse_from_str(start_date, "%Y-%m-%d") {
+            Ok(date) => return date.and_hms_opt(0, 0, 0).unwrap().and_utc().into(),
+            Err(_) => eprintln!("Warning: Invalid challenge start date: {}", start_date),
+        }
+    }
+    get_install_time(display_config)
+}
 
-    let install_time = get_install_time(display_config);
-    let install_dt: DateTime<Utc> = install_time.into();
-    let now_dt: DateTime<Utc> = SystemTime::now().into();
+/// Where the challenge start is kept, so a reinstall can be noticed
+fn recorded_start_path() -> PathBuf {
+    data_dir().join("challenge-start")
+}
 
-    let days_from_years = 365 * years;
-    let days_from_months = (months as f64 * 30.44).round() as i64;
-    let total_challenge_days = days_from_years + days_from_months;
-    let target_dt = install_dt + Duration::days(total_challenge_days);
+fn recorded_start() -> Option<DateTime<Utc>> {
+    fs::read_to_string(recorded_start_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
 
-    let days_old = now_dt.signed_duration_since(install_dt).num_days();
-    let progress_percentage = ((days_old as f64 / total_challenge_days as f64) * 100.0)
-        .min(100.0)
-        .max(0.0) as i32;
+/// Record the install time as the challenge start the first time the
+/// challenge is shown, so a later reinstall can be noticed
+pub fn record_first_start(config: &Config) {
+    if config.challenge.start_date.is_some()
+        || config.display.custom_install_date.is_some()
+        || recorded_start().is_some()
+    {
+        return;
+    }
+    if let Err(e) = record_start(get_install_time(&config.display).into()) {
+        eprintln!("Warning: could not record the challenge start: {}", e);
+    }
+}
 
-    let mut info_items = vec![
-        ("Installed", install_dt.format("%Y-%m-%d").to_string()),
-        ("Current Age", format!("{} days", days_old)),
-    ];
+fn record_start(start: DateTime<Utc>) -> io::Result<()> {
+    fs::create_dir_all(data_dir())?;
+    fs::write(recorded_start_path(), start.to_rfc3339())
+}
+
+/// Computed state of the install challenge, shared by every renderer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeStatus {
+    /// When the challenge started, the install date unless one was configured
+    pub install_dt: DateTime<Utc>,
+    /// Whether the start is a configured date rather than the install date
+    #[serde(default)]
+    pub custom_start: bool,
+    /// The system was installed after the challenge started
+    #[serde(default)]
+    pub reinstall_detected: bool,
+    pub target_dt: DateTime<Utc>,
+    pub days_old: i64,
+    pub total_days: i64,
+    pub progress_percentage: i32,
+    /// Milestone reached for the first time on this run
+    #[serde(default)]
+    pub new_milestone: Option<i32>,
+}
 
-    let remaining_duration = target_dt.signed_duration_since(now_dt);
+impl ChallengeStatus {
+    pub fn new(years: i64, months: i64, start_date: Option<&str>, config: &Config) -> Self {
+        // Without a configured date the challenge starts at the recorded
+        // start, which only moves on `huginn challenge reset`
+        // Only the one-shot fetch records it, see record_first_start
+        let (install_dt, custom_start, reinstall_detected) = match start_date {
+            Some(date) => (
+                get_start_time(Some(date), &config.display).into(),
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(),
+                false,
+            ),
+            None => {
+                let installed: DateTime<Utc> = get_install_time(&config.display).into();
+                match recorded_start() {
+                    // A configured install date is taken as is
+                    _ if config.display.custom_install_date.is_some() => (installed, false, false),
+                    Some(recorded) => (
+                        recorded,
+                        recorded.date_naive() != installed.date_naive(),
+                        installed - recorded > Duration::days(1),
+                    ),
+                    None => (installed, false, false),
+                }
+            }
+        };
+        let now_dt: DateTime<Utc> = SystemTime::now().into();
 
-    if remaining_duration.num_seconds() <= 0 {
-        info_items.push(("Status", "Challenge Complete!".green().bold().to_string()));
-    } else {
-        let rem_days = remaining_duration.num_days();
-        let rem_hours = remaining_duration.num_hours() % 24;
-        info_items.push((
-            "Time Left",
-            format!("{} days, {} hours", rem_days, rem_hours)
-                .magenta()
-                .to_string(),
-        ));
+        let calendar_target = config
+            .challenge
+            .calendar_months
+            .then(|| {
+                let months = u32::try_from(years * 12 + months).ok()?;
+                install_dt.checked_add_months(Months::new(months))
+            })
+            .flatten();
+        let (total_days, target_dt) = match calendar_target {
+            Some(target_dt) => ((target_dt - install_dt).num_days(), target_dt),
+            None => {
+                let days_from_years = 365 * years;
+                let days_from_months = (months as f64 * 30.44).round() as i64;
+                let total_days = days_from_years + days_from_months;
+                (total_days, install_dt + Duration::days(total_days))
+            }
+        };
+
+        let days_old = now_dt.signed_duration_since(install_dt).num_days();
+        let progress_percentage =
+            ((days_old as f64 / total_days as f64) * 100.0).clamp(0.0, 100.0) as i32;
+
+        Self {
+            install_dt,
+            custom_start,
+            reinstall_detected,
+            target_dt,
+            days_old,
+            total_days,
+            progress_percentage,
+            new_milestone: None,
+        }
+    }
+
+    /// Like `new`, but an error instead of a guess when the dates don't
+    /// parse, the challenge has no length or the install date is unknown
+    pub fn determine(
+        years: i64,
+        months: i64,
+        start_date: Option<&str>,
+        config: &Config,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let date = start_date.or(config.display.custom_install_date.as_deref());
+        if let Some(date) = date {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|e| format!("invalid date {:?}: {}", date, e))?;
+        }
+        if years * 12 + months <= 0 {
+            return Err("the challenge is zero months long".into());
+        }
+        let status = Self::new(years, months, start_date, config);
+        // get_install_time falls back to the epoch
+        if status.install_dt.timestamp() <= 0 {
+            return Err("the install date is unknown".into());
+        }
+        Ok(status)
+    }
+
+    /// Record milestones crossed for the first time, running the milestone
+    /// scripts for each and, if enabled, sending a desktop notification and
+    /// marking the newest one for display. Reaching 100% archives the
+    /// challenge
+    pub fn check_milestones(&mut self, config: &Config) {
+        let scripts = &config.scripts;
+        let path = data_dir().join("milestones");
+        // A challenge is told apart by its start and length
+        let key = format!("{}+{}", self.install_dt.format("%Y-%m-%d"), self.total_days);
+        let reached = fs::read_to_string(&path).unwrap_or_default();
+
+        let new: Vec<i32> = MILESTONES
+            .iter()
+            .copied()
+            .filter(|milestone| *milestone <= self.progress_percentage)
+            .filter(|milestone| {
+                let line = format!("{} {}", key, milestone);
+                !reached.lines().any(|reached| reached == line)
+            })
+            .collect();
+        let Some(&newest) = new.last() else {
+            return;
+        };
+
+        // Recorded first, so a failing script can't make them fire again
+        let recorded = fs::create_dir_all(data_dir()).and_then(|_| {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            new.iter()
+                .try_for_each(|milestone| writeln!(file, "{} {}", key, milestone))
+        });
+        if let Err(e) = recorded {
+            eprintln!("Warning: could not record milestones: {}", e);
+            return;
+        }
+
+        for milestone in &new {
+            if !scripts.on_milestone.is_empty() {
+                let mut command = crate::shell_command(&scripts.on_milestone);
+                command.env("HUGINN_MILESTONE", milestone.to_string());
+                crate::run_script("milestone", command);
+            }
+        }
+        if newest >= 100 {
+            if let Err(e) = archive::record(self, "completed") {
+                eprintln!("Warning: could not archive the challenge: {}", e);
+            }
+            if !scripts.on_challenge_complete.is_empty() {
+                crate::run_script(
+                    "challenge complete",
+                    crate::shell_command(&scripts.on_challenge_complete),
+                );
+            }
+        }
+
+        if !config.challenge.milestones {
+            return;
+        }
+        self.new_milestone = Some(newest);
+        if let Some(text) = self.milestone_text() {
+            let _ = notify_rust::Notification::new()
+                .summary("huginn")
+                .body(&text)
+                .show();
+        }
+    }
+
+    fn milestone_text(&self) -> Option<String> {
+        self.new_milestone.map(|milestone| {
+            if milestone >= 100 {
+                "Challenge complete!".to_string()
+            } else {
+                format!("{}% of the challenge done!", milestone)
+            }
+        })
+    }
+
+    /// Time until the target date, negative once it has passed
+    pub fn remaining(&self) -> Duration {
+        self.target_dt.signed_duration_since(Utc::now())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining().num_seconds() <= 0
+    }
+
+    /// Time until the target in the configured units: "hours" (days and
+    /// hours), "days", "weeks", "months" or "percent"
+    fn time_left(&self, units: &str) -> String {
+        let remaining = self.remaining();
+        let days = remaining.num_days();
+        match units {
+            "days" => format!("{} {}", days, tr("days")),
+            "weeks" => format!("{} {}, {} {}", days / 7, tr("weeks"), days % 7, tr("days")),
+            "months" => {
+                // Whole calendar months first, then the days left over
+                let today = Local::now().date_naive();
+                let target = self.target_dt.with_timezone(&Local).date_naive();
+                let mut months = 0;
+                while today
+                    .checked_add_months(Months::new(months + 1))
+                    .is_some_and(|date| date <= target)
+                {
+                    months += 1;
+                }
+                let rest = today
+                    .checked_add_months(Months::new(months))
+                    .map_or(0, |date| (target - date).num_days());
+                format!("{} {}, {} {}", months, tr("months"), rest, tr("days"))
+            }
+            "percent" => format!("{}% to go", 100 - self.progress_percentage),
+            _ => format!(
+                "{} {}, {} {}",
+                days,
+                tr("days"),
+                remaining.num_hours() % 24,
+                tr("hours")
+            ),
+        }
+    }
+
+    /// Label/value pairs for the challenge block along with the style of each value
+    pub fn entries(&self, config: &ChallengeConfig) -> Vec<(&'static str, String, ContentStyle)> {
+        let mut entries = vec![
+            (
+                tr(if self.custom_start {
+                    "Started"
+                } else {
+                    "Installed"
+                }),
+                self.install_dt.format("%Y-%m-%d").to_string(),
+                ContentStyle::new(),
+            ),
+            (
+                tr("Current Age"),
+                format!("{} {}", self.days_old, tr("days")),
+                ContentStyle::new(),
+            ),
+        ];
+
+        if self.reinstall_detected {
+            entries.insert(
+                0,
+                (
+                    tr("Reinstalled"),
+                    tr("run huginn challenge reset").to_string(),
+                    ContentStyle::new().red().bold(),
+                ),
+            );
+        }
+
+        if let Some(text) = self.milestone_text() {
+            entries.push((tr("Milestone"), text, ContentStyle::new().yellow().bold()));
+        }
+
+        if self.is_complete() {
+            entries.push((
+                tr("Status"),
+                tr("Challenge Complete!").to_string(),
+                ContentStyle::new().green().bold(),
+            ));
+        } else {
+            entries.push((
+                tr("Time Left"),
+                self.time_left(&config.units),
+                ContentStyle::new().magenta(),
+            ));
+
+            let format = if config.show_weekday {
+                "%a %Y-%m-%d"
+            } else {
+                "%Y-%m-%d"
+            };
+            entries.push((
+                tr("Completes"),
+                self.target_dt
+                    .with_timezone(&Local)
+                    .format(format)
+                    .to_string(),
+                ContentStyle::new(),
+            ));
+        }
+
+        entries
+    }
+
+    /// Columns the countdown block takes up in the terminal
+    pub fn countdown_width(&self, config: &Config) -> usize {
+        let entries = self.entries(&config.challenge);
+        let extra = extra_challenges(config);
+        let label_width = entries
+            .iter()
+            .map(|(l, _, _)| l.chars().count())
+            .chain(extra.iter().map(|(name, _)| name.chars().count()))
+            .max()
+            .unwrap_or(0);
+        let value_width = entries
+            .iter()
+            .map(|(_, v, _)| v.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        // The progress line starts 8 columns left of the values
+        let separator_width = config.info.separator.chars().count() + 3;
+        let extra_width = if extra.is_empty() {
+            0
+        } else {
+            label_width + separator_width + progress_width(3, &config.bars)
+        };
+        (label_width + separator_width + value_width)
+            .max((label_width + progress_width(3, &config.bars)).saturating_sub(8))
+            .max(extra_width)
+    }
+
+    /// Label/value pairs for the challenge block, values already styled
+    pub fn info_items(&self, config: &ChallengeConfig) -> Vec<(&'static str, String)> {
+        self.entries(config)
+            .into_iter()
+            .map(|(label, value, style)| (label, paint(style.apply(value))))
+            .collect()
+    }
+}
+
+/// Archive the current challenge and start it over from today
+pub fn reset(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let status = ChallengeStatus::new(
+        config.challenge.years,
+        config.challenge.months,
+        config.challenge.start_date.as_deref(),
+        config,
+    );
+    archive::record(&status, "reset")?;
+    record_start(Utc::now())?;
+
+    println!("Challenge restarted today");
+    if config.challenge.start_date.is_some() {
+        println!("start_date under [challenge] still takes precedence, remove it to use today");
+    } else if config.display.custom_install_date.is_some() {
+        println!(
+            "custom_install_date under [display] still takes precedence, remove it to use today"
+        );
+    }
+    Ok(())
+}
+
+/// The challenges from `[[challenge.extra]]`, by name
+pub fn extra_challenges(config: &Config) -> Vec<(String, ChallengeStatus)> {
+    config
+        .challenge
+        .extra
+        .iter()
+        .map(|extra| {
+            (
+                extra.name.clone(),
+                ChallengeStatus::new(
+                    extra.years,
+                    extra.months,
+                    extra.start_date.as_deref(),
+                    config,
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Banner for today if it's worth celebrating: an install anniversary or
+/// the day the challenge ends
+pub fn celebration(config: &Config, challenge: Option<&ChallengeStatus>) -> Option<String> {
+    if !config.challenge.celebrate {
+        return None;
+    }
+    let today = Local::now().date_naive();
+    // Configured dates are kept as midnight UTC, but they name a calendar
+    // day; other times are instants, which fall on the local day
+    let configured =
+        config.challenge.start_date.is_some() || config.display.custom_install_date.is_some();
+    let date = |time: DateTime<Utc>| {
+        if configured {
+            time.date_naive()
+        } else {
+            time.with_timezone(&Local).date_naive()
+        }
+    };
+
+    if challenge.is_some_and(|status| date(status.target_dt) == today) {
+        return Some(tr("Challenge complete, congratulations!").to_string());
+    }
+
+    let installed = match &config.display.custom_install_date {
+        Some(_) => DateTime::<Utc>::from(get_install_time(&config.display)).date_naive(),
+        None => DateTime::<Local>::from(get_install_time(&config.display)).date_naive(),
+    };
+    let years = today.year() - installed.year();
+    // Installs on February 29 celebrate on the 28th in other years
+    let anniversary = NaiveDate::from_ymd_opt(today.year(), installed.month(), installed.day())
+        .or_else(|| NaiveDate::from_ymd_opt(today.year(), 2, 28));
+    if years > 0 && anniversary == Some(today) {
+        if years == 1 {
+            return Some(tr("Happy 1st install anniversary!").to_string());
+        }
+        // {nth} is the English ordinal, other languages write their own
+        // around {years}
+        let suffix = match (years % 10, years % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+        return Some(
+            tr("Happy {nth} install anniversary!")
+                .replace("{nth}", &format!("{}{}", years, suffix))
+                .replace("{years}", &years.to_string()),
+        );
+    }
+    None
+}
+
+/// A `[[countdowns]]` entry measured against today
+pub struct Countdown {
+    pub name: String,
+    pub days_left: i64,
+    pub progress_percentage: i32,
+}
+
+impl Countdown {
+    /// None, with a warning, when one of its dates can't be parsed
+    pub fn new(config: &CountdownConfig, display_config: &DisplayConfig) -> Option<Self> {
+        let parse = |date: &str| match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => Some(date),
+            Err(_) => {
+                eprintln!(
+                    "Warning: Invalid date for countdown {}: {}",
+                    config.name, date
+                );
+                None
+            }
+        };
+        let target = parse(&config.date)?;
+        let start = match &config.start {
+            Some(start) => parse(start)?,
+            None => DateTime::<Utc>::from(get_install_time(display_config)).date_naive(),
+        };
+        let today = Local::now().date_naive();
+
+        let total_days = (target - start).num_days();
+        let progress_percentage = if total_days > 0 {
+            (((today - start).num_days() as f64 / total_days as f64) * 100.0).clamp(0.0, 100.0)
+                as i32
+        } else {
+            100
+        };
+
+        Some(Self {
+            name: config.name.clone(),
+            days_left: (target - today).num_days(),
+            progress_percentage,
+        })
+    }
+
+    /// How far away the date is, like "12 days left"
+    pub fn days_text(&self) -> String {
+        let days = |text: &str, n: i64| tr(text).replace("{days}", &n.to_string());
+        match self.days_left {
+            0 => tr("today").to_string(),
+            1 => tr("1 day left").to_string(),
+            n if n > 0 => days("{days} days left", n),
+            -1 => tr("1 day ago").to_string(),
+            n => days("{days} days ago", -n),
+        }
     }
 
+    /// Progress bar followed by the days left
+    pub fn value(&self, bars: &BarsConfig) -> String {
+        let days = self.days_text();
+        format!(
+            "{} {}",
+            draw_progress(
+                self.progress_percentage,
+                3,
+                ProgressColorScheme::Challenge,
+                bars
+            ),
+            paint(days.magenta())
+        )
+    }
+}
+
+/// Label/value pairs for every configured countdown
+pub fn countdown_items(config: &Config) -> Vec<(String, String)> {
+    config
+        .countdowns
+        .iter()
+        .filter_map(|countdown| Countdown::new(countdown, &config.display))
+        .map(|countdown| (countdown.name.clone(), countdown.value(&config.bars)))
+        .collect()
+}
+
+pub fn run_challenge_countdown(
+    status: &ChallengeStatus,
+    config: &Config,
+    padding_left: u16,
+    start_row: u16,
+) -> u16 {
+    use crossterm::{cursor, execute};
+
+    let info_items = status.info_items(&config.challenge);
+    let progress_percentage = status.progress_percentage;
+    let extra = extra_challenges(config);
+
     let max_label_width = info_items
         .iter()
-        .map(|(label, _)| label.len())
+        .map(|(label, _)| label.chars().count())
+        .chain(extra.iter().map(|(name, _)| name.chars().count()))
         .max()
         .unwrap_or(0);
 
-    let padding_left = 50;
     let mut current_row = start_row;
 
     for (label, value) in info_items {
-        let _ = execute!(io::stdout(), cursor::MoveTo(padding_left, current_row));
-        print!(
-            "{: >width$} {} {}",
-            label,
-            " ".green(),
-            value,
-            width = max_label_width
+        let _ = execute!(screen::out(), cursor::MoveTo(padding_left, current_row));
+        let _ = write!(
+            screen::out(),
+            "{} {} {}",
+            config.info.align_label(label, max_label_width),
+            config.info.painted_separator(),
+            value
         );
         current_row += 1;
     }
 
     let _ = execute!(
-        io::stdout(),
+        screen::out(),
         cursor::MoveTo(padding_left + max_label_width as u16 - 8, current_row)
     );
-    print!(
-        "{:>3}% {}",
-        progress_percentage,
-        draw_progress(progress_percentage, 14, ProgressColorScheme::Challenge)
+    let _ = write!(
+        screen::out(),
+        "{}",
+        draw_progress(
+            progress_percentage,
+            3,
+            ProgressColorScheme::Challenge,
+            &config.bars
+        )
     );
+
+    // Extra challenges stack under the main progress bar
+    for (name, extra_status) in extra {
+        current_row += 1;
+        let _ = execute!(screen::out(), cursor::MoveTo(padding_left, current_row));
+        let _ = write!(
+            screen::out(),
+            "{} {} {}",
+            config.info.align_label(&name, max_label_width),
+            config.info.painted_separator(),
+            draw_progress(
+                extra_status.progress_percentage,
+                3,
+                ProgressColorScheme::Challenge,
+                &config.bars
+            )
+        );
+    }
     current_row
 }
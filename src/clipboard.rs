@@ -0,0 +1,55 @@
+// --copy: put the fetch on the clipboard
+// Uses the first clipboard tool it finds, and falls back to OSC 52, which asks
+// the terminal itself to set the clipboard (works over ssh in most terminals)
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Clipboard tools reading the text on stdin, and the variable that tells
+/// whether their display server is around
+const TOOLS: &[(&str, &[&str], Option<&str>)] = &[
+    ("wl-copy", &[], Some("WAYLAND_DISPLAY")),
+    ("xclip", &["-selection", "clipboard"], Some("DISPLAY")),
+    ("xsel", &["--clipboard", "--input"], Some("DISPLAY")),
+    ("pbcopy", &[], None),
+    ("clip.exe", &[], None),
+    ("termux-clipboard-set", &[], None),
+];
+
+/// Copy `text` to the clipboard, returning how it was done
+pub fn copy(text: &str) -> Result<&'static str, Box<dyn std::error::Error>> {
+    let tools = TOOLS.iter().filter(|(tool, _, display)| {
+        display.is_none_or(|var| std::env::var_os(var).is_some()) && which::which(tool).is_ok()
+    });
+    for (tool, args, _) in tools {
+        match pipe_to(tool, args, text) {
+            Ok(()) => return Ok(tool),
+            Err(e) => eprintln!("Warning: {} failed: {}", tool, e),
+        }
+    }
+
+    if !io::stdout().is_terminal() {
+        return Err("no clipboard tool found, and stdout isn't a terminal for OSC 52".into());
+    }
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+    Ok("OSC 52")
+}
+
+fn pipe_to(tool: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    // Closing stdin tells the tool the text is complete
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("exited with {}", status)));
+    }
+    Ok(())
+}
@@ -0,0 +1,304 @@
+use crossterm::style::{Color, StyledContent};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+// Global switch for all styled output, flipped off by --no-color or NO_COLOR
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+// Colors the terminal can show, a ColorDepth
+static DEPTH: AtomicU8 = AtomicU8::new(ColorDepth::TrueColor as u8);
+
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// Enable or disable styling for the rest of the run
+pub fn set_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+    crossterm::style::force_color_output(enabled);
+}
+
+pub fn enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Check the NO_COLOR convention (https://no-color.org)
+/// Any non-empty value disables color
+pub fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Render styled content, dropping all escape codes when color is disabled
+/// and mapping colors down to what the terminal can show
+pub fn paint<D: Display>(styled: StyledContent<D>) -> String {
+    if !enabled() {
+        return styled.content().to_string();
+    }
+    if depth() == ColorDepth::TrueColor {
+        return styled.to_string();
+    }
+    let mut style = *styled.style();
+    style.foreground_color = style.foreground_color.map(fit);
+    style.background_color = style.background_color.map(fit);
+    style.underline_color = style.underline_color.map(fit);
+    StyledContent::new(style, styled.content()).to_string()
+}
+
+/// Use the configured color depth: "truecolor", "256", "16", or "auto" to
+/// detect it
+pub fn set_depth(configured: &str) {
+    let depth = match configured {
+        "truecolor" => ColorDepth::TrueColor,
+        "256" => ColorDepth::Ansi256,
+        "16" => ColorDepth::Ansi16,
+        other => {
+            if other != "auto" {
+                eprintln!("Warning: Unknown color depth: {}", other);
+            }
+            detect_depth()
+        }
+    };
+    DEPTH.store(depth as u8, Ordering::Relaxed);
+}
+
+pub fn depth() -> ColorDepth {
+    match DEPTH.load(Ordering::Relaxed) {
+        0 => ColorDepth::Ansi16,
+        1 => ColorDepth::Ansi256,
+        _ => ColorDepth::TrueColor,
+    }
+}
+
+/// Terminals that show true color but don't always say so in COLORTERM,
+/// which ssh and sudo drop
+const TRUECOLOR_TERMS: [&str; 6] = [
+    "xterm-kitty",
+    "xterm-ghostty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "contour",
+];
+
+/// The terminal's color depth, from COLORTERM, TERM and its terminfo entry
+/// Output that isn't going to a terminal keeps every color
+pub fn detect_depth() -> ColorDepth {
+    static DETECTED: OnceLock<ColorDepth> = OnceLock::new();
+    *DETECTED.get_or_init(|| {
+        if !io::stdout().is_terminal() {
+            return ColorDepth::TrueColor;
+        }
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+        let term = env::var("TERM").unwrap_or_default();
+        if term.ends_with("-direct") || TRUECOLOR_TERMS.contains(&term.as_str()) {
+            return ColorDepth::TrueColor;
+        }
+        // The Windows console has no TERM and has shown true color since
+        // Windows 10
+        if term.is_empty() && cfg!(windows) {
+            return ColorDepth::TrueColor;
+        }
+        match terminfo_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => ColorDepth::TrueColor,
+            Some(colors) if colors >= 256 => ColorDepth::Ansi256,
+            Some(_) => ColorDepth::Ansi16,
+            None if term.contains("256color") => ColorDepth::Ansi256,
+            None => ColorDepth::Ansi16,
+        }
+    })
+}
+
+/// Index of max_colors among terminfo's numeric capabilities
+const MAX_COLORS: usize = 13;
+
+/// The colors capability of the compiled terminfo entry for `term`
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    dirs.extend(env::var_os("TERMINFO").map(PathBuf::from));
+    if let Some(home) = crate::config::home_dir() {
+        dirs.push(PathBuf::from(format!("{}/.terminfo", home)));
+    }
+    if let Some(list) = env::var_os("TERMINFO_DIRS") {
+        dirs.extend(env::split_paths(&list));
+    }
+    dirs.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .into_iter()
+            .map(PathBuf::from),
+    );
+    // Entries are filed under their first letter, or its hex code on macOS
+    let data = dirs.iter().find_map(|dir| {
+        [first.to_string(), format!("{:x}", first as u32)]
+            .into_iter()
+            .find_map(|sub| fs::read(dir.join(sub).join(term)).ok())
+    })?;
+
+    let short = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    // Numbers are 16 bits in the legacy format, 32 in ncurses' extended one
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names, bools, numbers) = (short(2)? as usize, short(4)? as usize, short(6)?);
+    if numbers as usize <= MAX_COLORS {
+        return None;
+    }
+    // The numbers start on an even byte, after the header, names and flags
+    let start = (12 + names + bools).next_multiple_of(2) + MAX_COLORS * number_size;
+    let bytes = data.get(start..start + number_size)?;
+    let colors = match *bytes {
+        [a, b] => i16::from_le_bytes([a, b]) as i32,
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]),
+        _ => return None,
+    };
+    // Absent capabilities are stored as -1
+    u32::try_from(colors).ok()
+}
+
+/// The closest color the terminal can show; the 16 named colors are left
+/// alone, as every terminal has them
+pub fn fit(color: Color) -> Color {
+    match (depth(), color) {
+        (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(nearest_256((r, g, b))),
+        (ColorDepth::Ansi16, Color::Rgb { .. } | Color::AnsiValue(_)) => {
+            let rgb = to_rgb(color);
+            *BASE
+                .iter()
+                .min_by_key(|base| distance(to_rgb(**base), rgb))
+                .unwrap_or(&color)
+        }
+        _ => color,
+    }
+}
+
+/// Nearest entry of the 256-color palette's color cube or grey ramp
+fn nearest_256((r, g, b): (u8, u8, u8)) -> u8 {
+    // Cube levels are 0, 95, 135, 175, 215 and 255
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v - 35) / 40,
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = (r as u16 + g as u16 + b as u16) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+    [cube, grey]
+        .into_iter()
+        .min_by_key(|index| distance(ansi_to_rgb(*index), (r, g, b)))
+        .unwrap_or(cube)
+}
+
+/// Squared distance between two colors
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+/// Color huginn highlights values with, also what `"accent"` refers to in
+/// the config
+pub const ACCENT: Color = Color::Cyan;
+
+/// Parse a color from the config: `"#rrggbb"`, a terminal color name like
+/// `"blue"` or `"dark_cyan"`, or `"accent"`
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase();
+    if value == "accent" {
+        return Some(ACCENT);
+    }
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+
+    // crossterm knows the names, including dark_ variants
+    Color::try_from(value.as_str()).ok()
+}
+
+/// Hex value of a terminal color, using the standard xterm palette
+/// Exports that leave the terminal (HTML, SVG, images) need concrete colors
+pub fn to_hex(color: Color) -> String {
+    let (r, g, b) = to_rgb(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// RGB value of a terminal color, using the standard xterm palette
+pub fn to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Black => (0x00, 0x00, 0x00),
+        Color::DarkRed => (0xcd, 0x00, 0x00),
+        Color::DarkGreen => (0x00, 0xcd, 0x00),
+        Color::DarkYellow => (0xcd, 0xcd, 0x00),
+        Color::DarkBlue => (0x00, 0x00, 0xee),
+        Color::DarkMagenta => (0xcd, 0x00, 0xcd),
+        Color::DarkCyan => (0x00, 0xcd, 0xcd),
+        Color::Grey => (0xe5, 0xe5, 0xe5),
+        Color::DarkGrey => (0x7f, 0x7f, 0x7f),
+        Color::Red => (0xff, 0x00, 0x00),
+        Color::Green => (0x00, 0xff, 0x00),
+        Color::Yellow => (0xff, 0xff, 0x00),
+        Color::Blue => (0x5c, 0x5c, 0xff),
+        Color::Magenta => (0xff, 0x00, 0xff),
+        Color::Cyan => (0x00, 0xff, 0xff),
+        Color::White | Color::Reset => (0xff, 0xff, 0xff),
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::AnsiValue(value) => ansi_to_rgb(value),
+    }
+}
+
+/// The 16 colors every terminal has, in palette order
+const BASE: [Color; 16] = [
+    Color::Black,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+    Color::Grey,
+    Color::DarkGrey,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => to_rgb(BASE[value as usize]),
+        16..=231 => {
+            // 6x6x6 color cube
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let v = value - 16;
+            (level(v / 36), level((v / 6) % 6), level(v % 6))
+        }
+        _ => {
+            let grey = 8 + (value - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
@@ -1,10 +1,22 @@
+use crate::color::{paint, parse_color};
+use crate::i18n::tr;
+use crate::migrate::{self, CONFIG_VERSION};
+use crate::plugins::PluginField;
+use chrono::Timelike;
+use crossterm::style::{Color, Stylize};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tracing::debug;
 
 /// Main configuration structure for huginn
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Layout the file was written for; older ones are upgraded on load
+    #[serde(default)]
+    pub version: i64,
+
     #[serde(default)]
     pub display: DisplayConfig,
 
@@ -16,6 +28,127 @@ pub struct Config {
 
     #[serde(default)]
     pub scripts: ScriptsConfig,
+
+    #[serde(default)]
+    pub status: StatusConfig,
+
+    #[serde(default)]
+    pub prompt: PromptConfig,
+
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    #[serde(default)]
+    pub collectors: CollectorsConfig,
+
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+
+    #[serde(default, rename = "box")]
+    pub outer_box: BoxConfig,
+
+    #[serde(default)]
+    pub info: InfoConfig,
+
+    #[serde(default)]
+    pub bars: BarsConfig,
+
+    #[serde(default)]
+    pub colorbar: ColorbarConfig,
+
+    #[serde(default)]
+    pub layout: LayoutConfig,
+
+    #[serde(default)]
+    pub greeting: GreetingConfig,
+
+    #[serde(default)]
+    pub quote: QuoteConfig,
+
+    #[serde(default)]
+    pub tips: TipsConfig,
+
+    #[serde(default)]
+    pub countdowns: Vec<CountdownConfig>,
+
+    #[serde(default)]
+    pub locale: LocaleConfig,
+
+    /// Shorthand for [collectors.timeouts], merged into it on load
+    #[serde(default)]
+    pub timeouts: HashMap<String, Millis>,
+
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    #[serde(default)]
+    pub share: ShareConfig,
+
+    #[serde(default)]
+    pub backup: BackupConfig,
+}
+
+/// What huginn may do over the network
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Never go online, same as --offline
+    #[serde(default)]
+    pub offline: bool,
+}
+
+/// Where `huginn share` uploads the fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareConfig {
+    /// Paste service URL; the response body is the link
+    #[serde(default = "default_share_endpoint")]
+    pub endpoint: String,
+
+    /// Upload as a multipart form with the text in this field, like 0x0.st's
+    /// "file"; empty sends the text as the request body, like paste.rs
+    #[serde(default)]
+    pub form_field: String,
+
+    /// "plain" or "json"
+    #[serde(default = "default_share_format")]
+    pub format: String,
+
+    /// Show the text and ask before uploading it
+    #[serde(default = "default_true")]
+    pub confirm: bool,
+}
+
+/// Where the backup field looks for the newest backup; the newest of all
+/// the sources set here wins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Repository for `restic snapshots`; its password comes from the usual
+    /// RESTIC_PASSWORD_FILE or RESTIC_PASSWORD_COMMAND
+    #[serde(default)]
+    pub restic_repo: String,
+
+    /// Repository for `borg list`, with BORG_PASSCOMMAND or the like set
+    #[serde(default)]
+    pub borg_repo: String,
+
+    /// Look at timeshift's snapshots
+    #[serde(default)]
+    pub timeshift: bool,
+
+    /// A file whose modification time is the last backup, touched by the job
+    #[serde(default)]
+    pub file: String,
+
+    /// Backups older than this are shown in red
+    #[serde(default = "default_backup_stale_after")]
+    pub stale_after: Millis,
+}
+
+/// Language for labels and messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleConfig {
+    /// A language code like "de", or "auto" to follow $LANG
+    #[serde(default = "default_language")]
+    pub language: String,
 }
 
 /// Configuration for which fields to display
@@ -24,101 +157,864 @@ pub struct DisplayConfig {
     #[serde(default = "default_mode")]
     pub mode: String, // "normal" or "challenge"
 
+    /// Colors the terminal shows: "auto", "truecolor", "256" or "16"
+    #[serde(default = "default_color_depth")]
+    pub color_depth: String,
+
     #[serde(default)]
     pub custom_install_date: Option<String>,
 
     #[serde(default = "default_true")]
     pub distro: bool,
 
-    #[serde(default = "default_true")]
-    pub age: bool,
+    /// On by default only on macOS, where the model is the usual way to tell
+    /// Macs apart
+    #[serde(default = "default_on_macos")]
+    pub model: bool,
+
+    #[serde(default = "default_true")]
+    pub age: bool,
+
+    #[serde(default = "default_true")]
+    pub kernel: bool,
+
+    #[serde(default = "default_true")]
+    pub packages: bool,
+
+    /// "total", or "breakdown" for the count of every package manager
+    #[serde(default = "default_packages_style")]
+    pub packages_style: String,
+
+    #[serde(default = "default_true")]
+    pub shell: bool,
+
+    #[serde(default = "default_true")]
+    pub term: bool,
+
+    #[serde(default = "default_true")]
+    pub wm: bool,
+
+    #[serde(default = "default_true")]
+    pub cpu: bool,
+
+    #[serde(default = "default_true")]
+    pub gpu: bool,
+
+    #[serde(default = "default_true")]
+    pub theme: bool,
+
+    #[serde(default = "default_true")]
+    pub nix: bool,
+
+    /// Guix System and Guix Home generations, on Guix
+    #[serde(default = "default_true")]
+    pub guix: bool,
+
+    /// System Integrity Protection, which only macOS has
+    #[serde(default = "default_on_macos")]
+    pub sip: bool,
+
+    #[serde(default)]
+    pub init: bool,
+
+    #[serde(default)]
+    pub streak: bool,
+
+    #[serde(default)]
+    pub uptime_record: bool,
+
+    /// Size of the home directory, cached and refreshed in the background
+    #[serde(default)]
+    pub home: bool,
+
+    /// Branch and unpushed or uncommitted changes of dotfiles_repo
+    #[serde(default)]
+    pub dotfiles: bool,
+
+    /// Your dotfiles repository: a work tree, or the git dir of a bare
+    /// repository whose work tree is the home directory
+    #[serde(default = "default_dotfiles_repo")]
+    pub dotfiles_repo: String,
+
+    /// How long ago the last backup ran, from the sources in [backup]
+    #[serde(default)]
+    pub backup: bool,
+
+    /// Round trip time of one ping to latency_host
+    #[serde(default)]
+    pub latency: bool,
+
+    /// Host for the latency field; empty pings the default gateway
+    #[serde(default)]
+    pub latency_host: String,
+
+    /// DNS servers in use, and whether DNS over TLS and DNSSEC are on
+    #[serde(default)]
+    pub dns: bool,
+
+    /// Connected Bluetooth devices and their battery levels
+    #[serde(default)]
+    pub bluetooth: bool,
+
+    /// How many users are logged in over SSH
+    #[serde(default)]
+    pub ssh: bool,
+
+    /// Logged-in users and how many terminals each has open
+    #[serde(default)]
+    pub users: bool,
+
+    /// When the system booted, next to how long it has been up
+    #[serde(default)]
+    pub booted: bool,
+
+    /// strftime format for the booted field
+    #[serde(default = "default_boot_time_format")]
+    pub boot_time_format: String,
+
+    /// Why the kernel is tainted, in yellow when it is
+    #[serde(default)]
+    pub taint: bool,
+
+    /// Pending updates that fix security issues, in red when there are any
+    #[serde(default)]
+    pub security: bool,
+
+    /// Watts drawn from the battery, or by the CPU package through RAPL
+    #[serde(default)]
+    pub power: bool,
+
+    /// Watts where the power field turns from green to yellow and from yellow
+    /// to red; empty leaves it uncolored
+    #[serde(default)]
+    pub power_thresholds: Vec<f64>,
+
+    /// GPU temperature, shown as "gpu temp"
+    #[serde(default)]
+    pub gpu_temp: bool,
+
+    /// CPU and GPU temperatures on one line
+    #[serde(default)]
+    pub temps: bool,
+
+    /// SoC temperature of a single-board computer, and whether the firmware
+    /// is throttling it
+    #[serde(default)]
+    pub soc: bool,
+
+    /// Fields from executables in ~/.config/huginn/plugins
+    #[serde(default = "default_true")]
+    pub plugins: bool,
+
+    /// Mount point for the disk bar, or "auto" for the largest physical
+    /// filesystem, which is also the fallback when it isn't mounted or is
+    /// a tmpfs
+    #[serde(default = "default_disk_mount")]
+    pub disk_mount: String,
+}
+
+/// Configuration for the challenge mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeConfig {
+    #[serde(default = "default_years")]
+    pub years: i64,
+
+    #[serde(default = "default_months")]
+    pub months: i64,
+
+    /// Confetti and a banner on install anniversaries and the day a
+    /// challenge ends
+    #[serde(default = "default_true")]
+    pub celebrate: bool,
+
+    /// More challenges, stacked under the main one
+    #[serde(default)]
+    pub extra: Vec<ExtraChallengeConfig>,
+
+    /// When the challenge started, "YYYY-MM-DD"; the install date if unset
+    #[serde(default)]
+    pub start_date: Option<String>,
+
+    /// Notify and highlight the first time 25/50/75/100% is reached
+    #[serde(default = "default_true")]
+    pub milestones: bool,
+
+    /// Include the weekday in the completion date
+    #[serde(default)]
+    pub show_weekday: bool,
+
+    /// How the time left is shown: "hours" (days and hours), "days",
+    /// "weeks", "months" or "percent"
+    #[serde(default = "default_challenge_units")]
+    pub units: String,
+
+    /// Count months on the calendar instead of as 30.44 days
+    #[serde(default)]
+    pub calendar_months: bool,
+}
+
+/// A named challenge shown alongside the main one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraChallengeConfig {
+    pub name: String,
+
+    #[serde(default = "default_years")]
+    pub years: i64,
+
+    #[serde(default = "default_months")]
+    pub months: i64,
+
+    /// When this challenge started, "YYYY-MM-DD"; the install date if unset
+    #[serde(default)]
+    pub start_date: Option<String>,
+}
+
+/// A date to count down to, shown under the usage bars
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountdownConfig {
+    pub name: String,
+
+    /// Target date, "YYYY-MM-DD"
+    pub date: String,
+
+    /// Where the progress bar starts, "YYYY-MM-DD"; the install date if unset
+    #[serde(default)]
+    pub start: Option<String>,
+}
+
+/// Configuration for the logo display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogoConfig {
+    #[serde(default)]
+    pub custom_path: String,
+
+    #[serde(default)]
+    pub width: Option<u32>,
+
+    #[serde(default)]
+    pub height: Option<u32>,
+
+    /// Graphics protocol: "auto" probes the terminal for it, or force "kitty",
+    /// "sixel", "iterm" or "blocks"; "none" draws no logo at all
+    #[serde(default = "default_logo_backend")]
+    pub backend: String,
+
+    /// "top", "left" or "right" of the text (challenge mode always uses "top"),
+    /// or "none" to hide it
+    #[serde(default = "default_logo_position")]
+    pub position: String,
+
+    /// Columns to shift the logo by, negative moves it left
+    #[serde(default)]
+    pub x_offset: i32,
+
+    /// Rows to shift the logo by, negative moves it up
+    #[serde(default)]
+    pub y_offset: i32,
+
+    /// Recolor the logo: "#rrggbb", a color name, or "accent"
+    #[serde(default)]
+    pub tint: Option<String>,
+
+    /// Extra distro name fragment to logo file mappings, checked before the
+    /// built-in ones; files are looked up in the logo directory
+    #[serde(default)]
+    pub map: BTreeMap<String, String>,
+
+    /// Overrides used in normal mode, `[logo.normal]`
+    #[serde(default)]
+    pub normal: LogoOverride,
+
+    /// Overrides used in challenge mode, `[logo.challenge]`, where the box
+    /// layout leaves a different amount of room
+    #[serde(default)]
+    pub challenge: LogoOverride,
+}
+
+/// Logo settings for one mode; those that are set replace `[logo]`'s
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogoOverride {
+    #[serde(default)]
+    pub custom_path: Option<String>,
+
+    #[serde(default)]
+    pub width: Option<u32>,
+
+    #[serde(default)]
+    pub height: Option<u32>,
+
+    #[serde(default)]
+    pub x_offset: Option<i32>,
+
+    #[serde(default)]
+    pub y_offset: Option<i32>,
+
+    #[serde(default)]
+    pub tint: Option<String>,
+}
+
+impl LogoConfig {
+    /// Whether the logo is turned off, by its position or its backend
+    pub fn hidden(&self) -> bool {
+        self.position == "none" || self.backend == "none"
+    }
+
+    /// Apply the `[logo.normal]` or `[logo.challenge]` overrides
+    pub fn use_mode(&mut self, challenge: bool) {
+        let mode = if challenge {
+            self.challenge.clone()
+        } else {
+            self.normal.clone()
+        };
+        if let Some(path) = mode.custom_path {
+            self.custom_path = path;
+        }
+        if mode.width.is_some() {
+            self.width = mode.width;
+        }
+        if mode.height.is_some() {
+            self.height = mode.height;
+        }
+        if let Some(x_offset) = mode.x_offset {
+            self.x_offset = x_offset;
+        }
+        if let Some(y_offset) = mode.y_offset {
+            self.y_offset = y_offset;
+        }
+        if mode.tint.is_some() {
+            self.tint = mode.tint;
+        }
+    }
+}
+
+/// Configuration for custom scripts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptsConfig {
+    #[serde(default)]
+    pub pre_fetch: String,
+
+    #[serde(default)]
+    pub post_fetch: String,
+
+    /// Runs once when the challenge is completed
+    #[serde(default)]
+    pub on_challenge_complete: String,
+
+    /// Runs once per milestone reached, with it in $HUGINN_MILESTONE
+    #[serde(default)]
+    pub on_milestone: String,
+}
+
+/// Configuration for the one-line status bar formats (polybar, i3blocks, tmux)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusConfig {
+    /// Info field names plus "uptime", "cpu_usage", "ram_usage", "disk_usage" and "challenge"
+    #[serde(default = "default_status_fields")]
+    pub fields: Vec<String>,
+
+    #[serde(default = "default_status_separator")]
+    pub separator: String,
+}
+
+/// Configuration for the shell prompt segment, `--format prompt`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptConfig {
+    /// Same field names as [status]
+    #[serde(default = "default_prompt_fields")]
+    pub fields: Vec<String>,
+
+    #[serde(default = "default_prompt_separator")]
+    pub separator: String,
+
+    /// Shown before each field's value, "" for none
+    #[serde(default = "default_prompt_icons")]
+    pub icons: BTreeMap<String, String>,
+
+    /// "bash" or "zsh" escape what their PS1 would expand; "none" for
+    /// starship and fish, which print the text as is
+    #[serde(default = "default_prompt_escape")]
+    pub escape: String,
+}
+
+/// Configuration for the per-run metrics history used by `huginn history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Entries older than this many days are dropped
+    #[serde(default = "default_history_keep_days")]
+    pub keep_days: i64,
+}
+
+/// Time limits for the info collectors, so a hung command can't stall the fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectorsConfig {
+    #[serde(default = "default_collector_timeout_ms")]
+    pub timeout_ms: u64,
+
+    /// Per-collector overrides keyed by field name, e.g. packages = "500ms"
+    #[serde(default)]
+    pub timeouts: HashMap<String, Millis>,
+
+    /// Shown in place of a field whose collector timed out; empty leaves it out
+    #[serde(default = "default_timeout_placeholder")]
+    pub placeholder: String,
+
+    /// How long the cached home directory size is shown before it is
+    /// measured again in the background
+    #[serde(default = "default_home_ttl")]
+    pub home_ttl: Millis,
+}
+
+/// How many collectors run at once, and which go first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    /// Worker threads for the collectors; 0 uses one per CPU core
+    #[serde(default)]
+    pub threads: usize,
+
+    /// Collectors that get a worker first, by field or plugin name; the rest
+    /// follow in the order they are shown, plugins last
+    #[serde(default = "default_priority")]
+    pub priority: Vec<String>,
+}
+
+/// A duration in milliseconds, written as a number or as "500ms", "2s", "1m",
+/// "6h" or "7d"
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(transparent)]
+pub struct Millis(pub u64);
+
+impl<'de> Deserialize<'de> for Millis {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(ms) => Ok(Millis(ms)),
+            Raw::Text(text) => parse_millis(&text)
+                .map(Millis)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {}", text))),
+        }
+    }
+}
+
+/// Milliseconds in "500ms", "2s", "1.5s", "1m", "6h" or "7d"
+fn parse_millis(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let scale = match unit.trim() {
+        "ms" | "" => 1.0,
+        "s" => 1000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        _ => return None,
+    };
+    Some((number * scale).round() as u64)
+}
+
+/// The border drawn around the fetch in challenge mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxConfig {
+    /// "rounded", "square", "double", "heavy", "ascii" or "none"
+    #[serde(default = "default_box_style")]
+    pub style: String,
+
+    /// Columns between the left and right border; fits the content if unset
+    #[serde(default)]
+    pub width: Option<u16>,
+
+    /// Blank cells between the border and the content
+    #[serde(default = "default_box_padding")]
+    pub padding: u16,
+
+    /// Text set into the top border
+    #[serde(default)]
+    pub title: String,
+}
+
+/// How the label/value info lines are laid out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoConfig {
+    /// Glyph between label and value; the default needs a Nerd Font
+    #[serde(default = "default_info_separator")]
+    pub separator: String,
+
+    /// Label alignment, "right" or "left"
+    #[serde(default = "default_info_align")]
+    pub align: String,
+
+    /// Columns before the labels in the terminal layout
+    #[serde(default = "default_info_indent")]
+    pub indent: usize,
+
+    /// Built-in icons before the labels: "none", "nerd" or "ascii"
+    #[serde(default = "default_icon_style")]
+    pub icon_style: String,
+
+    /// Icons per field, overriding the built-in ones
+    #[serde(default)]
+    pub icons: BTreeMap<String, String>,
+
+    /// Label text per field, e.g. `wm = "compositor"`
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+
+    /// Label colors per field, as names or "#rrggbb"
+    #[serde(default)]
+    pub label_colors: BTreeMap<String, String>,
+
+    /// Value colors per field, as names or "#rrggbb"
+    #[serde(default)]
+    pub value_colors: BTreeMap<String, String>,
+}
+
+/// Look of the cpu/ram/disk and challenge progress bars
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarsConfig {
+    /// Number of cells in the bar, not counting brackets
+    #[serde(default = "default_bar_width")]
+    pub width: usize,
+
+    #[serde(default = "default_bar_glyph")]
+    pub fill: String,
+
+    #[serde(default = "default_bar_glyph")]
+    pub empty: String,
+
+    /// Opening and closing bracket, e.g. "[]"; empty for none
+    #[serde(default)]
+    pub brackets: String,
+
+    /// Where the percentage goes: "left", "right" or "none"
+    #[serde(default = "default_bar_percentage")]
+    pub percentage: String,
+
+    /// cpu/ram/disk colors as [percentage, color] pairs, each color applying
+    /// from its percentage up; empty keeps the built-in ones
+    #[serde(default)]
+    pub system_colors: Vec<(i32, String)>,
+
+    /// Challenge progress colors, in the same form as system_colors
+    #[serde(default)]
+    pub challenge_colors: Vec<(i32, String)>,
+
+    /// Recorded runs to draw as a sparkline after the cpu/ram/disk bars,
+    /// from the history; 0 for none
+    #[serde(default)]
+    pub sparkline: usize,
+}
+
+/// The row of colors above the greeting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorbarConfig {
+    /// "shaded", "blocks", "circles", "gradient" or "none"
+    #[serde(default = "default_colorbar_style")]
+    pub style: String,
+
+    /// Number of columns
+    #[serde(default = "default_colorbar_width")]
+    pub width: usize,
+
+    /// Colors from left to right, as names or "#rrggbb"; empty keeps the
+    /// built-in rainbow
+    #[serde(default)]
+    pub colors: Vec<String>,
+}
+
+/// A template that replaces the built-in terminal layout
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LayoutConfig {
+    /// Inline template with placeholders like `{distro}` or `{bar:cpu}`
+    #[serde(default)]
+    pub template: String,
+
+    /// Template file, used instead of `template` when set
+    #[serde(default)]
+    pub file: String,
+}
+
+/// The "Hi! user" line above the uptime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GreetingConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Greeting text, with `{user}` and `{hostname}` filled in
+    #[serde(default = "default_greeting")]
+    pub template: String,
+
+    /// Used instead of `template` from 5:00 to 12:00, if set
+    #[serde(default)]
+    pub morning: String,
+
+    /// Used instead of `template` from 12:00 to 18:00, if set
+    #[serde(default)]
+    pub afternoon: String,
+
+    /// Used instead of `template` from 18:00 to 5:00, if set
+    #[serde(default)]
+    pub evening: String,
+}
+
+/// A random quote under the info lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Quotes file with one quote per line; empty runs `fortune`
+    #[serde(default)]
+    pub file: String,
+
+    /// Columns to wrap the quote at
+    #[serde(default = "default_quote_width")]
+    pub width: usize,
+}
+
+/// A rotating tip under the info lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TipsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Show a tip once every this many runs
+    #[serde(default = "default_tips_every")]
+    pub every: usize,
+
+    /// Include huginn's own tips
+    #[serde(default = "default_true")]
+    pub builtin: bool,
+
+    /// Extra tips, one per line
+    #[serde(default)]
+    pub file: String,
+
+    /// Columns to wrap the tip at
+    #[serde(default = "default_quote_width")]
+    pub width: usize,
+}
+
+// These provide defaults if values aren't in the config file
+
+fn default_mode() -> String {
+    "normal".to_string()
+}
+
+fn default_color_depth() -> String {
+    "auto".to_string()
+}
+
+fn default_disk_mount() -> String {
+    "/".to_string()
+}
+
+fn default_packages_style() -> String {
+    "total".to_string()
+}
+
+fn default_boot_time_format() -> String {
+    "%Y-%m-%d %H:%M".to_string()
+}
+
+fn default_dotfiles_repo() -> String {
+    "~/.dotfiles".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_on_macos() -> bool {
+    cfg!(target_os = "macos")
+}
+
+fn default_logo_backend() -> String {
+    "auto".to_string()
+}
+
+fn default_logo_position() -> String {
+    "top".to_string()
+}
+
+fn default_years() -> i64 {
+    2
+}
+
+fn default_months() -> i64 {
+    0
+}
+
+fn default_status_fields() -> Vec<String> {
+    ["cpu_usage", "ram_usage", "disk_usage", "challenge"]
+        .iter()
+        .map(|f| f.to_string())
+        .collect()
+}
+
+fn default_status_separator() -> String {
+    " | ".to_string()
+}
+
+fn default_prompt_fields() -> Vec<String> {
+    vec!["challenge".to_string(), "ram_usage".to_string()]
+}
+
+fn default_prompt_separator() -> String {
+    "  ".to_string()
+}
+
+fn default_prompt_icons() -> BTreeMap<String, String> {
+    [
+        ("uptime", "\u{f017}"),
+        ("cpu_usage", "\u{f4bc}"),
+        ("ram_usage", "\u{f2db}"),
+        ("disk_usage", "\u{f0a0}"),
+        ("challenge", "\u{f252}"),
+    ]
+    .into_iter()
+    .map(|(field, icon)| (field.to_string(), format!("{} ", icon)))
+    .collect()
+}
+
+fn default_prompt_escape() -> String {
+    "none".to_string()
+}
+
+fn default_history_keep_days() -> i64 {
+    365
+}
+
+fn default_collector_timeout_ms() -> u64 {
+    2000
+}
 
-    #[serde(default = "default_true")]
-    pub kernel: bool,
+/// The logo depends on the distro
+fn default_priority() -> Vec<String> {
+    vec!["distro".to_string()]
+}
 
-    #[serde(default = "default_true")]
-    pub packages: bool,
+fn default_timeout_placeholder() -> String {
+    "(timed out)".to_string()
+}
 
-    #[serde(default = "default_true")]
-    pub shell: bool,
+fn default_home_ttl() -> Millis {
+    Millis(6 * 60 * 60 * 1000)
+}
 
-    #[serde(default = "default_true")]
-    pub term: bool,
+fn default_info_separator() -> String {
+    "\u{f444}".to_string()
+}
 
-    #[serde(default = "default_true")]
-    pub wm: bool,
+fn default_info_align() -> String {
+    "right".to_string()
+}
 
-    #[serde(default = "default_true")]
-    pub cpu: bool,
+fn default_info_indent() -> usize {
+    10
+}
 
-    #[serde(default = "default_true")]
-    pub gpu: bool,
+fn default_icon_style() -> String {
+    "none".to_string()
+}
 
-    #[serde(default = "default_true")]
-    pub theme: bool,
+fn default_bar_width() -> usize {
+    14
+}
 
-    #[serde(default = "default_true")]
-    pub nix: bool,
+fn default_bar_glyph() -> String {
+    "━".to_string()
 }
 
-/// Configuration for the challenge mode
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ChallengeConfig {
-    #[serde(default = "default_years")]
-    pub years: i64,
+fn default_bar_percentage() -> String {
+    "left".to_string()
+}
 
-    #[serde(default = "default_months")]
-    pub months: i64,
+fn default_challenge_units() -> String {
+    "hours".to_string()
 }
 
-/// Configuration for the logo display
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LogoConfig {
-    #[serde(default)]
-    pub custom_path: String,
+fn default_greeting() -> String {
+    "Hi! {user}".to_string()
+}
 
-    #[serde(default)]
-    pub width: Option<u32>,
+fn default_language() -> String {
+    "auto".to_string()
+}
 
-    #[serde(default)]
-    pub height: Option<u32>,
+fn default_quote_width() -> usize {
+    50
 }
 
-/// Configuration for custom scripts
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ScriptsConfig {
-    #[serde(default)]
-    pub pre_fetch: String,
+fn default_tips_every() -> usize {
+    3
+}
 
-    #[serde(default)]
-    pub post_fetch: String,
+fn default_share_endpoint() -> String {
+    "https://paste.rs".to_string()
 }
 
-// These provide defaults if values aren't in the config file
+fn default_share_format() -> String {
+    "plain".to_string()
+}
 
-fn default_mode() -> String {
-    "normal".to_string()
+fn default_backup_stale_after() -> Millis {
+    Millis(7 * 24 * 60 * 60 * 1000)
 }
 
-fn default_true() -> bool {
-    true
+fn default_colorbar_style() -> String {
+    "shaded".to_string()
 }
 
-fn default_years() -> i64 {
-    2
+fn default_colorbar_width() -> usize {
+    25
 }
 
-fn default_months() -> i64 {
-    0
+fn default_box_style() -> String {
+    "rounded".to_string()
+}
+
+fn default_box_padding() -> u16 {
+    1
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             display: DisplayConfig::default(),
             challenge: ChallengeConfig::default(),
             logo: LogoConfig::default(),
             scripts: ScriptsConfig::default(),
+            status: StatusConfig::default(),
+            prompt: PromptConfig::default(),
+            history: HistoryConfig::default(),
+            collectors: CollectorsConfig::default(),
+            performance: PerformanceConfig::default(),
+            outer_box: BoxConfig::default(),
+            info: InfoConfig::default(),
+            bars: BarsConfig::default(),
+            colorbar: ColorbarConfig::default(),
+            layout: LayoutConfig::default(),
+            greeting: GreetingConfig::default(),
+            quote: QuoteConfig::default(),
+            tips: TipsConfig::default(),
+            countdowns: Vec::new(),
+            locale: LocaleConfig::default(),
+            timeouts: HashMap::new(),
+            network: NetworkConfig::default(),
+            share: ShareConfig::default(),
+            backup: BackupConfig::default(),
         }
     }
 }
@@ -127,11 +1023,14 @@ impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
             mode: default_mode(),
+            color_depth: default_color_depth(),
             custom_install_date: None,
             distro: true,
+            model: default_on_macos(),
             age: true,
             kernel: true,
             packages: true,
+            packages_style: default_packages_style(),
             shell: true,
             term: true,
             wm: true,
@@ -139,6 +1038,32 @@ impl Default for DisplayConfig {
             gpu: true,
             theme: true,
             nix: true,
+            guix: true,
+            sip: default_on_macos(),
+            init: false,
+            streak: false,
+            uptime_record: false,
+            home: false,
+            dotfiles: false,
+            dotfiles_repo: default_dotfiles_repo(),
+            backup: false,
+            latency: false,
+            latency_host: String::new(),
+            dns: false,
+            bluetooth: false,
+            ssh: false,
+            users: false,
+            booted: false,
+            boot_time_format: default_boot_time_format(),
+            taint: false,
+            security: false,
+            power: false,
+            power_thresholds: Vec::new(),
+            gpu_temp: false,
+            temps: false,
+            soc: false,
+            plugins: true,
+            disk_mount: default_disk_mount(),
         }
     }
 }
@@ -148,6 +1073,13 @@ impl Default for ChallengeConfig {
         Self {
             years: default_years(),
             months: default_months(),
+            celebrate: true,
+            extra: Vec::new(),
+            start_date: None,
+            milestones: true,
+            show_weekday: false,
+            units: default_challenge_units(),
+            calendar_months: false,
         }
     }
 }
@@ -158,19 +1090,577 @@ impl Default for LogoConfig {
             custom_path: String::new(),
             width: None,
             height: None,
+            backend: default_logo_backend(),
+            position: default_logo_position(),
+            x_offset: 0,
+            y_offset: 0,
+            tint: None,
+            map: BTreeMap::new(),
+            normal: LogoOverride::default(),
+            challenge: LogoOverride::default(),
+        }
+    }
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            fields: default_status_fields(),
+            separator: default_status_separator(),
+        }
+    }
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            fields: default_prompt_fields(),
+            separator: default_prompt_separator(),
+            icons: default_prompt_icons(),
+            escape: default_prompt_escape(),
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_days: default_history_keep_days(),
+        }
+    }
+}
+
+impl Default for CollectorsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_collector_timeout_ms(),
+            timeouts: HashMap::new(),
+            placeholder: default_timeout_placeholder(),
+            home_ttl: default_home_ttl(),
+        }
+    }
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            priority: default_priority(),
+        }
+    }
+}
+
+impl Default for BoxConfig {
+    fn default() -> Self {
+        Self {
+            style: default_box_style(),
+            width: None,
+            padding: default_box_padding(),
+            title: String::new(),
+        }
+    }
+}
+
+impl Default for InfoConfig {
+    fn default() -> Self {
+        Self {
+            separator: default_info_separator(),
+            align: default_info_align(),
+            indent: default_info_indent(),
+            icon_style: default_icon_style(),
+            icons: BTreeMap::new(),
+            labels: BTreeMap::new(),
+            label_colors: BTreeMap::new(),
+            value_colors: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for BarsConfig {
+    fn default() -> Self {
+        Self {
+            width: default_bar_width(),
+            fill: default_bar_glyph(),
+            empty: default_bar_glyph(),
+            brackets: String::new(),
+            percentage: default_bar_percentage(),
+            system_colors: Vec::new(),
+            challenge_colors: Vec::new(),
+            sparkline: 0,
+        }
+    }
+}
+
+impl Default for ColorbarConfig {
+    fn default() -> Self {
+        Self {
+            style: default_colorbar_style(),
+            width: default_colorbar_width(),
+            colors: Vec::new(),
+        }
+    }
+}
+
+impl Default for GreetingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            template: default_greeting(),
+            morning: String::new(),
+            afternoon: String::new(),
+            evening: String::new(),
+        }
+    }
+}
+
+impl Default for LocaleConfig {
+    fn default() -> Self {
+        Self {
+            language: default_language(),
+        }
+    }
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: String::new(),
+            width: default_quote_width(),
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            restic_repo: String::new(),
+            borg_repo: String::new(),
+            timeshift: false,
+            file: String::new(),
+            stale_after: default_backup_stale_after(),
+        }
+    }
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: default_share_endpoint(),
+            form_field: String::new(),
+            format: default_share_format(),
+            confirm: true,
         }
     }
 }
 
-impl Default for ScriptsConfig {
+impl Default for TipsConfig {
     fn default() -> Self {
         Self {
-            pre_fetch: String::new(),
-            post_fetch: String::new(),
+            enabled: false,
+            every: default_tips_every(),
+            builtin: true,
+            file: String::new(),
+            width: default_quote_width(),
+        }
+    }
+}
+
+impl GreetingConfig {
+    /// The greeting for the current time of day as literal text and
+    /// filled-in values (`true`), or None when it is turned off
+    pub fn parts(&self, user: &str, hostname: &str) -> Option<Vec<(String, bool)>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let timed = match chrono::Local::now().hour() {
+            5..=11 => &self.morning,
+            12..=17 => &self.afternoon,
+            _ => &self.evening,
+        };
+        let mut rest = if !timed.is_empty() {
+            timed.as_str()
+        } else if self.template == default_greeting() {
+            tr(&self.template)
+        } else {
+            self.template.as_str()
+        };
+
+        let mut parts = Vec::new();
+        loop {
+            let next = [("{user}", user), ("{hostname}", hostname)]
+                .into_iter()
+                .filter_map(|(placeholder, value)| {
+                    rest.find(placeholder).map(|i| (i, placeholder, value))
+                })
+                .min();
+            let Some((start, placeholder, value)) = next else {
+                break;
+            };
+            if start > 0 {
+                parts.push((rest[..start].to_string(), false));
+            }
+            parts.push((value.to_string(), true));
+            rest = &rest[start + placeholder.len()..];
+        }
+        if !rest.is_empty() {
+            parts.push((rest.to_string(), false));
+        }
+        Some(parts)
+    }
+}
+
+impl LayoutConfig {
+    /// The configured template, if any
+    pub fn template(&self) -> Option<String> {
+        if !self.file.is_empty() {
+            let path = crate::expand_home(&self.file);
+            match fs::read_to_string(&path) {
+                Ok(template) => return Some(template),
+                Err(e) => eprintln!("Warning: Failed to read layout file {}: {}", path, e),
+            }
+        }
+
+        (!self.template.is_empty()).then(|| self.template.clone())
+    }
+}
+
+/// Labels for fields whose config name isn't how they read
+const FIELD_NAMES: [(&str, &str); 1] = [("gpu_temp", "gpu temp")];
+
+/// Built-in field icons: Nerd Font glyph and a plain ASCII stand-in
+const FIELD_ICONS: [(&str, &str, &str); 31] = [
+    ("distro", "\u{f17c}", "@"),
+    ("model", "\u{f109}", "^"),
+    ("age", "\u{f017}", "~"),
+    ("kernel", "\u{f013}", "#"),
+    ("packages", "\u{f487}", "+"),
+    ("shell", "\u{f489}", "$"),
+    ("term", "\u{f120}", ">"),
+    ("wm", "\u{f2d2}", "="),
+    ("cpu", "\u{f4bc}", "%"),
+    ("gpu", "\u{f108}", "&"),
+    ("theme", "\u{f53f}", "*"),
+    ("nix", "\u{f313}", "N"),
+    ("guix", "\u{f325}", "g"),
+    ("sip", "\u{f023}", "L"),
+    ("init", "\u{f0e7}", "I"),
+    ("home", "\u{f015}", "H"),
+    ("dotfiles", "\u{e702}", "G"),
+    ("backup", "\u{f0c7}", "B"),
+    ("latency", "\u{f0ec}", "P"),
+    ("dns", "\u{f0ac}", "D"),
+    ("bluetooth", "\u{f293}", "b"),
+    ("ssh", "\u{f489}", ">"),
+    ("users", "\u{f0c0}", "U"),
+    ("booted", "\u{f011}", "O"),
+    ("taint", "\u{f071}", "T"),
+    ("security", "\u{f132}", "S"),
+    ("power", "\u{f0e7}", "W"),
+    ("gpu_temp", "\u{f2c9}", "t"),
+    ("temps", "\u{f2c9}", "t"),
+    ("soc", "\u{f2db}", "s"),
+    ("streak", "\u{f06d}", "!"),
+];
+
+fn field_color(colors: &BTreeMap<String, String>, field: &str) -> Option<Color> {
+    let name = colors.get(field)?;
+    let color = parse_color(name);
+    if color.is_none() {
+        eprintln!("Warning: Unknown color for {}: {}", field, name);
+    }
+    color
+}
+
+/// Paint text in an optional color
+fn paint_in(text: String, color: Option<Color>) -> String {
+    match color {
+        Some(color) => paint(text.with(color)),
+        None => text,
+    }
+}
+
+impl InfoConfig {
+    /// Icon shown before a label, if any
+    fn icon(&self, label: &str) -> Option<&str> {
+        if let Some(icon) = self.icons.get(label) {
+            return Some(icon);
+        }
+
+        // The Linux console can't draw Nerd Font glyphs
+        let nerd = match self.icon_style.as_str() {
+            "nerd" => std::env::var("TERM").map_or(true, |term| term != "linux"),
+            "ascii" => false,
+            _ => return None,
+        };
+        FIELD_ICONS
+            .iter()
+            .find(|(field, _, _)| *field == label)
+            .map(|(_, glyph, ascii)| if nerd { *glyph } else { *ascii })
+    }
+
+    /// Configured label color for a field
+    pub fn label_color(&self, field: &str) -> Option<Color> {
+        field_color(&self.label_colors, field)
+    }
+
+    /// Use the colors plugins asked for, where no value color is configured
+    pub fn add_plugin_colors(&mut self, fields: &[PluginField]) {
+        for field in fields {
+            if let Some(color) = &field.color {
+                self.value_colors
+                    .entry(field.label.clone())
+                    .or_insert_with(|| color.clone());
+            }
+        }
+    }
+
+    /// Paint a field's value in `color` to flag it, unless a value color is
+    /// configured
+    pub fn add_alert_color(&mut self, field: &str, color: &str) {
+        self.value_colors
+            .entry(field.to_string())
+            .or_insert_with(|| color.to_string());
+    }
+
+    /// Configured value color for a field
+    pub fn value_color(&self, field: &str) -> Option<Color> {
+        field_color(&self.value_colors, field)
+    }
+
+    /// Custom name for a field, or the field itself
+    pub fn label_name<'a>(&'a self, label: &'a str) -> &'a str {
+        let name = FIELD_NAMES
+            .iter()
+            .find(|(field, _)| *field == label)
+            .map_or(label, |(_, name)| name);
+        self.labels
+            .get(label)
+            .map_or_else(|| tr(name), String::as_str)
+    }
+
+    /// Label as displayed, with its icon in front
+    pub fn label_text(&self, label: &str) -> String {
+        let name = self.label_name(label);
+        match self.icon(label) {
+            Some(icon) => format!("{} {}", icon, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Pad a label to `width` on the configured side
+    pub fn align_label(&self, label: &str, width: usize) -> String {
+        if self.align == "left" {
+            format!("{:<width$}", label)
+        } else {
+            format!("{:>width$}", label)
+        }
+    }
+
+    /// Label text, aligned to `width` and painted in the field's color
+    pub fn painted_label(&self, field: &str, width: usize) -> String {
+        paint_in(
+            self.align_label(&self.label_text(field), width),
+            self.label_color(field),
+        )
+    }
+
+    /// Value painted in the field's color
+    pub fn painted_value(&self, field: &str, value: &str) -> String {
+        paint_in(value.to_string(), self.value_color(field))
+    }
+
+    /// Separator followed by its trailing space, colored
+    pub fn painted_separator(&self) -> String {
+        paint(format!("{} ", self.separator).green())
+    }
+}
+
+impl CollectorsConfig {
+    /// Timeout in milliseconds for the named collector
+    pub fn timeout_for(&self, name: &str) -> u64 {
+        self.timeouts
+            .get(name)
+            .map_or(self.timeout_ms, |timeout| timeout.0)
+    }
+}
+
+impl PerformanceConfig {
+    /// The number of worker threads, at least one
+    pub fn worker_count(&self) -> usize {
+        if self.threads > 0 {
+            return self.threads;
+        }
+        std::thread::available_parallelism().map_or(4, |cores| cores.get())
+    }
+}
+
+/// Info field names that can be toggled in [display] or picked with --field
+pub const INFO_FIELDS: [&str; 31] = [
+    "distro",
+    "model",
+    "age",
+    "kernel",
+    "packages",
+    "shell",
+    "term",
+    "wm",
+    "cpu",
+    "gpu",
+    "theme",
+    "nix",
+    "guix",
+    "sip",
+    "init",
+    "home",
+    "dotfiles",
+    "backup",
+    "latency",
+    "dns",
+    "bluetooth",
+    "ssh",
+    "users",
+    "booted",
+    "taint",
+    "security",
+    "power",
+    "gpu_temp",
+    "temps",
+    "soc",
+    "streak",
+];
+
+impl DisplayConfig {
+    /// Enable only the named fields, so the rest are neither collected nor shown
+    pub fn show_only(&mut self, fields: &[String]) -> Result<(), String> {
+        if let Some(unknown) = fields.iter().find(|f| !INFO_FIELDS.contains(&f.as_str())) {
+            return Err(format!(
+                "unknown field '{}' (expected one of: {})",
+                unknown,
+                INFO_FIELDS.join(", ")
+            ));
+        }
+
+        self.show(|name| fields.iter().any(|f| f == name));
+        Ok(())
+    }
+
+    /// Enable every field and plugins, for --all
+    pub fn show_all(&mut self) {
+        self.show(|_| true);
+        self.uptime_record = true;
+        self.plugins = true;
+    }
+
+    /// Enable the fields `wanted` accepts and disable the rest, plugins included
+    fn show(&mut self, wanted: impl Fn(&str) -> bool) {
+        for (name, shown) in self.flags_mut() {
+            *shown = wanted(name);
         }
+        self.plugins = false;
+    }
+
+    /// Flip whether an info field is shown, returning its new state
+    pub fn toggle(&mut self, name: &str) -> Option<bool> {
+        let (_, shown) = self
+            .flags_mut()
+            .into_iter()
+            .find(|(field, _)| *field == name)?;
+        *shown = !*shown;
+        Some(*shown)
+    }
+
+    /// The info fields that are shown, in INFO_FIELDS order
+    pub fn shown_fields(&mut self) -> Vec<&'static str> {
+        self.flags_mut()
+            .into_iter()
+            .filter(|(_, shown)| **shown)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Each info field's name and show flag
+    fn flags_mut(&mut self) -> [(&'static str, &mut bool); INFO_FIELDS.len()] {
+        [
+            ("distro", &mut self.distro),
+            ("model", &mut self.model),
+            ("age", &mut self.age),
+            ("kernel", &mut self.kernel),
+            ("packages", &mut self.packages),
+            ("shell", &mut self.shell),
+            ("term", &mut self.term),
+            ("wm", &mut self.wm),
+            ("cpu", &mut self.cpu),
+            ("gpu", &mut self.gpu),
+            ("theme", &mut self.theme),
+            ("nix", &mut self.nix),
+            ("guix", &mut self.guix),
+            ("sip", &mut self.sip),
+            ("init", &mut self.init),
+            ("home", &mut self.home),
+            ("dotfiles", &mut self.dotfiles),
+            ("backup", &mut self.backup),
+            ("latency", &mut self.latency),
+            ("dns", &mut self.dns),
+            ("bluetooth", &mut self.bluetooth),
+            ("ssh", &mut self.ssh),
+            ("users", &mut self.users),
+            ("booted", &mut self.booted),
+            ("taint", &mut self.taint),
+            ("security", &mut self.security),
+            ("power", &mut self.power),
+            ("gpu_temp", &mut self.gpu_temp),
+            ("temps", &mut self.temps),
+            ("soc", &mut self.soc),
+            ("streak", &mut self.streak),
+        ]
     }
 }
 
+impl Config {
+    /// The --minimal preset: distro, kernel, uptime and the cpu/ram/disk bars,
+    /// without a logo, colorbar or anything else
+    pub fn minimal(&mut self) {
+        self.display
+            .show(|name| matches!(name, "distro" | "kernel"));
+        self.display.mode = "normal".to_string();
+        self.display.uptime_record = false;
+        self.logo.position = "none".to_string();
+        self.colorbar.style = "none".to_string();
+        self.greeting.enabled = false;
+        self.quote.enabled = false;
+        self.tips.enabled = false;
+        self.countdowns.clear();
+        self.layout = LayoutConfig::default();
+    }
+}
+
+/// The user's home directory; Windows sets USERPROFILE instead of HOME
+pub fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
+
+/// huginn's directory under XDG cache home, for things that can be rebuilt
+pub fn cache_dir() -> PathBuf {
+    let home = home_dir().unwrap_or_default();
+    let cache_home = std::env::var("XDG_CACHE_HOME").unwrap_or_else(|_| format!("{}/.cache", home));
+
+    PathBuf::from(cache_home).join("huginn")
+}
+
+/// huginn's directory under XDG data home, holding logos and history
+pub fn data_dir() -> PathBuf {
+    let home = home_dir().unwrap_or_default();
+    let data_home =
+        std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
+
+    PathBuf::from(data_home).join("huginn")
+}
+
 // Config loading function
 
 impl Config {
@@ -181,8 +1671,15 @@ impl Config {
         // Try to find existing config file
         if let Some(config_path) = Self::find_config_file() {
             // Config exists, try to read and parse it
-            if let Ok(contents) = fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str::<Config>(&contents) {
+            if let Ok(mut contents) = fs::read_to_string(&config_path) {
+                if let Some(upgraded) = Self::upgrade_file(&config_path, &contents) {
+                    contents = upgraded;
+                }
+                if let Ok((config, unknown)) = Self::parse_checked(&contents) {
+                    debug!("loaded config from {}", config_path.display());
+                    for key in unknown {
+                        eprintln!("Warning: Unknown key {} in {}", key, config_path.display());
+                    }
                     return config;
                 } else {
                     eprintln!(
@@ -195,6 +1692,7 @@ impl Config {
             }
         } else {
             // Config doesn't exist - this is first run!
+            debug!("no config file found, writing the default one");
             Self::create_default_config_silently();
         }
 
@@ -202,9 +1700,63 @@ impl Config {
         Config::default()
     }
 
+    /// Parse the contents of a config file
+    pub fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        Self::parse_checked(contents).map(|(config, _)| config)
+    }
+
+    /// Parse a config file, along with the keys in it huginn doesn't know,
+    /// like "box.widht"
+    fn parse_checked(contents: &str) -> Result<(Self, Vec<String>), toml::de::Error> {
+        let mut unknown = Vec::new();
+        let mut config: Config =
+            serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+                unknown.push(path.to_string())
+            })?;
+        let timeouts = std::mem::take(&mut config.timeouts);
+        config.collectors.timeouts.extend(timeouts);
+        Ok((config, unknown))
+    }
+
+    /// Upgrade a config written for an older huginn in place, keeping the
+    /// original next to it as config.toml.v<version>.bak
+    /// The upgraded contents are returned even if they can't be saved
+    /// Symlinked and read-only configs are only upgraded in memory, since
+    /// saving would replace the link with a plain file
+    fn upgrade_file(path: &Path, contents: &str) -> Option<String> {
+        let (from, upgraded) = migrate::upgrade(contents)?;
+        let in_place = fs::symlink_metadata(path)
+            .is_ok_and(|meta| !meta.file_type().is_symlink() && !meta.permissions().readonly());
+        if !in_place {
+            eprintln!(
+                "Note: {} is for config version {}; it was upgraded to {} for this run but left as it is, since it is a symlink or read-only",
+                path.display(),
+                from,
+                migrate::CONFIG_VERSION
+            );
+            return Some(upgraded);
+        }
+        let backup = PathBuf::from(format!("{}.v{}.bak", path.display(), from));
+        let saved = fs::copy(path, &backup).and_then(|_| crate::write_atomically(path, &upgraded));
+        match saved {
+            Ok(()) => eprintln!(
+                "Note: Upgraded {} to config version {}, the old file is at {}",
+                path.display(),
+                migrate::CONFIG_VERSION,
+                backup.display()
+            ),
+            Err(e) => eprintln!(
+                "Warning: Could not upgrade config file at {}: {}",
+                path.display(),
+                e
+            ),
+        }
+        Some(upgraded)
+    }
+
     /// Silently create default config on first run
     fn create_default_config_silently() {
-        if let Ok(home) = std::env::var("HOME") {
+        if let Some(home) = home_dir() {
             let config_path = PathBuf::from(format!("{}/.config/huginn/config.toml", home));
 
             // Only create if it truly doesn't exist
@@ -221,10 +1773,11 @@ impl Config {
         }
     }
     /// Find the config file in standard locations
-    /// Checks in order: ~/.config/huginn/config.toml, ~/.huginn.toml
-    fn find_config_file() -> Option<PathBuf> {
+    /// Checks in order: ~/.config/huginn/config.toml, ~/.huginn.toml, and on
+    /// macOS ~/Library/Application Support/huginn/config.toml
+    pub fn find_config_file() -> Option<PathBuf> {
         // Try XDG config directory first
-        if let Ok(home) = std::env::var("HOME") {
+        if let Some(home) = home_dir() {
             let xdg_config = PathBuf::from(format!("{}/.config/huginn/config.toml", home));
             if xdg_config.exists() {
                 return Some(xdg_config);
@@ -235,6 +1788,17 @@ impl Config {
             if home_config.exists() {
                 return Some(home_config);
             }
+
+            // Where macOS apps usually keep their settings
+            if cfg!(target_os = "macos") {
+                let app_support = PathBuf::from(format!(
+                    "{}/Library/Application Support/huginn/config.toml",
+                    home
+                ));
+                if app_support.exists() {
+                    return Some(app_support);
+                }
+            }
         }
 
         None
@@ -259,7 +1823,7 @@ impl Config {
 
     /// Generate a default config file at ~/.config/huginn/config.toml
     pub fn generate_default_config() -> Result<(), Box<dyn std::error::Error>> {
-        let home = std::env::var("HOME")?;
+        let home = home_dir().ok_or("neither HOME nor USERPROFILE is set")?;
         let config_path = PathBuf::from(format!("{}/.config/huginn/config.toml", home));
 
         let default_config = Config::default();
@@ -0,0 +1,147 @@
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::reload::ConfigWatcher;
+use std::io;
+use std::time::Duration;
+#[cfg(unix)]
+use {
+    crate::protocol::{read_message, socket_path, write_message, Request, Response},
+    std::fs,
+    std::io::BufReader,
+    std::os::unix::fs::PermissionsExt,
+    std::os::unix::net::{UnixListener, UnixStream},
+    std::sync::{Arc, RwLock},
+    std::thread,
+};
+
+#[cfg(unix)]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Keep a warm fetch in memory and answer clients on the Unix socket,
+/// collecting again right away when the config file changes
+#[cfg(unix)]
+pub fn run(
+    mut config: Config,
+    interval: Duration,
+    watcher: Option<ConfigWatcher>,
+) -> io::Result<()> {
+    let path = socket_path();
+
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("a daemon is already listening on {}", path.display()),
+            ));
+        }
+        // Left behind by a daemon that didn't shut down cleanly
+        fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    let fetch = Arc::new(RwLock::new(Fetch::collect(&config)));
+    {
+        let fetch = Arc::clone(&fetch);
+        thread::spawn(move || loop {
+            let changed = ConfigWatcher::sleep(watcher.as_ref(), interval);
+            if let Some(watcher) = watcher.as_ref().filter(|_| changed) {
+                match watcher.reload() {
+                    Ok(reloaded) => {
+                        println!("reloaded the config");
+                        config = reloaded;
+                    }
+                    Err(e) => eprintln!("Warning: could not reload the config: {}", e),
+                }
+            }
+            let fresh = Fetch::collect(&config);
+            if let Ok(mut current) = fetch.write() {
+                *current = fresh;
+            }
+        });
+    }
+
+    println!("huginn daemon listening on {}", path.display());
+
+    for stream in listener.incoming().flatten() {
+        let fetch = Arc::clone(&fetch);
+        thread::spawn(move || {
+            let _ = handle_client(stream, &fetch);
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn handle_client(stream: UnixStream, fetch: &RwLock<Fetch>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let response = match read_message::<Request>(&mut reader) {
+        Ok(Request::Ping) => Response::Pong,
+        Ok(Request::Fetch) => match fetch.read() {
+            Ok(current) => {
+                let mut current = current.clone();
+                current.refresh_uptime();
+                Response::Fetch {
+                    fetch: Box::new(current),
+                }
+            }
+            Err(_) => Response::Error {
+                message: "fetch lock poisoned".to_string(),
+            },
+        },
+        Err(e) => Response::Error {
+            message: e.to_string(),
+        },
+    };
+
+    write_message(&mut writer, &response)
+}
+
+/// Ask a running daemon for its fetch; None when no daemon is reachable
+#[cfg(unix)]
+pub fn query() -> Option<Fetch> {
+    let path = socket_path();
+    let stream = match UnixStream::connect(&path) {
+        Ok(stream) => stream,
+        Err(e) => {
+            tracing::debug!("no daemon at {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+
+    let mut writer = stream.try_clone().ok()?;
+    write_message(&mut writer, &Request::Fetch).ok()?;
+
+    match read_message(&mut BufReader::new(stream)).ok()? {
+        Response::Fetch { fetch } => {
+            tracing::debug!("fetch answered by the daemon");
+            Some(*fetch)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn run(
+    _config: Config,
+    _interval: Duration,
+    _watcher: Option<ConfigWatcher>,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the daemon needs Unix sockets, which this platform lacks",
+    ))
+}
+
+/// Without Unix sockets there is never a daemon to ask
+#[cfg(not(unix))]
+pub fn query() -> Option<Fetch> {
+    None
+}
@@ -0,0 +1,100 @@
+use crate::config::Config;
+use crate::fetch::Snapshot;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use zbus::{blocking::connection, fdo, interface};
+
+pub const BUS_NAME: &str = "org.huginn.Fetch";
+const OBJECT_PATH: &str = "/org/huginn/Fetch";
+
+struct FetchService {
+    config: Arc<Config>,
+    challenge: Option<(i64, i64)>,
+    snapshot: Arc<RwLock<Snapshot>>,
+}
+
+impl FetchService {
+    fn current(&self) -> fdo::Result<Snapshot> {
+        self.snapshot
+            .read()
+            .map(|snapshot| snapshot.clone())
+            .map_err(|_| fdo::Error::Failed("snapshot lock poisoned".to_string()))
+    }
+}
+
+#[interface(name = "org.huginn.Fetch")]
+impl FetchService {
+    /// Every field as a name to value map
+    fn get_all(&self) -> fdo::Result<HashMap<String, String>> {
+        Ok(self.current()?.fields().into_iter().collect())
+    }
+
+    /// A single field, e.g. "kernel" or "cpu_usage"
+    fn get(&self, field: &str) -> fdo::Result<String> {
+        self.current()?
+            .fields()
+            .into_iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown field: {}", field)))
+    }
+
+    /// Names of the fields currently available
+    fn fields(&self) -> fdo::Result<Vec<String>> {
+        Ok(self
+            .current()?
+            .fields()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// The full snapshot as JSON, in the same shape as `huginn serve` /json
+    fn get_json(&self) -> fdo::Result<String> {
+        serde_json::to_string(&self.current()?).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Run the collectors now instead of waiting for the next refresh
+    fn refresh(&self) -> fdo::Result<()> {
+        let fresh = Snapshot::collect(&self.config, self.challenge);
+        let mut current = self
+            .snapshot
+            .write()
+            .map_err(|_| fdo::Error::Failed("snapshot lock poisoned".to_string()))?;
+        *current = fresh;
+        Ok(())
+    }
+}
+
+/// Register org.huginn.Fetch on the session bus and serve it until killed
+pub fn serve(
+    config: Config,
+    challenge: Option<(i64, i64)>,
+    interval: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Arc::new(config);
+    let snapshot = Arc::new(RwLock::new(Snapshot::collect(&config, challenge)));
+
+    let service = FetchService {
+        config: Arc::clone(&config),
+        challenge,
+        snapshot: Arc::clone(&snapshot),
+    };
+
+    let _connection = connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, service)?
+        .build()?;
+
+    println!("Registered {} on the session bus", BUS_NAME);
+
+    loop {
+        thread::sleep(interval);
+        let fresh = Snapshot::collect(&config, challenge);
+        if let Ok(mut current) = snapshot.write() {
+            *current = fresh;
+        }
+    }
+}
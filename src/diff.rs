@@ -0,0 +1,89 @@
+use crate::color::paint;
+use crate::fetch::Snapshot;
+use crossterm::style::Stylize;
+use std::fs;
+use std::path::Path;
+
+/// Compare two saved snapshots and print the fields that changed
+pub fn run_diff(old_path: &Path, new_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let old = load_snapshot(old_path)?;
+    let new = load_snapshot(new_path)?;
+
+    let old_fields = tracked_fields(&old);
+    let new_fields = tracked_fields(&new);
+
+    let mut names: Vec<&str> = old_fields.iter().map(|(name, _)| name.as_str()).collect();
+    for (name, _) in &new_fields {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+
+    let lookup = |fields: &[(String, String)], name: &str| {
+        fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+    };
+
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+    let mut unchanged = 0;
+
+    for name in &names {
+        let before = lookup(&old_fields, name);
+        let after = lookup(&new_fields, name);
+        if before == after {
+            unchanged += 1;
+            continue;
+        }
+
+        let before = before.unwrap_or_else(|| "(none)".to_string());
+        let after = after.unwrap_or_else(|| "(none)".to_string());
+        let delta = match (before.parse::<i64>(), after.parse::<i64>()) {
+            (Ok(b), Ok(a)) => format!(" ({:+})", a - b),
+            _ => String::new(),
+        };
+
+        println!(
+            "{:>width$}  {} → {}{}",
+            name,
+            paint(before.as_str().red()),
+            paint(after.as_str().green()),
+            delta,
+            width = width
+        );
+    }
+
+    if unchanged == names.len() {
+        println!("No changes");
+    } else {
+        println!("\n{} fields unchanged", unchanged);
+    }
+
+    Ok(())
+}
+
+fn load_snapshot(path: &Path) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a huginn snapshot: {}", path.display(), e).into())
+}
+
+/// Fields worth comparing over time; momentary load and uptime are left out
+fn tracked_fields(snapshot: &Snapshot) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = snapshot
+        .fetch
+        .info
+        .fields()
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    fields.push(("user".to_string(), snapshot.fetch.user.clone()));
+    fields.push((
+        "disk_usage".to_string(),
+        snapshot.fetch.disk_usage.to_string(),
+    ));
+    fields
+}
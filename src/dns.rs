@@ -0,0 +1,108 @@
+// The `dns` field: the DNS servers in use, like "1.1.1.1, 9.9.9.9 · DoT"
+// When /etc/resolv.conf only points at systemd-resolved's local stub, the
+// real servers, and whether DNS over TLS and DNSSEC are on, come from
+// resolved itself
+use crate::error::CollectError;
+use std::fs;
+
+/// Where systemd-resolved listens for the resolv.conf it manages
+#[cfg(target_os = "linux")]
+const RESOLVED_STUBS: [&str; 2] = ["127.0.0.53", "127.0.0.54"];
+
+pub fn servers() -> Result<String, CollectError> {
+    let servers = nameservers("/etc/resolv.conf");
+
+    #[cfg(target_os = "linux")]
+    if !servers.is_empty() && servers.iter().all(|s| RESOLVED_STUBS.contains(&s.as_str())) {
+        // Without D-Bus, resolved still writes its upstream servers here
+        return resolved::status().or_else(|_| {
+            let upstream = nameservers("/run/systemd/resolve/resolv.conf");
+            summarize(&upstream, &[])
+        });
+    }
+    summarize(&servers, &[])
+}
+
+/// Servers and the security features that are on, like "DoT"
+fn summarize(servers: &[String], features: &[String]) -> Result<String, CollectError> {
+    if servers.is_empty() {
+        return Err(CollectError::Failed(
+            "no DNS servers configured".to_string(),
+        ));
+    }
+    let mut unique: Vec<&str> = Vec::new();
+    for server in servers {
+        if !unique.contains(&server.as_str()) {
+            unique.push(server);
+        }
+    }
+    let mut parts = vec![unique.join(", ")];
+    parts.extend(features.iter().cloned());
+    Ok(parts.join(" · "))
+}
+
+/// The `nameserver` lines of a resolv.conf
+fn nameservers(path: &str) -> Vec<String> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            if words.next()? != "nameserver" {
+                return None;
+            }
+            words.next().map(str::to_string)
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod resolved {
+    use super::summarize;
+    use crate::error::CollectError;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use zbus::blocking::{Connection, Proxy};
+
+    fn failed(error: zbus::Error) -> CollectError {
+        CollectError::Failed(error.to_string())
+    }
+
+    /// Servers of every link, from org.freedesktop.resolve1
+    pub fn status() -> Result<String, CollectError> {
+        let connection = Connection::system().map_err(failed)?;
+        let manager = Proxy::new(
+            &connection,
+            "org.freedesktop.resolve1",
+            "/org/freedesktop/resolve1",
+            "org.freedesktop.resolve1.Manager",
+        )
+        .map_err(failed)?;
+
+        // Interface index, address family and the address's bytes
+        let dns: Vec<(i32, i32, Vec<u8>)> = manager.get_property("DNS").map_err(failed)?;
+        let servers: Vec<String> = dns
+            .into_iter()
+            .filter_map(|(_, _, address)| match address.len() {
+                4 => Some(Ipv4Addr::from(<[u8; 4]>::try_from(address).ok()?).to_string()),
+                16 => Some(Ipv6Addr::from(<[u8; 16]>::try_from(address).ok()?).to_string()),
+                _ => None,
+            })
+            .collect();
+
+        // "yes", "no", "opportunistic" or "allow-downgrade"
+        let mut features = Vec::new();
+        let dot: String = manager.get_property("DNSOverTLS").map_err(failed)?;
+        match dot.as_str() {
+            "yes" => features.push("DoT".to_string()),
+            "opportunistic" => features.push("opportunistic DoT".to_string()),
+            _ => {}
+        }
+        let dnssec: String = manager.get_property("DNSSEC").map_err(failed)?;
+        match dnssec.as_str() {
+            "yes" => features.push("DNSSEC".to_string()),
+            "allow-downgrade" => features.push("DNSSEC if supported".to_string()),
+            _ => {}
+        }
+        summarize(&servers, &features)
+    }
+}
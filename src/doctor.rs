@@ -0,0 +1,420 @@
+// `huginn doctor`: check the environment and suggest fixes
+use crate::color::paint;
+use crate::config::{data_dir, Config};
+use crate::fetch::Fetch;
+use crate::graphics;
+use crate::logo;
+#[cfg(target_os = "linux")]
+use crate::sbc;
+use crossterm::style::Stylize;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+
+const BACKENDS: [&str; 6] = ["auto", "kitty", "sixel", "iterm", "blocks", "none"];
+
+/// A tool huginn runs: its name, whether the config has it run, what goes
+/// missing without it and how to get it
+type Tool = (
+    &'static str,
+    fn(&Config) -> bool,
+    &'static str,
+    &'static str,
+);
+
+/// External programs huginn runs, checked only when a shown field needs them
+const TOOLS: &[Tool] = &[
+    #[cfg(target_os = "linux")]
+    (
+        "lspci",
+        |config| config.display.gpu,
+        "the gpu field stays empty",
+        "install pciutils",
+    ),
+    ("ssh", |_| true, "--remote can't connect", "install OpenSSH"),
+    (
+        "git",
+        |config| config.display.dotfiles,
+        "the dotfiles field stays empty",
+        "install git",
+    ),
+    (
+        "ping",
+        |config| config.display.latency,
+        "the latency field stays empty",
+        "install iputils",
+    ),
+    (
+        "restic",
+        |config| config.display.backup && !config.backup.restic_repo.is_empty(),
+        "restic_repo under [backup] can't be read",
+        "install restic",
+    ),
+    (
+        "borg",
+        |config| config.display.backup && !config.backup.borg_repo.is_empty(),
+        "borg_repo under [backup] can't be read",
+        "install borgbackup",
+    ),
+    (
+        "timeshift",
+        |config| config.display.backup && config.backup.timeshift,
+        "snapshots are only found while the backup device is mounted",
+        "install timeshift",
+    ),
+    (
+        "who",
+        |config| config.display.ssh || config.display.users,
+        "the ssh and users fields stay empty",
+        "install coreutils",
+    ),
+    // Only the proprietary driver needs it
+    #[cfg(target_os = "linux")]
+    (
+        "nvidia-smi",
+        |config| config.display.gpu_temp && Path::new("/proc/driver/nvidia").exists(),
+        "the gpu_temp field stays empty",
+        "install the NVIDIA driver's utilities",
+    ),
+    #[cfg(target_os = "linux")]
+    (
+        "vcgencmd",
+        |config| {
+            config.display.soc
+                && sbc::board().is_some_and(|board| board.starts_with("Raspberry Pi"))
+        },
+        "the soc field can't tell whether the Pi is throttled",
+        "install libraspberrypi-bin or raspi-utils",
+    ),
+    // apt-get and dnf answer for the other distros
+    #[cfg(target_os = "linux")]
+    (
+        "arch-audit",
+        |config| config.display.security && which::which("pacman").is_ok(),
+        "the security field stays empty",
+        "install arch-audit",
+    ),
+];
+
+/// --copy uses the first of these that runs under the current display server
+#[cfg(target_os = "linux")]
+const CLIPBOARD_TOOLS: [&str; 3] = ["wl-copy", "xclip", "xsel"];
+
+enum Outcome {
+    Ok(String),
+    /// Something optional is missing: what, and how to fix it
+    Warn(String, String),
+    /// Something is broken: what, and how to fix it
+    Fail(String, String),
+}
+
+struct Check {
+    name: String,
+    outcome: Outcome,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, outcome: Outcome) -> Self {
+        Self {
+            name: name.into(),
+            outcome,
+        }
+    }
+}
+
+/// Run every check and print the results with fixes
+pub fn run(config: &Config) {
+    let mut checks = vec![check_config(config)];
+    checks.push(check_graphics(config));
+    checks.extend(check_tools(config));
+    #[cfg(target_os = "linux")]
+    checks.push(check_clipboard());
+    #[cfg(target_os = "linux")]
+    checks.push(check_updates());
+    checks.extend(check_logos(config));
+    checks.push(check_writable("cache", &logo::cache_dir()));
+    checks.push(check_cache());
+    checks.push(check_writable("data", &data_dir()));
+
+    let mut problems = 0;
+    for check in &checks {
+        let (mark, detail, fix) = match &check.outcome {
+            Outcome::Ok(detail) => (paint("✓".green()), detail, None),
+            Outcome::Warn(detail, fix) => (paint("!".yellow().bold()), detail, Some(fix)),
+            Outcome::Fail(detail, fix) => (paint("✗".red().bold()), detail, Some(fix)),
+        };
+        println!("{} {:<10} {}", mark, check.name, detail);
+        if let Some(fix) = fix {
+            println!("  {} {}", paint("→".dark_grey()), fix);
+            problems += 1;
+        }
+    }
+
+    println!();
+    match problems {
+        0 => println!("Everything looks fine."),
+        1 => println!("1 thing to look at."),
+        n => println!("{} things to look at.", n),
+    }
+    println!("Run `huginn --verbose` to see which collectors fail, and why.");
+}
+
+fn check_config(config: &Config) -> Check {
+    let Some(path) = Config::find_config_file() else {
+        return Check::new(
+            "config",
+            Outcome::Warn(
+                "no config file, using the defaults".to_string(),
+                "run `huginn --generate-config` to create one".to_string(),
+            ),
+        );
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            return Check::new(
+                "config",
+                Outcome::Fail(
+                    format!("can't read {}: {}", path.display(), e),
+                    "check the file's permissions".to_string(),
+                ),
+            )
+        }
+    };
+    if let Err(e) = toml::from_str::<Config>(&contents) {
+        let line = e
+            .span()
+            .map_or(0, |span| contents[..span.start].lines().count().max(1));
+        return Check::new(
+            "config",
+            Outcome::Fail(
+                format!(
+                    "{} line {}: {}",
+                    path.display(),
+                    line,
+                    e.message().replace('\n', ", ")
+                ),
+                "fix the syntax, or run `huginn --generate-config` to start over".to_string(),
+            ),
+        );
+    }
+    if !BACKENDS.contains(&config.logo.backend.as_str()) {
+        return Check::new(
+            "config",
+            Outcome::Fail(
+                format!("unknown logo backend {:?}", config.logo.backend),
+                format!("set [logo] backend to one of {}", BACKENDS.join(", ")),
+            ),
+        );
+    }
+    if !config.logo.custom_path.is_empty()
+        && !Path::new(&crate::expand_home(&config.logo.custom_path)).exists()
+    {
+        return Check::new(
+            "config",
+            Outcome::Fail(
+                format!("custom logo {} doesn't exist", config.logo.custom_path),
+                "fix [logo] custom_path, or empty it to use the distro logo".to_string(),
+            ),
+        );
+    }
+    Check::new("config", Outcome::Ok(path.display().to_string()))
+}
+
+fn check_graphics(config: &Config) -> Check {
+    if !std::io::stdout().is_terminal() {
+        return Check::new(
+            "graphics",
+            Outcome::Warn(
+                "not running in a terminal, so it can't be asked".to_string(),
+                "run `huginn doctor` directly in your terminal".to_string(),
+            ),
+        );
+    }
+
+    let protocols = logo::graphics_protocols();
+    let backend = config.logo.backend.as_str();
+    if protocols.is_empty() {
+        return Check::new(
+            "graphics",
+            Outcome::Warn(
+                "no image protocol found, logos are drawn with unicode blocks".to_string(),
+                if graphics::tmux_blocks_passthrough() {
+                    "add `set -g allow-passthrough on` to tmux.conf".to_string()
+                } else {
+                    "use a terminal with kitty, iTerm2 or sixel graphics, such as kitty, WezTerm, foot or Konsole".to_string()
+                },
+            ),
+        );
+    }
+    let supported = protocols.join(", ");
+    if !matches!(backend, "auto" | "blocks" | "none") && !protocols.contains(&backend) {
+        return Check::new(
+            "graphics",
+            Outcome::Warn(
+                format!(
+                    "[logo] backend is {}, but this terminal supports {}",
+                    backend, supported
+                ),
+                "set [logo] backend to \"auto\"".to_string(),
+            ),
+        );
+    }
+    Check::new(
+        "graphics",
+        Outcome::Ok(format!(
+            "supports {}, auto uses {}",
+            supported,
+            graphics::detect()
+        )),
+    )
+}
+
+fn check_tools(config: &Config) -> Vec<Check> {
+    TOOLS
+        .iter()
+        .filter(|(_, needed, _, _)| needed(config))
+        .map(|(tool, _, missing, fix)| {
+            let outcome = match which::which(tool) {
+                Ok(path) => Outcome::Ok(path.display().to_string()),
+                Err(_) => Outcome::Warn(format!("not installed, so {}", missing), fix.to_string()),
+            };
+            Check::new(*tool, outcome)
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn check_clipboard() -> Check {
+    let outcome = match CLIPBOARD_TOOLS
+        .iter()
+        .find(|tool| which::which(tool).is_ok())
+    {
+        Some(tool) => Outcome::Ok(format!("using {}", tool)),
+        None => Outcome::Warn(
+            "no wl-copy, xclip or xsel, so --copy falls back to asking the terminal".to_string(),
+            "install wl-clipboard on Wayland or xclip on X11".to_string(),
+        ),
+    };
+    Check::new("clipboard", outcome)
+}
+
+/// --notify counts pending updates with whichever of these it finds
+#[cfg(target_os = "linux")]
+fn check_updates() -> Check {
+    let checkers = ["checkupdates", "apt-get", "dnf"];
+    let outcome = match checkers.iter().find(|tool| which::which(tool).is_ok()) {
+        Some(tool) => Outcome::Ok(format!("using {}", tool)),
+        None => Outcome::Warn(
+            "no checkupdates, apt-get or dnf, so --notify can't count pending updates".to_string(),
+            "on Arch, install pacman-contrib for checkupdates".to_string(),
+        ),
+    };
+    Check::new("updates", outcome)
+}
+
+fn check_logos(config: &Config) -> Vec<Check> {
+    let dir = logo::logo_dir();
+    let mut checks = Vec::new();
+
+    // Files that usvg can't parse fall back to linux.svg without a word
+    match fs::read_dir(&dir) {
+        Ok(entries) => {
+            let svgs: Vec<_> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "svg"))
+                .collect();
+            let broken: Vec<String> = svgs
+                .iter()
+                .filter(|path| {
+                    fs::read(path)
+                        .ok()
+                        .and_then(|data| logo::rasterize_svg(&data, 8, 8))
+                        .is_none()
+                })
+                .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+                .collect();
+            let outcome = if broken.is_empty() {
+                Outcome::Ok(format!("{} logos in {}", svgs.len(), dir.display()))
+            } else {
+                Outcome::Fail(
+                    format!("can't render {}", broken.join(", ")),
+                    format!("replace or delete them in {}", dir.display()),
+                )
+            };
+            checks.push(Check::new("logos", outcome));
+        }
+        Err(_) => checks.push(Check::new(
+            "logos",
+            Outcome::Ok("no logo directory, using the built-in logos".to_string()),
+        )),
+    }
+
+    let distro = Fetch::collect(config).logo_subject(&config.logo);
+    let outcome = match logo::distro_logo(&distro, &config.logo) {
+        Some(name) => Outcome::Ok(format!("{} for {}", name, distro)),
+        None => Outcome::Warn(
+            format!("no logo for {}, showing linux.svg", distro),
+            format!(
+                "run `huginn logos install <name>`, or add an SVG to {} and map it under [logo.map]",
+                dir.display()
+            ),
+        ),
+    };
+    checks.push(Check::new("distro", outcome));
+    checks
+}
+
+/// Whether huginn can create files in `dir`
+fn check_writable(name: &str, dir: &Path) -> Check {
+    let writable = fs::create_dir_all(dir).and_then(|_| tempfile::tempfile_in(dir));
+    let outcome = match writable {
+        Ok(_) => Outcome::Ok(format!("{} is writable", dir.display())),
+        Err(e) => Outcome::Fail(
+            format!("can't write to {}: {}", dir.display(), e),
+            format!(
+                "fix the permissions on {}, or point XDG_{}_HOME elsewhere",
+                dir.display(),
+                name.to_uppercase()
+            ),
+        ),
+    };
+    Check::new(name, outcome)
+}
+
+/// Rendered logos that are empty or not PNGs, say from a full disk
+fn check_cache() -> Check {
+    let dir = logo::cache_dir();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Check::new("renders", Outcome::Ok("nothing cached yet".to_string()));
+    };
+
+    let mut count = 0;
+    let mut size = 0;
+    let mut corrupt = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Ok(data) = fs::read(&path) else {
+            corrupt += 1;
+            continue;
+        };
+        count += 1;
+        size += data.len();
+        if !data.starts_with(b"\x89PNG") {
+            corrupt += 1;
+        }
+    }
+
+    let outcome = if corrupt > 0 {
+        Outcome::Fail(
+            format!("{} of {} cached renders are damaged", corrupt, count),
+            format!(
+                "delete {}; logos are rendered again on the next run",
+                dir.display()
+            ),
+        )
+    } else {
+        Outcome::Ok(format!("{} cached, {} KB", count, size / 1024))
+    };
+    Check::new("renders", outcome)
+}
@@ -0,0 +1,79 @@
+// The `dotfiles` field: branch, uncommitted changes and unpushed commits of
+// the repository holding your dotfiles, like "main, 3 changed, ↑2"
+// A bare repository, as in the `git --git-dir=~/.cfg --work-tree=~` setup,
+// is checked against the home directory
+use crate::config::home_dir;
+use crate::error::{self, CollectError};
+use std::path::Path;
+
+/// Status of the repository at `repo`, a work tree or a bare git dir
+pub fn status(repo: &str) -> Result<String, CollectError> {
+    let path = Path::new(repo);
+    let bare = !path.join(".git").exists() && path.join("HEAD").is_file();
+
+    let mut args = Vec::new();
+    let work_tree;
+    if bare {
+        work_tree = home_dir().unwrap_or_default();
+        args.extend(["--git-dir", repo, "--work-tree", &work_tree]);
+    } else if path.is_dir() {
+        args.extend(["-C", repo]);
+    } else {
+        return Err(CollectError::Failed(format!("{} doesn't exist", repo)));
+    }
+    args.extend(["status", "--porcelain=v2", "--branch"]);
+    // Every file in home would count as untracked
+    if bare {
+        args.push("--untracked-files=no");
+    }
+
+    let output = error::run("git", &args)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CollectError::Failed(stderr.trim().to_string()));
+    }
+    Ok(summarize(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// One line from `git status --porcelain=v2 --branch`
+fn summarize(status: &str) -> String {
+    let mut branch = "detached".to_string();
+    let (mut ahead, mut behind) = (0, 0);
+    let mut changed = 0;
+
+    for line in status.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            if head != "(detached)" {
+                branch = head.to_string();
+            }
+        } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            // "+<ahead> -<behind>", only there when the branch has an upstream
+            for count in counts.split_whitespace() {
+                if let Some(n) = count.strip_prefix('+') {
+                    ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = count.strip_prefix('-') {
+                    behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') {
+            changed += 1;
+        }
+    }
+
+    let mut parts = vec![branch];
+    if changed > 0 {
+        parts.push(format!("{} changed", changed));
+    }
+    let sync: Vec<String> = [("↑", ahead), ("↓", behind)]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(arrow, n)| format!("{}{}", arrow, n))
+        .collect();
+    if !sync.is_empty() {
+        parts.push(sync.join(" "));
+    }
+    if changed == 0 && sync.is_empty() {
+        parts.push("clean".to_string());
+    }
+    parts.join(", ")
+}
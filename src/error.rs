@@ -0,0 +1,50 @@
+// Why a collector came back empty, reported with --verbose
+use std::io;
+use std::process::{Command, Output, Stdio};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CollectError {
+    #[error("{0} is not installed")]
+    MissingBinary(String),
+    #[error("permission denied running {0}")]
+    PermissionDenied(String),
+    #[error("timed out after {0} ms")]
+    Timeout(u64),
+    #[error("the collector panicked")]
+    Panicked,
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl CollectError {
+    /// Classify the error from starting `program`
+    pub fn spawn(program: &str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::MissingBinary(program.to_string()),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(program.to_string()),
+            _ => Self::Failed(format!("{}: {}", program, error)),
+        }
+    }
+}
+
+/// Run a program and wait for its output
+pub fn run(program: &str, args: &[&str]) -> Result<Output, CollectError> {
+    output(Command::new(program).args(args))
+}
+
+/// Standard output of a command, trimmed, if it ran and printed anything
+pub fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = run(program, args).ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+/// Wait for a prepared command's output, with stdin closed so one that
+/// asks for a password fails instead of waiting on the terminal
+pub fn output(command: &mut Command) -> Result<Output, CollectError> {
+    command
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| CollectError::spawn(&command.get_program().to_string_lossy(), e))
+}
@@ -0,0 +1,121 @@
+use crate::challenge::ChallengeStatus;
+use crate::config::Config;
+use crate::fetch::Fetch;
+use std::io;
+use tiny_http::{Header, Response, Server};
+
+/// Only this machine by default; pass an address like 0.0.0.0:9731 to let
+/// other hosts scrape it
+pub const DEFAULT_ADDR: &str = "127.0.0.1:9731";
+
+/// Serve freshly collected metrics in the Prometheus text format
+/// Every scrape of /metrics runs the collectors again
+pub fn serve(addr: &str, config: &Config, years: i64, months: i64) -> io::Result<()> {
+    let server = Server::http(addr).map_err(io::Error::other)?;
+    println!("Serving metrics on http://{}/metrics", addr);
+
+    for request in server.incoming_requests() {
+        let response = if request.url() == "/metrics" {
+            let fetch = Fetch::collect(config);
+            let challenge = ChallengeStatus::new(
+                years,
+                months,
+                config.challenge.start_date.as_deref(),
+                config,
+            );
+            let content_type =
+                Header::from_bytes("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+                    .expect("static header is valid");
+
+            Response::from_string(render_metrics(&fetch, Some(&challenge)))
+                .with_header(content_type)
+        } else {
+            Response::from_string("huginn exporter: metrics are served at /metrics\n")
+                .with_status_code(404)
+        };
+
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+pub fn render_metrics(fetch: &Fetch, challenge: Option<&ChallengeStatus>) -> String {
+    let mut out = String::new();
+
+    gauge(
+        &mut out,
+        "huginn_cpu_usage_percent",
+        "Global CPU usage in percent.",
+        fetch.cpu_usage,
+    );
+    gauge(
+        &mut out,
+        "huginn_memory_usage_percent",
+        "Used memory in percent.",
+        fetch.ram_usage,
+    );
+    gauge(
+        &mut out,
+        "huginn_disk_usage_percent",
+        "Used space on the root filesystem in percent.",
+        fetch.disk_usage,
+    );
+    gauge(
+        &mut out,
+        "huginn_uptime_seconds",
+        "Time since boot in seconds.",
+        fetch.uptime_secs,
+    );
+    if let Some(packages) = fetch
+        .info
+        .packages
+        .as_ref()
+        .and_then(|p| p.parse::<u64>().ok())
+    {
+        gauge(
+            &mut out,
+            "huginn_packages",
+            "Number of installed packages.",
+            packages,
+        );
+    }
+    if let Some(challenge) = challenge {
+        gauge(
+            &mut out,
+            "huginn_challenge_days_remaining",
+            "Days left until the install challenge is complete.",
+            challenge.remaining().num_days().max(0),
+        );
+        gauge(
+            &mut out,
+            "huginn_challenge_progress_percent",
+            "Install challenge progress in percent.",
+            challenge.progress_percentage,
+        );
+    }
+
+    out.push_str("# HELP huginn_info Static system information as labels.\n");
+    out.push_str("# TYPE huginn_info gauge\n");
+    out.push_str(&format!(
+        "huginn_info{{distro=\"{}\",kernel=\"{}\"}} 1\n",
+        label_value(&fetch.distro()),
+        label_value(fetch.info.kernel.as_deref().unwrap_or("unknown"))
+    ));
+
+    out
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    out.push_str(&format!(
+        "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"
+    ));
+}
+
+/// Escape a Prometheus label value
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
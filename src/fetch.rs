@@ -0,0 +1,234 @@
+use crate::challenge::ChallengeStatus;
+use crate::config::{Config, LogoConfig};
+use crate::i18n::tr;
+use crate::system_info::SystemInfo;
+use crate::uptime_record::UptimeRecord;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use sysinfo::{CpuRefreshKind, Disk, Disks, MemoryRefreshKind, RefreshKind, System};
+
+/// Everything collected for a single run, independent of how it gets rendered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fetch {
+    pub user: String,
+    #[serde(default)]
+    pub hostname: String,
+    pub uptime: String,
+    pub uptime_secs: u64,
+    /// Filled in per invocation when uptime records are enabled
+    #[serde(default)]
+    pub uptime_record: Option<UptimeRecord>,
+    pub info: SystemInfo,
+    pub cpu_usage: i32,
+    pub ram_usage: i32,
+    pub disk_usage: i32,
+}
+
+impl Fetch {
+    pub fn collect(config: &Config) -> Self {
+        let started = std::time::Instant::now();
+        // One System and one Disks, refreshed only for what huginn reads,
+        // shared by every collector
+        let mut sys = System::new_with_specifics(
+            RefreshKind::new()
+                .with_cpu(CpuRefreshKind::everything())
+                .with_memory(MemoryRefreshKind::everything()),
+        );
+        sys.refresh_cpu_usage();
+        let disks = Disks::new_with_refreshed_list();
+
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        let hostname = hostname::get()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| "unknown".to_string());
+        let uptime_secs = System::uptime();
+        let uptime = format_uptime(uptime_secs);
+
+        // Collect all system info
+        let mut info = SystemInfo::new();
+        info.collect_all(
+            &config.display,
+            &config.collectors,
+            &config.backup,
+            &config.performance,
+            &sys,
+        );
+        tracing::debug!(
+            "collected in {:.1}ms",
+            started.elapsed().as_secs_f64() * 1000.0
+        );
+
+        let cpu_usage = sys.global_cpu_usage() as i32;
+        let ram_usage = ((sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0) as i32;
+        // sysinfo reports no memory on some BSDs
+        #[cfg(any(
+            target_os = "freebsd",
+            target_os = "openbsd",
+            target_os = "netbsd",
+            target_os = "dragonfly"
+        ))]
+        let ram_usage = if sys.total_memory() == 0 {
+            crate::bsd::memory_usage().unwrap_or(0)
+        } else {
+            ram_usage
+        };
+        let disk_usage = get_disk_usage(&disks, &config.display.disk_mount);
+
+        Self {
+            user,
+            hostname,
+            uptime,
+            uptime_secs,
+            uptime_record: None,
+            info,
+            cpu_usage,
+            ram_usage,
+            disk_usage,
+        }
+    }
+
+    /// Update the uptime fields, which go stale first when a fetch is cached
+    pub fn refresh_uptime(&mut self) {
+        self.uptime_secs = System::uptime();
+        self.uptime = format_uptime(self.uptime_secs);
+    }
+
+    /// Pair the fetch with the challenge state for serialization
+    pub fn into_snapshot(self, challenge: Option<ChallengeStatus>) -> Snapshot {
+        Snapshot {
+            fetch: self,
+            challenge,
+        }
+    }
+
+    pub fn distro(&self) -> String {
+        self.info
+            .distro
+            .clone()
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    /// The distro or board the logo is shown for
+    pub fn logo_subject(&self, logo_config: &LogoConfig) -> String {
+        crate::logo::logo_subject(&self.distro(), self.info.board.as_deref(), logo_config)
+    }
+
+    /// The cpu/ram/disk usage rows shown as progress bars
+    pub fn usage_items(&self) -> Vec<(&'static str, i32)> {
+        vec![
+            ("cpu", self.cpu_usage),
+            ("ram", self.ram_usage),
+            ("disk", self.disk_usage),
+        ]
+    }
+}
+
+/// A fetch together with the challenge state, as exchanged in JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(flatten)]
+    pub fetch: Fetch,
+    pub challenge: Option<ChallengeStatus>,
+}
+
+impl Snapshot {
+    /// Collect a fetch, plus the challenge state when a (years, months) target is given
+    pub fn collect(config: &Config, challenge: Option<(i64, i64)>) -> Self {
+        let status = challenge.map(|(years, months)| {
+            ChallengeStatus::new(
+                years,
+                months,
+                config.challenge.start_date.as_deref(),
+                config,
+            )
+        });
+        Fetch::collect(config).into_snapshot(status)
+    }
+
+    /// Flat name/value view of the snapshot for consumers that want plain strings
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields: Vec<(String, String)> = self
+            .fetch
+            .info
+            .fields()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        fields.push(("user".to_string(), self.fetch.user.clone()));
+        fields.push(("uptime".to_string(), self.fetch.uptime.clone()));
+        for (label, value) in self.fetch.usage_items() {
+            fields.push((format!("{}_usage", label), value.to_string()));
+        }
+
+        if let Some(status) = &self.challenge {
+            fields.push((
+                "challenge_days_old".to_string(),
+                status.days_old.to_string(),
+            ));
+            fields.push((
+                "challenge_days_remaining".to_string(),
+                status.remaining().num_days().max(0).to_string(),
+            ));
+            fields.push((
+                "challenge_progress".to_string(),
+                status.progress_percentage.to_string(),
+            ));
+        }
+
+        fields
+    }
+}
+
+pub fn format_uptime(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let hours = (seconds % 86400) / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if days > 0 {
+        format!("{} {}, {} {}", days, tr("days"), hours, tr("hrs"))
+    } else if hours > 0 {
+        format!("{} {}, {} {}", hours, tr("hrs"), minutes, tr("mins"))
+    } else {
+        format!("{} {}", minutes, tr("mins"))
+    }
+}
+
+/// Filesystems that live in memory or are stacked on others, which say
+/// nothing about how full the disk is
+const VIRTUAL_FILESYSTEMS: [&str; 7] = [
+    "tmpfs", "devtmpfs", "ramfs", "overlay", "squashfs", "zram", "rootfs",
+];
+
+fn is_physical(disk: &Disk) -> bool {
+    let fs = disk.file_system().to_string_lossy();
+    disk.total_space() > 0 && !VIRTUAL_FILESYSTEMS.contains(&fs.as_ref())
+}
+
+/// Usage of the filesystem at `mount`, falling back to the largest physical
+/// one when it isn't mounted or is virtual, as / is on impermanence setups
+fn get_disk_usage(disks: &Disks, mount: &str) -> i32 {
+    let configured = disks
+        .iter()
+        .find(|d| mount != "auto" && d.mount_point() == Path::new(mount))
+        .filter(|d| is_physical(d));
+    if configured.is_none() && mount != "auto" {
+        tracing::debug!("no physical filesystem at {}, using the largest", mount);
+    }
+    configured
+        .or_else(|| {
+            disks
+                .iter()
+                .filter(|d| is_physical(d))
+                .max_by_key(|d| d.total_space())
+        })
+        .map(|d| {
+            let total = d.total_space();
+            let available = d.available_space();
+            let used = total - available;
+            ((used as f64 / total as f64) * 100.0) as i32
+        })
+        .unwrap_or(0)
+}
@@ -0,0 +1,507 @@
+use crate::color;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute};
+use std::io::{self, Cursor, Write};
+use std::path::Path;
+use std::time::Duration;
+
+// Terminal graphics protocols driven directly, for terminals where viuer's
+// auto-detection picks the wrong one (e.g. inside tmux)
+
+/// Kitty splits payloads into chunks of at most 4096 base64 bytes
+const KITTY_CHUNK: usize = 4096;
+
+/// Image file as PNG bytes, re-encoding other formats
+fn png_bytes(path: &Path) -> io::Result<Vec<u8>> {
+    let is_png = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    if is_png {
+        return std::fs::read(path);
+    }
+
+    let image = image::open(path).map_err(io::Error::other)?;
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(io::Error::other)?;
+    Ok(png)
+}
+
+/// Wrap an escape sequence so tmux forwards it to the outer terminal
+/// Needs `set -g allow-passthrough on` in tmux.conf
+fn passthrough(sequence: &str) -> String {
+    if in_tmux() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence.to_string()
+    }
+}
+
+/// Size in cells that fits the image inside `width` x `height`, assuming
+/// cells are about twice as tall as they are wide
+fn fit_cells(path: &Path, width: u32, height: u32) -> (u32, u32) {
+    let Ok((image_w, image_h)) = image::image_dimensions(path) else {
+        return (width, height);
+    };
+    let aspect = image_w as f64 / image_h.max(1) as f64;
+
+    let cols_at_full_height = (height as f64 * 2.0 * aspect).round() as u32;
+    if cols_at_full_height <= width {
+        (cols_at_full_height.max(1), height)
+    } else {
+        (
+            width,
+            ((width as f64 / (2.0 * aspect)).round() as u32).max(1),
+        )
+    }
+}
+
+/// Draw an image with the kitty graphics protocol at cell (x, y), scaled to
+/// fit `width` x `height` cells
+pub fn print_kitty(path: &Path, x: u16, y: u16, width: u32, height: u32) -> io::Result<()> {
+    let encoded = STANDARD.encode(png_bytes(path)?);
+    let (cols, rows) = fit_cells(path, width, height);
+
+    let mut stdout = io::stdout();
+    execute!(stdout, cursor::MoveTo(x, y))?;
+
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        // Only the first chunk carries the control keys; C=1 keeps the cursor in place
+        let keys = if i == 0 {
+            format!("a=T,f=100,q=2,C=1,c={},r={},m={}", cols, rows, more)
+        } else {
+            format!("m={}", more)
+        };
+        let data = std::str::from_utf8(chunk).map_err(io::Error::other)?;
+        write!(
+            stdout,
+            "{}",
+            passthrough(&format!("\x1b_G{};{}\x1b\\", keys, data))
+        )?;
+    }
+
+    stdout.flush()
+}
+
+/// Delete kitty images covering cell (x, y), so a new frame doesn't stack
+/// on top of the previous one
+pub fn clear_kitty_at(x: u16, y: u16) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    // Kitty counts cells from 1
+    write!(
+        stdout,
+        "{}",
+        passthrough(&format!("\x1b_Ga=d,d=p,q=2,x={},y={}\x1b\\", x + 1, y + 1))
+    )?;
+    stdout.flush()
+}
+
+/// Draw an image with the iTerm2 inline image protocol (also understood by
+/// WezTerm) at cell (x, y), scaled to fit `width` x `height` cells
+pub fn print_iterm(path: &Path, x: u16, y: u16, width: u32, height: u32) -> io::Result<()> {
+    let png = png_bytes(path)?;
+
+    let mut stdout = io::stdout();
+    execute!(stdout, cursor::MoveTo(x, y))?;
+    let sequence = format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        width,
+        height,
+        STANDARD.encode(&png)
+    );
+    write!(stdout, "{}", passthrough(&sequence))?;
+    stdout.flush()
+}
+
+/// Graphics the terminal supports, from the startup probe
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub kitty: bool,
+    pub iterm: bool,
+    pub sixel: bool,
+}
+
+/// Probe the terminal once and cache what it supports; call this before
+/// drawing, so the terminal's reply can't land in the middle of the output
+pub fn capabilities() -> Capabilities {
+    static CAPABILITIES: std::sync::OnceLock<Capabilities> = std::sync::OnceLock::new();
+    *CAPABILITIES.get_or_init(probe)
+}
+
+pub fn sixel_supported() -> bool {
+    capabilities().sixel
+}
+
+/// The protocol "auto" draws with: kitty, then iTerm2, then sixel, falling
+/// back to unicode blocks
+pub fn detect() -> &'static str {
+    let caps = capabilities();
+    if caps.kitty {
+        "kitty"
+    } else if caps.iterm {
+        "iterm"
+    } else if caps.sixel {
+        "sixel"
+    } else {
+        "blocks"
+    }
+}
+
+/// The backend to draw with, resolving "auto" through the probe
+pub fn resolve(backend: &str) -> &str {
+    if backend == "auto" {
+        detect()
+    } else {
+        backend
+    }
+}
+
+fn in_tmux() -> bool {
+    std::env::var("TMUX").is_ok_and(|v| !v.is_empty())
+}
+
+/// Whether huginn runs inside tmux and tmux drops images meant for the
+/// outer terminal, because `allow-passthrough` is off
+pub fn tmux_blocks_passthrough() -> bool {
+    // tmux before 3.3 has no such option and always lets them through
+    in_tmux() && matches!(tmux_format("#{allow-passthrough}").as_str(), "off" | "0")
+}
+
+/// Output of `tmux display-message -p`, or an empty string
+fn tmux_format(format: &str) -> String {
+    std::process::Command::new("tmux")
+        .args(["display-message", "-p", format])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Environment variables only describe the terminal huginn runs in directly:
+/// inside tmux they are tmux's, or stale ones from the terminal tmux was
+/// started in, so ask tmux about the terminal attached to it instead, and
+/// over SSH ask the terminal itself
+fn probe() -> Capabilities {
+    let reply = query_terminal();
+    let tmux = in_tmux();
+    // kitty answers its graphics query with OK, even over SSH
+    let kitty_reply = reply.contains("\x1b_Gi=31;OK");
+    // Reply looks like ESC [ ? 62 ; 4 ; 22 c
+    let sixel = reply
+        .split_once("\x1b[?")
+        .and_then(|(_, attrs)| attrs.split_once('c'))
+        .is_some_and(|(attrs, _)| attrs.split(';').any(|attr| attr == "4"));
+
+    let (kitty, iterm) = if tmux {
+        // Images for the outer terminal only get through tmux with
+        // `set -g allow-passthrough on`; sixel is drawn by tmux itself
+        if tmux_blocks_passthrough() {
+            return Capabilities {
+                kitty: false,
+                iterm: false,
+                sixel,
+            };
+        }
+        // The outer terminal's TERM and its name and version, like
+        // "xterm-kitty kitty(0.35.2)"
+        let client = tmux_format("#{client_termname} #{client_termtype}").to_lowercase();
+        (
+            kitty_reply || client.contains("kitty") || client.contains("ghostty"),
+            client.contains("iterm") || client.contains("wezterm"),
+        )
+    } else {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        (
+            kitty_reply
+                || !var("KITTY_WINDOW_ID").is_empty()
+                || var("TERM").contains("kitty")
+                || var("TERM").contains("ghostty"),
+            // iTerm2 sets LC_TERMINAL, which ssh forwards by default
+            matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm")
+                || var("LC_TERMINAL") == "iTerm2",
+        )
+    };
+    Capabilities {
+        kitty,
+        iterm,
+        sixel,
+    }
+}
+
+/// How long to wait for the terminal to answer, long enough for one at the
+/// other end of a slow SSH connection
+const QUERY_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Send kitty's graphics query followed by a primary device attributes
+/// (DA1) request, which every terminal answers, and return everything
+/// read up to the DA1 reply
+#[cfg(unix)]
+fn query_terminal() -> String {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+    use rustix::event::{poll, PollFd, PollFlags, Timespec};
+    use std::fs::OpenOptions;
+    use std::io::{IsTerminal, Read};
+    use std::time::Instant;
+
+    if !io::stdout().is_terminal() {
+        return String::new();
+    }
+    let Ok(mut tty) = OpenOptions::new().read(true).write(true).open("/dev/tty") else {
+        return String::new();
+    };
+    if enable_raw_mode().is_err() {
+        return String::new();
+    }
+
+    // A 1x1 image that is only checked, never stored or shown
+    let query = format!(
+        "{}\x1b[c",
+        passthrough("\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\")
+    );
+    let mut response = Vec::new();
+    if tty
+        .write_all(query.as_bytes())
+        .and_then(|_| tty.flush())
+        .is_ok()
+    {
+        // Only read once poll says a byte is there, so a terminal that never
+        // answers can't hang us
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        let mut byte = [0u8; 1];
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let Ok(left) = Timespec::try_from(left) else {
+                break;
+            };
+            let mut fds = [PollFd::new(&tty, PollFlags::IN)];
+            if !matches!(poll(&mut fds, Some(&left)), Ok(1..)) {
+                break;
+            }
+            if tty.read_exact(&mut byte).is_err() {
+                break;
+            }
+            response.push(byte[0]);
+            if byte[0] == b'c' && response.windows(3).any(|w| w == b"\x1b[?") {
+                break;
+            }
+        }
+    }
+    let _ = disable_raw_mode();
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+/// Other platforms have no /dev/tty to ask, so only the environment is used
+#[cfg(not(unix))]
+fn query_terminal() -> String {
+    String::new()
+}
+
+/// Pixel size of one terminal cell, guessing when the terminal won't say
+fn cell_pixels() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => (10, 20),
+    }
+}
+
+/// Draw an image as sixel graphics at cell (x, y), scaled to fit
+/// `width` x `height` cells
+pub fn print_sixel(path: &Path, x: u16, y: u16, width: u32, height: u32) -> io::Result<()> {
+    let (cell_w, cell_h) = cell_pixels();
+    let image = image::open(path)
+        .map_err(io::Error::other)?
+        .resize(
+            width * cell_w,
+            height * cell_h,
+            image::imageops::FilterType::Triangle,
+        )
+        .to_rgba8();
+
+    let mut stdout = io::stdout();
+    execute!(stdout, cursor::MoveTo(x, y))?;
+    write!(stdout, "{}", encode_sixel(&image))?;
+    stdout.flush()
+}
+
+/// Sixel encoding on a fixed 6x6x6 color cube; transparent pixels are left unset
+fn encode_sixel(image: &image::RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |c: u8| (c as u32 * 5 + 127) / 255;
+    let color_index = |pixel: &image::Rgba<u8>| -> Option<usize> {
+        let [r, g, b, a] = pixel.0;
+        (a >= 128).then(|| (level(r) * 36 + level(g) * 6 + level(b)) as usize)
+    };
+
+    // P2=1 keeps unset pixels transparent
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for index in 0..216u32 {
+        let percent = |l: u32| l * 100 / 5;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            index,
+            percent(index / 36),
+            percent(index / 6 % 6),
+            percent(index % 6)
+        ));
+    }
+
+    for band_top in (0..height).step_by(6) {
+        let band_rows = (height - band_top).min(6);
+
+        // Sixel bits per color for each column of this band
+        let mut colors: Vec<(usize, Vec<u8>)> = Vec::new();
+        for col in 0..width {
+            for row in 0..band_rows {
+                let Some(index) = color_index(image.get_pixel(col, band_top + row)) else {
+                    continue;
+                };
+                let slot = match colors.iter().position(|(i, _)| *i == index) {
+                    Some(slot) => slot,
+                    None => {
+                        colors.push((index, vec![0; width as usize]));
+                        colors.len() - 1
+                    }
+                };
+                colors[slot].1[col as usize] |= 1 << row;
+            }
+        }
+
+        for (n, (index, bits)) in colors.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", index));
+            push_runs(&mut out, bits);
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Append sixel characters with run-length encoding
+fn push_runs(out: &mut String, bits: &[u8]) {
+    let mut i = 0;
+    while i < bits.len() {
+        let run = bits[i..].iter().take_while(|&&b| b == bits[i]).count();
+        let ch = (63 + bits[i]) as char;
+        if run > 3 {
+            out.push_str(&format!("!{}{}", run, ch));
+        } else {
+            out.extend(std::iter::repeat_n(ch, run));
+        }
+        i += run;
+    }
+}
+
+/// Quadrant block glyphs, indexed by which quarters are filled
+/// (top-left = 1, top-right = 2, bottom-left = 4, bottom-right = 8)
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Squared color distance below which a cell is drawn as one solid block
+const BLOCK_SPLIT_DISTANCE: i32 = 48 * 48;
+
+/// Draw an image with colored quadrant blocks at cell (x, y), scaled to
+/// fit `width` x `height` cells; works in any terminal, in the colors it has
+pub fn print_blocks(path: &Path, x: u16, y: u16, width: u32, height: u32) -> io::Result<()> {
+    let (cols, rows) = fit_cells(path, width, height);
+    // Each cell covers 2x2 pixels
+    let image = image::open(path)
+        .map_err(io::Error::other)?
+        .resize_exact(cols * 2, rows * 2, image::imageops::FilterType::Triangle)
+        .to_rgba8();
+
+    let mut stdout = io::stdout();
+    for row in 0..rows {
+        execute!(stdout, cursor::MoveTo(x, y + row as u16))?;
+        let mut line = String::new();
+        for col in 0..cols {
+            let quarters = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| image.get_pixel(col * 2 + dx, row * 2 + dy).0);
+            line.push_str(&block_cell(&quarters));
+        }
+        write!(stdout, "{}\x1b[0m", line)?;
+    }
+    stdout.flush()
+}
+
+/// One cell as a quadrant glyph: the quarters are split into the two most
+/// different colors, drawn as foreground and background
+fn block_cell(quarters: &[[u8; 4]; 4]) -> String {
+    let opaque: Vec<bool> = quarters.iter().map(|p| p[3] >= 128).collect();
+    let mask_of =
+        |filled: &dyn Fn(usize) -> bool| (0..4).filter(|&i| filled(i)).fold(0, |m, i| m | 1 << i);
+
+    // Nothing to draw, just step over the cell
+    if !opaque.contains(&true) {
+        return "\x1b[0m\x1b[C".to_string();
+    }
+
+    // Partly transparent cells keep the terminal background behind the glyph
+    if opaque.contains(&false) {
+        let mask = mask_of(&|i| opaque[i]);
+        let fg = average(quarters, mask);
+        return format!("\x1b[49m{}{}", foreground(fg), QUADRANTS[mask]);
+    }
+
+    // Seed the two groups with the most distant pair of quarters
+    let distance = |a: &[u8; 4], b: &[u8; 4]| {
+        (0..3)
+            .map(|c| (a[c] as i32 - b[c] as i32).pow(2))
+            .sum::<i32>()
+    };
+    let mut seeds = (0, 1);
+    for i in 0..4 {
+        for j in i + 1..4 {
+            if distance(&quarters[i], &quarters[j])
+                > distance(&quarters[seeds.0], &quarters[seeds.1])
+            {
+                seeds = (i, j);
+            }
+        }
+    }
+
+    // Near-identical quarters would only add noise, so fill the cell
+    if distance(&quarters[seeds.0], &quarters[seeds.1]) < BLOCK_SPLIT_DISTANCE {
+        let fg = average(quarters, 0xf);
+        return format!("\x1b[49m{}█", foreground(fg));
+    }
+    let mask = mask_of(&|i| {
+        distance(&quarters[i], &quarters[seeds.0]) <= distance(&quarters[i], &quarters[seeds.1])
+    });
+
+    let fg = average(quarters, mask);
+    let bg = average(quarters, !mask & 0xf);
+    let bg = SetBackgroundColor(color::fit(rgb(bg)));
+    format!("{}{}{}", foreground(fg), bg, QUADRANTS[mask])
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+/// Escape code for a block's color, as close as the terminal shows it
+fn foreground(fg: [u8; 3]) -> SetForegroundColor {
+    SetForegroundColor(color::fit(rgb(fg)))
+}
+
+/// Mean color of the quarters in `mask`
+fn average(quarters: &[[u8; 4]; 4], mask: usize) -> [u8; 3] {
+    let picked: Vec<&[u8; 4]> = (0..4)
+        .filter(|i| mask & 1 << i != 0)
+        .map(|i| &quarters[i])
+        .collect();
+    let count = picked.len().max(1) as u32;
+    [0, 1, 2].map(|c| (picked.iter().map(|p| p[c] as u32).sum::<u32>() / count) as u8)
+}
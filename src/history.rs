@@ -0,0 +1,186 @@
+use crate::challenge::ChallengeStatus;
+use crate::color::paint;
+use crate::config::{data_dir, HistoryConfig};
+use crate::fetch::Fetch;
+use chrono::{DateTime, Duration, Local, Utc};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Chart label and how to read the value out of an entry
+type Metric = (&'static str, fn(&HistoryEntry) -> Option<i64>);
+
+const METRICS: [Metric; 5] = [
+    ("packages", |e| e.packages.map(|p| p as i64)),
+    ("disk %", |e| Some(e.disk_usage as i64)),
+    ("ram %", |e| Some(e.ram_usage as i64)),
+    ("uptime h", |e| Some((e.uptime_secs / 3600) as i64)),
+    ("challenge %", |e| e.challenge_progress.map(i64::from)),
+];
+
+/// Metrics recorded for a single run, one JSON object per line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub packages: Option<u64>,
+    pub disk_usage: i32,
+    pub ram_usage: i32,
+    /// Missing from entries written before it was recorded
+    #[serde(default)]
+    pub cpu_usage: Option<i32>,
+    pub uptime_secs: u64,
+    pub challenge_progress: Option<i32>,
+}
+
+impl HistoryEntry {
+    fn from_fetch(fetch: &Fetch, challenge: Option<&ChallengeStatus>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            packages: fetch.info.packages.as_ref().and_then(|p| p.parse().ok()),
+            disk_usage: fetch.disk_usage,
+            ram_usage: fetch.ram_usage,
+            cpu_usage: Some(fetch.cpu_usage),
+            uptime_secs: fetch.uptime_secs,
+            challenge_progress: challenge.map(|c| c.progress_percentage),
+        }
+    }
+}
+
+fn history_path() -> PathBuf {
+    data_dir().join("history.jsonl")
+}
+
+/// Append this run to the history file, dropping entries past `keep_days`
+pub fn record(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &HistoryConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Only rewrite the file when the oldest entry has expired
+    let cutoff = Utc::now() - Duration::days(config.keep_days);
+    let entries = load_entries();
+    if entries.first().is_some_and(|e| e.timestamp < cutoff) {
+        let kept: String = entries
+            .iter()
+            .filter(|e| e.timestamp >= cutoff)
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .map(|line| line + "\n")
+            .collect();
+        fs::write(&path, kept)?;
+    }
+
+    let line = serde_json::to_string(&HistoryEntry::from_fetch(fetch, challenge))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Unparseable lines are skipped so a bad write never hides the rest
+fn load_entries() -> Vec<HistoryEntry> {
+    fs::read_to_string(history_path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The longest sparkline, one column per day
+const MAX_DAYS: i64 = 3660;
+
+/// Print one sparkline per metric over the last `days` days
+pub fn show_history(days: i64) {
+    let days = days.clamp(1, MAX_DAYS);
+    let today = Local::now().date_naive();
+    let age =
+        |e: &HistoryEntry| (today - e.timestamp.with_timezone(&Local).date_naive()).num_days();
+    // Entries dated after today, from before a clock or timezone change,
+    // have no place on the sparkline
+    let entries: Vec<HistoryEntry> = load_entries()
+        .into_iter()
+        .filter(|e| (0..days).contains(&age(e)))
+        .collect();
+
+    if entries.is_empty() {
+        println!("No history recorded in the last {} days", days);
+        println!("History is written on every run unless [history] enabled = false");
+        return;
+    }
+
+    println!(
+        "{} {} {}\n",
+        paint("History for the last".cyan()),
+        paint(days.to_string().bold()),
+        paint(format!("days ({} runs)", entries.len()).cyan())
+    );
+
+    for (label, value_of) in METRICS {
+        // Last value recorded on each day, oldest day first
+        let mut daily: Vec<Option<i64>> = vec![None; days as usize];
+        for entry in &entries {
+            if let Some(value) = value_of(entry) {
+                daily[(days - 1 - age(entry)) as usize] = Some(value);
+            }
+        }
+
+        let values: Vec<i64> = daily.iter().flatten().copied().collect();
+        let (Some(&first), Some(&last)) = (values.first(), values.last()) else {
+            continue;
+        };
+        let min = values.iter().copied().min().unwrap_or(first);
+        let max = values.iter().copied().max().unwrap_or(last);
+
+        println!(
+            "{}  {}  {} → {}  (min {}, max {})",
+            paint(format!("{:>11}", label).green()),
+            sparkline(&daily, min, max),
+            first,
+            paint(last.to_string().bold()),
+            min,
+            max
+        );
+    }
+}
+
+/// What goes after the cpu, ram and disk bars: the usage of the last
+/// `samples` runs as a sparkline on a 0 to 100% scale, padded on the left
+/// while there are fewer runs, or nothing if `samples` is 0
+pub fn usage_trends(samples: usize) -> [String; 3] {
+    if samples == 0 {
+        return Default::default();
+    }
+    let entries = load_entries();
+    let recent = &entries[entries.len().saturating_sub(samples)..];
+    let usage = |value_of: fn(&HistoryEntry) -> Option<i32>| {
+        let mut values = vec![None; samples - recent.len()];
+        values.extend(recent.iter().map(|e| value_of(e).map(i64::from)));
+        format!(" {}", paint(sparkline(&values, 0, 100).dark_grey()))
+    };
+    [
+        usage(|e| e.cpu_usage),
+        usage(|e| Some(e.ram_usage)),
+        usage(|e| Some(e.disk_usage)),
+    ]
+}
+
+fn sparkline(daily: &[Option<i64>], min: i64, max: i64) -> String {
+    daily
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max == min => SPARK_LEVELS[SPARK_LEVELS.len() / 2],
+            Some(v) => {
+                let level = (v - min) * (SPARK_LEVELS.len() as i64 - 1) / (max - min);
+                SPARK_LEVELS[level as usize]
+            }
+        })
+        .collect()
+}
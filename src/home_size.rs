@@ -0,0 +1,147 @@
+// The `home` field: how much space the home directory takes up
+// Walking a big home directory takes a while, so the fetch only reads a
+// cached size, and a detached `huginn cache-home-size` refreshes it once it is
+// older than [collectors] home_ttl
+use crate::config::{cache_dir, home_dir};
+use std::fs::{self, Metadata};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A refresh that has been running this long has died, and its lock is ignored
+const LOCK_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+fn cache_path() -> PathBuf {
+    cache_dir().join("home_size")
+}
+
+fn lock_path() -> PathBuf {
+    cache_dir().join("home_size.lock")
+}
+
+/// The cached size, starting a refresh in the background if it is stale
+/// or missing; None until the first walk has finished
+pub fn get(ttl: Duration) -> Option<String> {
+    // Stored as "<unix seconds> <bytes>"
+    let cached = fs::read_to_string(cache_path()).ok().and_then(|state| {
+        let (at, bytes) = state.trim().split_once(' ')?;
+        let at = UNIX_EPOCH + Duration::from_secs(at.parse().ok()?);
+        Some((at, bytes.parse::<u64>().ok()?))
+    });
+
+    let stale = cached.is_none_or(|(at, _)| at.elapsed().map_or(true, |age| age > ttl));
+    if stale {
+        spawn_refresh();
+    }
+    cached.map(|(_, bytes)| format_size(bytes))
+}
+
+/// Start `huginn cache-home-size`, unless another one is already walking
+fn spawn_refresh() {
+    let lock = lock_path();
+    let running = fs::metadata(&lock)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|at| at.elapsed().is_ok_and(|age| age < LOCK_TIMEOUT));
+    if running {
+        return;
+    }
+
+    let spawned = fs::create_dir_all(cache_dir())
+        .and_then(|_| fs::write(&lock, ""))
+        .and_then(|_| std::env::current_exe())
+        .and_then(|exe| {
+            Command::new(exe)
+                .arg("cache-home-size")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+        });
+    match spawned {
+        Ok(_) => tracing::debug!("refreshing the home size in the background"),
+        Err(e) => {
+            // Otherwise no refresh would start until the lock times out
+            let _ = fs::remove_file(&lock);
+            tracing::debug!("could not refresh the home size: {}", e)
+        }
+    }
+}
+
+/// Walk the home directory and cache its size
+pub fn refresh() -> Result<(), Box<dyn std::error::Error>> {
+    let result = walk_home();
+    let _ = fs::remove_file(lock_path());
+    let bytes = result?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    fs::create_dir_all(cache_dir())?;
+    fs::write(cache_path(), format!("{} {}\n", now, bytes))?;
+    Ok(())
+}
+
+fn walk_home() -> Result<u64, Box<dyn std::error::Error>> {
+    let home = PathBuf::from(home_dir().ok_or("neither HOME nor USERPROFILE is set")?);
+    let home_device = device(&fs::symlink_metadata(&home)?);
+
+    let mut total = 0;
+    let mut dirs = vec![home];
+    while let Some(dir) = dirs.pop() {
+        // Unreadable directories are skipped, like du does
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            // Mounts under ~, like network shares, aren't part of it
+            if home_device.is_some() && device(&meta) != home_device {
+                continue;
+            }
+            total += disk_usage(&meta);
+            if meta.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Space a file takes on disk, which is less than its length for sparse files
+#[cfg(unix)]
+fn disk_usage(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn disk_usage(meta: &Metadata) -> u64 {
+    meta.len()
+}
+
+#[cfg(unix)]
+fn device(meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Bytes in binary units, like "48.2 GiB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,156 @@
+use crate::challenge::ChallengeStatus;
+use crate::color::to_hex;
+use crate::config::{BarsConfig, Config};
+use crate::fetch::Fetch;
+use crate::logo::logo_image_data;
+use crate::{colorbar_cells, progress_color, ProgressColorScheme};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use crossterm::style::Color;
+use std::time::Duration;
+
+const STYLE: &str = r#"<style>
+.huginn { display: inline-flex; gap: 2em; align-items: center; padding: 1.5em 2em; border-radius: 12px; background: #1e1e2e; color: #e5e5e5; font-family: ui-monospace, "JetBrains Mono", "DejaVu Sans Mono", monospace; font-size: 14px; line-height: 1.5; }
+.huginn img { width: 160px; height: auto; }
+.huginn p { margin: 0; text-align: center; }
+.huginn table { border-collapse: collapse; margin: 0.8em auto 0; }
+.huginn td { padding: 0 0.3em; white-space: nowrap; }
+.huginn td.label { text-align: right; }
+.huginn .colorbar { text-align: center; margin-bottom: 0.8em; letter-spacing: -0.05em; }
+.huginn .bar { display: inline-block; width: 9em; height: 0.45em; border-radius: 2px; background: #7f7f7f; vertical-align: middle; overflow: hidden; }
+.huginn .bar span { display: block; height: 100%; }
+</style>
+"#;
+
+/// Self-contained HTML snippet with inline styles and the logo embedded as base64
+pub fn render_html(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    let mut out = String::from("<div class=\"huginn\">\n");
+    out.push_str(STYLE);
+
+    if let Some((data, mime)) = logo_image_data(&fetch.logo_subject(&config.logo), &config.logo) {
+        out.push_str(&format!(
+            "<img src=\"data:{};base64,{}\" alt=\"{} logo\">\n",
+            mime,
+            STANDARD.encode(data),
+            escape(&fetch.distro())
+        ));
+    }
+
+    out.push_str("<div>\n");
+    let cells = colorbar_cells(&config.colorbar);
+    if !cells.is_empty() {
+        out.push_str("<div class=\"colorbar\">");
+        for (block, color) in cells {
+            out.push_str(&span(color, block));
+        }
+        out.push_str("</div>\n");
+    }
+
+    if let Some(parts) = config.greeting.parts(&fetch.user, &fetch.hostname) {
+        out.push_str("<p>");
+        for (part, is_value) in parts {
+            if is_value {
+                out.push_str(&format!("<b>{}</b>", span(Color::Cyan, &escape(&part))));
+            } else {
+                out.push_str(&span(Color::Green, &escape(&part)));
+            }
+        }
+        out.push_str("</p>\n");
+    }
+    out.push_str(&format!(
+        "<p>{} <b>{}</b></p>\n",
+        span(Color::Yellow, "up"),
+        span(Color::Cyan, &escape(&fetch.uptime)),
+    ));
+
+    out.push_str("<table>\n");
+    for (label, value) in fetch.info.to_info_items(true, &config.display) {
+        let colored = |color: Option<Color>, text: &str| match color {
+            Some(color) => span(color, &escape(text)),
+            None => escape(text),
+        };
+        out.push_str(&info_row(
+            &colored(
+                config.info.label_color(label),
+                config.info.label_name(label),
+            ),
+            &colored(config.info.value_color(label), &value),
+        ));
+    }
+    out.push_str("</table>\n<table>\n");
+    for (label, value) in fetch.usage_items() {
+        out.push_str(&format!(
+            "<tr><td class=\"label\">{}</td><td>{}%</td><td>{}</td></tr>\n",
+            span(Color::Green, label),
+            value,
+            bar(value, ProgressColorScheme::System, &config.bars)
+        ));
+    }
+    out.push_str("</table>\n");
+
+    if let Some(status) = challenge {
+        out.push_str("<table>\n");
+        for (label, value) in status.info_items(&config.challenge) {
+            out.push_str(&info_row(&escape(label), &escape(&value)));
+        }
+        out.push_str(&info_row(
+            &escape(crate::i18n::tr("Progress")),
+            &format!(
+                "{}% {}",
+                status.progress_percentage,
+                bar(
+                    status.progress_percentage,
+                    ProgressColorScheme::Challenge,
+                    &config.bars
+                )
+            ),
+        ));
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("</div>\n</div>\n");
+    out
+}
+
+/// Full HTML document around the snippet, reloading itself every `refresh`
+pub fn render_page(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+    refresh: Duration,
+) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta http-equiv=\"refresh\" content=\"{}\">\n<title>huginn - {}</title>\n</head>\n<body style=\"background:#11111b;margin:2em\">\n{}</body>\n</html>\n",
+        refresh.as_secs().max(1),
+        escape(&fetch.user),
+        render_html(fetch, challenge, config)
+    )
+}
+
+/// Table row from an already escaped label and value
+fn info_row(label: &str, value: &str) -> String {
+    format!(
+        "<tr><td class=\"label\">{}</td><td>{}</td><td>{}</td></tr>\n",
+        label,
+        span(Color::Green, "•"),
+        value
+    )
+}
+
+fn bar(percentage: i32, scheme: ProgressColorScheme, bars: &BarsConfig) -> String {
+    format!(
+        "<span class=\"bar\"><span style=\"width:{}%;background:{}\"></span></span>",
+        percentage.clamp(0, 100),
+        to_hex(progress_color(percentage, scheme, bars))
+    )
+}
+
+fn span(color: Color, text: &str) -> String {
+    format!("<span style=\"color:{}\">{}</span>", to_hex(color), text)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,213 @@
+// Translations of labels and messages, keyed by their English text
+// To add a language, add a table below and list it in LANGUAGES
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type Table = &'static [(&'static str, &'static str)];
+
+const GERMAN: Table = &[
+    ("distro", "distro"),
+    ("model", "modell"),
+    ("age", "alter"),
+    ("kernel", "kernel"),
+    ("packages", "pakete"),
+    ("shell", "shell"),
+    ("term", "terminal"),
+    ("wm", "wm"),
+    ("cpu", "cpu"),
+    ("gpu", "gpu"),
+    ("theme", "thema"),
+    ("streak", "serie"),
+    ("day", "Tag"),
+    ("days", "Tage"),
+    ("hours", "Stunden"),
+    ("hrs", "Std"),
+    ("mins", "Min"),
+    ("weeks", "Wochen"),
+    ("months", "Monate"),
+    ("up", "seit"),
+    ("Hi! {user}", "Hallo {user}!"),
+    ("Installed", "Installiert"),
+    ("Started", "Begonnen"),
+    ("Current Age", "Alter"),
+    ("Time Left", "Verbleibend"),
+    ("Completes", "Endet am"),
+    ("Progress", "Fortschritt"),
+    ("Milestone", "Meilenstein"),
+    ("Reinstalled", "Neu installiert"),
+    ("Status", "Status"),
+    ("Challenge Complete!", "Challenge geschafft!"),
+    ("today", "heute"),
+    ("1 day left", "noch 1 Tag"),
+    ("{days} days left", "noch {days} Tage"),
+    ("1 day ago", "vor 1 Tag"),
+    ("{days} days ago", "vor {days} Tagen"),
+    (
+        "run huginn challenge reset",
+        "huginn challenge reset ausführen",
+    ),
+    (
+        "Challenge complete, congratulations!",
+        "Challenge geschafft, herzlichen Glückwunsch!",
+    ),
+    (
+        "Happy 1st install anniversary!",
+        "Alles Gute zum 1. Installationsjubiläum!",
+    ),
+    (
+        "Happy {nth} install anniversary!",
+        "Alles Gute zum {years}. Installationsjubiläum!",
+    ),
+];
+
+const SPANISH: Table = &[
+    ("distro", "distro"),
+    ("model", "modelo"),
+    ("age", "edad"),
+    ("kernel", "kernel"),
+    ("packages", "paquetes"),
+    ("shell", "shell"),
+    ("term", "terminal"),
+    ("wm", "wm"),
+    ("cpu", "cpu"),
+    ("gpu", "gpu"),
+    ("theme", "tema"),
+    ("streak", "racha"),
+    ("day", "día"),
+    ("days", "días"),
+    ("hours", "horas"),
+    ("hrs", "h"),
+    ("mins", "min"),
+    ("weeks", "semanas"),
+    ("months", "meses"),
+    ("up", "activo"),
+    ("Hi! {user}", "¡Hola, {user}!"),
+    ("Installed", "Instalado"),
+    ("Started", "Iniciado"),
+    ("Current Age", "Edad"),
+    ("Time Left", "Restante"),
+    ("Completes", "Termina"),
+    ("Progress", "Progreso"),
+    ("Milestone", "Hito"),
+    ("Reinstalled", "Reinstalado"),
+    ("Status", "Estado"),
+    ("Challenge Complete!", "¡Reto completado!"),
+    ("today", "hoy"),
+    ("1 day left", "queda 1 día"),
+    ("{days} days left", "quedan {days} días"),
+    ("1 day ago", "hace 1 día"),
+    ("{days} days ago", "hace {days} días"),
+    (
+        "run huginn challenge reset",
+        "ejecuta huginn challenge reset",
+    ),
+    (
+        "Challenge complete, congratulations!",
+        "¡Reto completado, enhorabuena!",
+    ),
+    (
+        "Happy 1st install anniversary!",
+        "¡Feliz 1.er aniversario de instalación!",
+    ),
+    (
+        "Happy {nth} install anniversary!",
+        "¡Feliz {years}.º aniversario de instalación!",
+    ),
+];
+
+const FRENCH: Table = &[
+    ("distro", "distro"),
+    ("model", "modèle"),
+    ("age", "âge"),
+    ("kernel", "noyau"),
+    ("packages", "paquets"),
+    ("shell", "shell"),
+    ("term", "terminal"),
+    ("wm", "wm"),
+    ("cpu", "cpu"),
+    ("gpu", "gpu"),
+    ("theme", "thème"),
+    ("streak", "série"),
+    ("day", "jour"),
+    ("days", "jours"),
+    ("hours", "heures"),
+    ("hrs", "h"),
+    ("mins", "min"),
+    ("weeks", "semaines"),
+    ("months", "mois"),
+    ("up", "actif"),
+    ("Hi! {user}", "Salut {user} !"),
+    ("Installed", "Installé"),
+    ("Started", "Commencé"),
+    ("Current Age", "Âge"),
+    ("Time Left", "Restant"),
+    ("Completes", "Fin"),
+    ("Progress", "Progression"),
+    ("Milestone", "Étape"),
+    ("Reinstalled", "Réinstallé"),
+    ("Status", "Statut"),
+    ("Challenge Complete!", "Défi terminé !"),
+    ("today", "aujourd'hui"),
+    ("1 day left", "1 jour restant"),
+    ("{days} days left", "{days} jours restants"),
+    ("1 day ago", "il y a 1 jour"),
+    ("{days} days ago", "il y a {days} jours"),
+    (
+        "run huginn challenge reset",
+        "lancez huginn challenge reset",
+    ),
+    (
+        "Challenge complete, congratulations!",
+        "Défi terminé, félicitations !",
+    ),
+    (
+        "Happy 1st install anniversary!",
+        "Joyeux 1er anniversaire d'installation !",
+    ),
+    (
+        "Happy {nth} install anniversary!",
+        "Joyeux {years}e anniversaire d'installation !",
+    ),
+];
+
+/// Language codes and their tables; English needs none
+const LANGUAGES: [(&str, Table); 3] = [("de", GERMAN), ("es", SPANISH), ("fr", FRENCH)];
+
+// Index into LANGUAGES, or usize::MAX for English
+static LANGUAGE: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Pick the language from the config, or from the locale when it is "auto"
+pub fn set_language(language: &str) {
+    let code = if language == "auto" {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+    } else {
+        language.to_string()
+    };
+    // "de_DE.UTF-8" -> "de"
+    let code = code
+        .split(['_', '.', '-', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let index = LANGUAGES.iter().position(|(known, _)| *known == code);
+    if index.is_none() && language != "auto" && code != "en" {
+        eprintln!("Warning: No translation for language: {}", language);
+    }
+    tracing::debug!(
+        "language {}",
+        index.map_or("en", |index| LANGUAGES[index].0)
+    );
+    LANGUAGE.store(index.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// The text in the current language, or as given if it has no translation
+pub fn tr(text: &str) -> &str {
+    LANGUAGES
+        .get(LANGUAGE.load(Ordering::Relaxed))
+        .and_then(|(_, table)| table.iter().find(|(key, _)| *key == text))
+        .map_or(text, |(_, translated)| translated)
+}
@@ -0,0 +1,69 @@
+// The `latency` field: round trip time of a single ping to the default
+// gateway, or to [display] latency_host
+use crate::error::{self, CollectError};
+
+/// Seconds ping waits for the reply
+const PING_TIMEOUT: &str = "1";
+
+/// Round trip time to `host`, or to the default gateway if it is empty
+pub fn measure(host: &str) -> Result<String, CollectError> {
+    crate::network::ensure_online("the latency field")
+        .map_err(|e| CollectError::Failed(e.to_string()))?;
+    let host = if host.is_empty() {
+        default_gateway().ok_or_else(|| CollectError::Failed("no default gateway".to_string()))?
+    } else {
+        host.to_string()
+    };
+
+    #[cfg(target_os = "linux")]
+    let args = ["-c", "1", "-W", PING_TIMEOUT, &host];
+    #[cfg(windows)]
+    let args = ["-n", "1", "-w", &format!("{}000", PING_TIMEOUT), &host];
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let args = ["-c", "1", "-t", PING_TIMEOUT, &host];
+
+    let output = error::run("ping", &args)?;
+    if !output.status.success() {
+        return Err(CollectError::Failed(format!("no reply from {}", host)));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    round_trip(&stdout)
+        .map(|ms| format!("{} ms", ms))
+        .ok_or_else(|| CollectError::Failed("no round trip time in ping's output".to_string()))
+}
+
+/// The number in "time=12.3 ms", or "time<1ms" on Windows
+fn round_trip(output: &str) -> Option<String> {
+    let start = output.find("time=").or_else(|| output.find("time<"))? + 5;
+    let rest = &output[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let ms = &rest[..end];
+    (!ms.is_empty()).then(|| ms.to_string())
+}
+
+/// The gateway of the default route, from the kernel's routing table
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<String> {
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    routes.lines().skip(1).find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.get(1) != Some(&"00000000") {
+            return None;
+        }
+        // Little-endian hex, so 010200C0 is 192.0.2.1
+        let gateway = u32::from_str_radix(columns.get(2)?, 16).ok()?;
+        Some(std::net::Ipv4Addr::from(gateway.to_le_bytes()).to_string())
+    })
+}
+
+/// The "gateway:" line of `route -n get default`
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<String> {
+    let output = error::run("route", &["-n", "get", "default"]).ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("gateway:"))
+        .map(|gateway| gateway.trim().to_string())
+}
@@ -0,0 +1,242 @@
+use crate::color::{self, paint};
+use crate::config::{Config, INFO_FIELDS};
+use crate::fetch::Fetch;
+use crate::logo::{draw_logo, logo_size};
+use crate::screen;
+use crate::{
+    colorbar_cells, draw_progress, get_colorbar, paint_greeting, visible_width, ProgressColorScheme,
+};
+use crate::{quote, tips};
+use crossterm::style::{Color, ResetColor, SetForegroundColor, Stylize};
+use crossterm::{cursor, execute};
+use std::io::{self, Write};
+
+#[derive(Clone, Copy)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A template line with its placeholders filled in
+struct Line {
+    text: String,
+    align: Align,
+    /// Column of a `{logo}` placeholder
+    logo_column: Option<usize>,
+}
+
+/// Print the fetch following a layout template, returning where the logo
+/// was drawn
+pub fn render(template: &str, config: &Config, fetch: &Fetch) -> io::Result<(u16, u16)> {
+    let (logo_width, logo_height) = logo_size(&config.logo);
+    let values = fetch.info.to_info_items(true, &config.display);
+
+    let mut lines = Vec::new();
+    for line in template.lines() {
+        let (align, rest) = split_align(line);
+        // Quotes and tips are wrapped over as many lines as they need
+        let block = match rest.trim() {
+            "{quote}" => Some(quote::quote_lines(&config.quote)),
+            "{tip}" => Some(tips::tip_lines(&config.tips)),
+            _ => None,
+        };
+        if let Some(block) = block {
+            lines.extend(block.into_iter().map(|(text, _)| Line {
+                text,
+                align,
+                logo_column: None,
+            }));
+        } else {
+            lines.push(expand(rest, align, logo_width as usize, |name| {
+                placeholder(name, config, fetch, &values)
+            }));
+        }
+    }
+    // Centered and right-aligned lines line up with the widest one
+    let width = lines
+        .iter()
+        .map(|line| visible_width(&line.text))
+        .max()
+        .unwrap_or(0);
+
+    let mut logo_origin = None;
+    let mut row = 0;
+    for line in &lines {
+        let padding = match line.align {
+            Align::Left => 0,
+            Align::Center => (width - visible_width(&line.text)) / 2,
+            Align::Right => width - visible_width(&line.text),
+        };
+        write!(screen::out(), "{}{}", " ".repeat(padding), line.text)?;
+        // Colors from {color:...} end with their line
+        if color::enabled() {
+            write!(screen::out(), "{}", ResetColor)?;
+        }
+        writeln!(screen::out())?;
+
+        if let Some(column) = line.logo_column {
+            logo_origin = Some((padding + column, row));
+            // A logo on a line of its own gets the rows it needs
+            if line.text.trim().is_empty() {
+                for _ in 1..logo_height {
+                    writeln!(screen::out())?;
+                    row += 1;
+                }
+            }
+        }
+        row += 1;
+    }
+
+    let Some((x, y)) = logo_origin else {
+        return Ok((0, 0));
+    };
+    let shift = |value: usize, offset: i32| (value as i32 + offset).max(0) as u16;
+    let origin = (
+        shift(x, config.logo.x_offset),
+        shift(y, config.logo.y_offset),
+    );
+    draw_logo(
+        &fetch.logo_subject(&config.logo),
+        origin.0,
+        origin.1,
+        &config.logo,
+    );
+    execute!(screen::out(), cursor::MoveTo(0, row as u16))?;
+
+    Ok(origin)
+}
+
+/// Alignment directive at the start of a line, and the rest of it
+fn split_align(line: &str) -> (Align, &str) {
+    if let Some(rest) = line.strip_prefix("{center}") {
+        (Align::Center, rest)
+    } else if let Some(rest) = line.strip_prefix("{right}") {
+        (Align::Right, rest)
+    } else {
+        (Align::Left, line.strip_prefix("{left}").unwrap_or(line))
+    }
+}
+
+/// Fill in the placeholders of one template line, looking each up with
+/// `placeholder`
+fn expand(
+    mut rest: &str,
+    align: Align,
+    logo_width: usize,
+    placeholder: impl Fn(&str) -> Option<String>,
+) -> Line {
+    let mut text = String::new();
+    let mut logo_column = None;
+    while let Some(start) = rest.find('{') {
+        // An unclosed brace is kept as written along with the rest
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + len];
+        rest = &rest[start + len + 1..];
+
+        if name == "logo" {
+            logo_column = Some(visible_width(&text));
+            text.push_str(&" ".repeat(logo_width));
+        } else if let Some(value) = placeholder(name) {
+            text.push_str(&value);
+        } else {
+            // Unknown placeholders are kept as written
+            text.push('{');
+            text.push_str(name);
+            text.push('}');
+        }
+    }
+    text.push_str(rest);
+
+    Line {
+        text,
+        align,
+        logo_column,
+    }
+}
+
+/// Text for a placeholder, or None if it isn't one
+fn placeholder(
+    name: &str,
+    config: &Config,
+    fetch: &Fetch,
+    values: &[(&str, String)],
+) -> Option<String> {
+    let text = match name.split_once(':') {
+        Some(("label", field)) if INFO_FIELDS.contains(&field) => {
+            config.info.painted_label(field, 0)
+        }
+        Some(("bar", usage)) => {
+            let percentage = match usage {
+                "cpu" => fetch.cpu_usage,
+                "ram" => fetch.ram_usage,
+                "disk" => fetch.disk_usage,
+                _ => return None,
+            };
+            draw_progress(percentage, 3, ProgressColorScheme::System, &config.bars)
+        }
+        Some(("color", value)) => {
+            let Some(color) = color::parse_color(value) else {
+                eprintln!("Warning: Unknown layout color: {}", value);
+                return Some(String::new());
+            };
+            if color::enabled() {
+                SetForegroundColor(color::fit(color)).to_string()
+            } else {
+                String::new()
+            }
+        }
+        Some(_) => return None,
+        None => match name {
+            "user" => paint(fetch.user.as_str().cyan().bold()),
+            "uptime" => paint(fetch.uptime.as_str().cyan().bold()),
+            "hostname" => paint(fetch.hostname.as_str().cyan().bold()),
+            "greeting" => config
+                .greeting
+                .parts(&fetch.user, &fetch.hostname)
+                .map_or(String::new(), |parts| {
+                    paint_greeting(&parts, Color::Green, Color::Cyan).0
+                }),
+            "sep" => config.info.painted_separator(),
+            "colorbar" => get_colorbar(colorbar_cells(&config.colorbar)),
+            "reset" if color::enabled() => ResetColor.to_string(),
+            "reset" => String::new(),
+            // Fields turned off in [display] are left empty
+            field if INFO_FIELDS.contains(&field) => values
+                .iter()
+                .find(|(label, _)| *label == field)
+                .map(|(_, value)| config.info.painted_value(field, value))
+                .unwrap_or_default(),
+            _ => return None,
+        },
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_text(line: &str) -> String {
+        expand(line, Align::Left, 4, |name| {
+            (name == "user").then(|| "huginn".to_string())
+        })
+        .text
+    }
+
+    #[test]
+    fn expand_fills_placeholders() {
+        assert_eq!(expand_text("{user}@host"), "huginn@host");
+        assert_eq!(expand_text("a{unknown}b"), "a{unknown}b");
+        assert_eq!(expand_text("<{logo}>"), "<    >");
+    }
+
+    #[test]
+    fn expand_keeps_unclosed_brace() {
+        assert_eq!(expand_text("a{b"), "a{b");
+        assert_eq!(expand_text("{user} a{b"), "huginn a{b");
+    }
+}
@@ -0,0 +1,129 @@
+// Debug logging for bug reports, enabled with --debug or HUGINN_LOG
+// A minimal tracing subscriber: events from huginn itself, one line each
+use std::fmt::{Debug, Write as _};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{span, Event, Level, Metadata, Subscriber};
+
+/// Start logging if asked to, to `file` or stderr when it is "-"
+/// HUGINN_LOG picks the level (error, warn, info, debug or trace) and
+/// enables logging on its own; --debug alone means debug
+pub fn init(file: Option<&Path>) {
+    let level = match std::env::var("HUGINN_LOG") {
+        Ok(value) if value.eq_ignore_ascii_case("off") => return,
+        Ok(value) => match value.parse::<Level>() {
+            Ok(level) => level,
+            Err(_) => {
+                eprintln!("Warning: Unknown HUGINN_LOG level: {}", value);
+                Level::DEBUG
+            }
+        },
+        Err(_) if file.is_some() => Level::DEBUG,
+        Err(_) => return,
+    };
+
+    let out: Box<dyn Write + Send> = match file {
+        Some(path) if path != Path::new("-") => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("Warning: could not open log file {}: {}", path.display(), e);
+                Box::new(io::stderr())
+            }
+        },
+        _ => Box::new(io::stderr()),
+    };
+
+    let logger = Logger {
+        level,
+        start: Instant::now(),
+        out: Mutex::new(out),
+        next_span: AtomicU64::new(1),
+    };
+    if tracing::subscriber::set_global_default(logger).is_err() {
+        eprintln!("Warning: logging was already set up");
+    }
+}
+
+struct Logger {
+    level: Level,
+    start: Instant,
+    out: Mutex<Box<dyn Write + Send>>,
+    next_span: AtomicU64,
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Dependencies like zbus log through tracing too; leave them out
+        *metadata.level() <= self.level && metadata.target().starts_with("huginn")
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+
+    // Spans aren't printed, but each needs a distinct id
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = Line::default();
+        event.record(&mut line);
+
+        let metadata = event.metadata();
+        let target = metadata.target().trim_start_matches("huginn::");
+        if let Ok(mut out) = self.out.lock() {
+            // Timed under the lock so lines from collector threads stay in order
+            let elapsed = self.start.elapsed().as_secs_f64() * 1000.0;
+            // Nowhere left to report a failed write to
+            let _ = writeln!(
+                out,
+                "[{:>9.3}ms {:>5} {}] {}{}",
+                elapsed,
+                metadata.level(),
+                target,
+                line.message,
+                line.fields
+            );
+        }
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+/// An event's message, followed by its other fields as key=value
+#[derive(Default)]
+struct Line {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Line {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
@@ -0,0 +1,767 @@
+use crate::color;
+use crate::config::{data_dir, LogoConfig};
+use crate::expand_home;
+use crate::graphics;
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::execute;
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, ImageFormat};
+use resvg::tiny_skia::Pixmap;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::{NamedTempFile, TempPath};
+use tracing::debug;
+use viuer::{print_from_file, Config as ViuerConfig};
+
+pub fn logo_dir() -> PathBuf {
+    data_dir().join("logos")
+}
+
+/// Logos compiled into the binary, so huginn works without any setup
+/// A file with the same name in the logo directory takes precedence
+const BUILTIN_LOGOS: [(&str, &[u8]); 17] = [
+    ("arch.svg", include_bytes!("../logos/arch.svg")),
+    ("debian.svg", include_bytes!("../logos/debian.svg")),
+    (
+        "endeavouros.svg",
+        include_bytes!("../logos/endeavouros.svg"),
+    ),
+    ("fedora.svg", include_bytes!("../logos/fedora.svg")),
+    ("freebsd.svg", include_bytes!("../logos/freebsd.svg")),
+    ("garuda.svg", include_bytes!("../logos/garuda.svg")),
+    ("gentoo.svg", include_bytes!("../logos/gentoo.svg")),
+    ("guix.svg", include_bytes!("../logos/guix.svg")),
+    ("linux.svg", include_bytes!("../logos/linux.svg")),
+    ("macos.svg", include_bytes!("../logos/macos.svg")),
+    ("manjaro.svg", include_bytes!("../logos/manjaro.svg")),
+    ("mint.svg", include_bytes!("../logos/mint.svg")),
+    ("nixos.svg", include_bytes!("../logos/nixos.svg")),
+    ("obsidian.svg", include_bytes!("../logos/obsidian.svg")),
+    ("openbsd.svg", include_bytes!("../logos/openbsd.svg")),
+    ("ubuntu.svg", include_bytes!("../logos/ubuntu.svg")),
+    ("windows.svg", include_bytes!("../logos/windows.svg")),
+];
+
+/// Built-in distro name fragments and their logo files, checked in order
+const DISTRO_LOGOS: [(&str, &str); 19] = [
+    ("arch", "arch.svg"),
+    ("debian", "debian.svg"),
+    ("endeavour", "endeavouros.svg"),
+    ("fedora", "fedora.svg"),
+    ("freebsd", "freebsd.svg"),
+    ("garuda", "garuda.svg"),
+    ("gentoo", "gentoo.svg"),
+    ("guix", "guix.svg"),
+    ("lmde", "lmde.svg"),
+    ("macos", "macos.svg"),
+    ("manjaro", "manjaro.svg"),
+    ("mint", "mint.svg"),
+    ("nixos", "nixos.svg"),
+    ("obsidian", "obsidian.svg"),
+    ("openbsd", "openbsd.svg"),
+    ("popos", "popos.svg"),
+    ("ubuntu", "ubuntu.svg"),
+    ("venom", "venom.svg"),
+    ("windows", "windows.svg"),
+];
+
+/// Single-board computer model fragments and their logo files, shown
+/// instead of the distro's logo once the file is in the logo directory
+const BOARD_LOGOS: [(&str, &str); 6] = [
+    ("raspberry pi", "raspberrypi.svg"),
+    ("pine64", "pine64.svg"),
+    ("orange pi", "orangepi.svg"),
+    ("odroid", "odroid.svg"),
+    ("beaglebone", "beagleboard.svg"),
+    ("radxa", "radxa.svg"),
+];
+
+/// Logo file for a distro or board, from [logo.map] first and then the
+/// built-in tables
+/// User entries match on the longest name fragment found in the distro name
+fn logo_name(distro: &str, logo_config: &LogoConfig) -> String {
+    let distro = distro.to_lowercase();
+
+    let user_match = logo_config
+        .map
+        .iter()
+        .filter(|(fragment, _)| distro.contains(&fragment.to_lowercase()))
+        .max_by_key(|(fragment, _)| fragment.len());
+    if let Some((_, file)) = user_match {
+        return expand_home(file);
+    }
+    known_logo_name(&distro)
+}
+
+/// The logo file huginn knows for a lowercase distro or board name,
+/// ignoring [logo.map]
+fn known_logo_name(distro: &str) -> String {
+    BOARD_LOGOS
+        .iter()
+        .chain(&DISTRO_LOGOS)
+        .find(|(fragment, _)| distro.contains(fragment))
+        .map(|(_, file)| file.to_string())
+        .unwrap_or_else(|| "linux.svg".to_string())
+}
+
+/// Whether `name` is a plain file name, which can't reach outside the logo
+/// directory or the repository's logos
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+        && !Path::new(name).is_absolute()
+}
+
+/// Where a distro logo's SVG comes from
+enum LogoSource {
+    File(PathBuf),
+    Builtin(&'static str, &'static [u8]),
+}
+
+impl LogoSource {
+    fn data(&self) -> Option<Vec<u8>> {
+        match self {
+            LogoSource::File(path) => std::fs::read(path).ok(),
+            LogoSource::Builtin(_, data) => Some(data.to_vec()),
+        }
+    }
+}
+
+/// The distro logo, trying the logo directory before the built-in copy,
+/// then the same for the generic linux.svg fallback
+fn resolve_logo(distro: &str, logo_config: &LogoConfig) -> LogoSource {
+    for name in [logo_name(distro, logo_config).as_str(), "linux.svg"] {
+        // Absolute paths from [logo.map] replace the logo directory entirely
+        let path = logo_dir().join(name);
+        if path.exists() {
+            debug!("logo for {:?} from {}", distro, path.display());
+            return LogoSource::File(path);
+        }
+        if let Some((name, data)) = BUILTIN_LOGOS.iter().find(|(n, _)| *n == name) {
+            debug!("logo for {:?} is the built-in {}", distro, name);
+            return LogoSource::Builtin(name, data);
+        }
+    }
+
+    let (name, data) = BUILTIN_LOGOS
+        .iter()
+        .find(|(n, _)| *n == "linux.svg")
+        .expect("linux.svg is built in");
+    LogoSource::Builtin(name, data)
+}
+
+/// The distro's own logo file, if there is one besides the generic linux.svg
+pub fn distro_logo(distro: &str, logo_config: &LogoConfig) -> Option<String> {
+    let name = logo_name(distro, logo_config);
+    let found = logo_dir().join(&name).exists() || BUILTIN_LOGOS.iter().any(|(n, _)| *n == name);
+    (found && name != "linux.svg").then_some(name)
+}
+
+/// What the logo is picked for: the board, if there is a logo for it, or
+/// else the distro
+pub fn logo_subject(distro: &str, board: Option<&str>, logo_config: &LogoConfig) -> String {
+    board
+        .filter(|board| distro_logo(board, logo_config).is_some())
+        .unwrap_or(distro)
+        .to_string()
+}
+
+/// Render SVG data into a pixmap scaled to fit the requested size
+pub fn rasterize_svg(svg_data: &[u8], width: u32, height: u32) -> Option<Pixmap> {
+    use resvg::usvg;
+
+    let options = usvg::Options::default();
+    let tree = usvg::Tree::from_data(svg_data, &options).ok()?;
+
+    let size = tree.size();
+    let scale_x = width as f32 / size.width();
+    let scale_y = height as f32 / size.height();
+    let scale = scale_x.min(scale_y);
+
+    let mut pixmap = Pixmap::new(width, height)?;
+    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    Some(pixmap)
+}
+
+pub fn cache_dir() -> PathBuf {
+    crate::config::cache_dir().join("logos")
+}
+
+/// A rendered logo PNG, either in the cache or in a private temp file
+/// that is removed when dropped
+enum LogoPng {
+    Cached(PathBuf),
+    Temp(TempPath),
+}
+
+impl LogoPng {
+    fn path(&self) -> &Path {
+        match self {
+            LogoPng::Cached(path) => path,
+            LogoPng::Temp(path) => path,
+        }
+    }
+}
+
+/// Paint every visible pixel in one color, keeping its transparency, so a
+/// monochrome logo takes on the tint
+fn tint_pixmap(pixmap: &mut Pixmap, (r, g, b): (u8, u8, u8)) {
+    use resvg::tiny_skia::PremultipliedColorU8;
+
+    for pixel in pixmap.pixels_mut() {
+        let alpha = pixel.alpha();
+        let scale = |channel: u8| (channel as u16 * alpha as u16 / 255) as u8;
+        if let Some(tinted) = PremultipliedColorU8::from_rgba(scale(r), scale(g), scale(b), alpha) {
+            *pixel = tinted;
+        }
+    }
+}
+
+/// The configured tint as RGB, warning about values that don't parse
+fn logo_tint(logo_config: &LogoConfig) -> Option<(u8, u8, u8)> {
+    let tint = logo_config.tint.as_deref()?;
+    match color::parse_color(tint) {
+        Some(color) => Some(color::to_rgb(color)),
+        None => {
+            eprintln!("Warning: Unknown logo tint: {}", tint);
+            None
+        }
+    }
+}
+
+/// PNG rendering of the SVG at the given size, re-rendered only when the
+/// SVG file is newer than the cached copy
+fn cached_png(
+    source: &LogoSource,
+    width: u32,
+    height: u32,
+    tint: Option<(u8, u8, u8)>,
+) -> Option<LogoPng> {
+    let dir = cache_dir();
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    // Tinted renders are cached next to the original ones
+    let size = match tint {
+        Some((r, g, b)) => format!("{}x{}-{:02x}{:02x}{:02x}", width, height, r, g, b),
+        None => format!("{}x{}", width, height),
+    };
+
+    let png_path = match source {
+        // Two files with the same name in different directories each get
+        // their own render
+        LogoSource::File(svg_path) => {
+            use std::hash::{DefaultHasher, Hash, Hasher};
+            let mut hasher = DefaultHasher::new();
+            std::fs::canonicalize(svg_path)
+                .unwrap_or_else(|_| svg_path.clone())
+                .hash(&mut hasher);
+
+            let name = svg_path.file_stem()?.to_string_lossy();
+            let png_path = dir.join(format!("{}-{:016x}-{}.png", name, hasher.finish(), size));
+            if let (Some(svg_time), Some(png_time)) = (modified(svg_path), modified(&png_path)) {
+                if png_time >= svg_time {
+                    debug!("cached render {}", png_path.display());
+                    return Some(LogoPng::Cached(png_path));
+                }
+            }
+            png_path
+        }
+        // Built-in logos only change with a new binary, so key them by content
+        LogoSource::Builtin(name, data) => {
+            use std::hash::{DefaultHasher, Hash, Hasher};
+            let mut hasher = DefaultHasher::new();
+            data.hash(&mut hasher);
+
+            let stem = name.trim_end_matches(".svg");
+            let png_path = dir.join(format!(
+                "builtin-{}-{:016x}-{}.png",
+                stem,
+                hasher.finish(),
+                size
+            ));
+            if png_path.exists() {
+                debug!("cached render {}", png_path.display());
+                return Some(LogoPng::Cached(png_path));
+            }
+            png_path
+        }
+    };
+    debug!(
+        "rendering {}x{} PNG into {}",
+        width,
+        height,
+        png_path.display()
+    );
+
+    let mut pixmap = rasterize_svg(&source.data()?, width, height)?;
+    if let Some(tint) = tint {
+        tint_pixmap(&mut pixmap, tint);
+    }
+    let png = pixmap.encode_png().ok()?;
+
+    // Write to a unique file and rename it into place, so concurrent runs
+    // never see a half-written PNG
+    let cached = std::fs::create_dir_all(&dir)
+        .and_then(|_| write_temp_png(NamedTempFile::new_in(&dir)?, &png))
+        .and_then(|temp| temp.persist(&png_path).map_err(|e| e.error));
+    if cached.is_ok() {
+        return Some(LogoPng::Cached(png_path));
+    }
+
+    // No usable cache directory, fall back to a private temp file
+    let temp = write_temp_png(NamedTempFile::new().ok()?, &png).ok()?;
+    Some(LogoPng::Temp(temp))
+}
+
+fn write_temp_png(mut file: NamedTempFile, png: &[u8]) -> std::io::Result<TempPath> {
+    file.write_all(png)?;
+    Ok(file.into_temp_path())
+}
+
+/// Encoded image bytes and MIME type of the logo that would be displayed,
+/// for exports that embed the logo instead of drawing it in the terminal
+pub fn logo_image_data(distro: &str, logo_config: &LogoConfig) -> Option<(Vec<u8>, &'static str)> {
+    if logo_config.custom_path.is_empty() {
+        let png = cached_png(
+            &resolve_logo(distro, logo_config),
+            400,
+            400,
+            logo_tint(logo_config),
+        )?;
+        return Some((std::fs::read(png.path()).ok()?, "image/png"));
+    }
+
+    let path = PathBuf::from(expand_home(&logo_config.custom_path));
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "svg" => Some((
+            std::fs::read(
+                cached_png(&LogoSource::File(path), 400, 400, logo_tint(logo_config))?.path(),
+            )
+            .ok()?,
+            "image/png",
+        )),
+        "png" => Some((std::fs::read(&path).ok()?, "image/png")),
+        "jpg" | "jpeg" => Some((std::fs::read(&path).ok()?, "image/jpeg")),
+        "gif" => Some((std::fs::read(&path).ok()?, "image/gif")),
+        "webp" => Some((std::fs::read(&path).ok()?, "image/webp")),
+        _ => None,
+    }
+}
+
+/// Draw the image with the configured backend, falling back to viuer
+fn show_image(path: &Path, conf: &ViuerConfig, logo_config: &LogoConfig) {
+    let width = conf.width.unwrap_or(20);
+    let height = conf.height.unwrap_or(10);
+    let backend = graphics::resolve(&logo_config.backend);
+    debug!(
+        "graphics backend {} ({}), {:?}",
+        backend,
+        logo_config.backend,
+        graphics::capabilities()
+    );
+
+    match backend {
+        "kitty" => {
+            let _ = graphics::print_kitty(path, conf.x, conf.y as u16, width, height);
+        }
+        "iterm" => {
+            let _ = graphics::print_iterm(path, conf.x, conf.y as u16, width, height);
+        }
+        // Only if the terminal answered that it can
+        "sixel" if graphics::sixel_supported() => {
+            let _ = graphics::print_sixel(path, conf.x, conf.y as u16, width, height);
+        }
+        // Our own block renderer beats viuer's, which only uses half blocks
+        "sixel" | "blocks" => {
+            let _ = graphics::print_blocks(path, conf.x, conf.y as u16, width, height);
+        }
+        _ => {
+            debug!("drawing the logo with viuer");
+            let _ = print_from_file(path, conf);
+            return;
+        }
+    }
+
+    // Leave the cursor below the logo like viuer does
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        crossterm::cursor::MoveTo(0, conf.y as u16 + height as u16)
+    );
+}
+
+/// Graphics protocols this terminal supports, best first
+pub fn graphics_protocols() -> Vec<&'static str> {
+    let caps = graphics::capabilities();
+    [
+        ("kitty", caps.kitty),
+        ("iterm", caps.iterm),
+        ("sixel", caps.sixel),
+    ]
+    .into_iter()
+    .filter_map(|(name, supported)| supported.then_some(name))
+    .collect()
+}
+
+/// Size in cells the logo is drawn at
+pub fn logo_size(logo_config: &LogoConfig) -> (u32, u32) {
+    if logo_config.hidden() {
+        (0, 0)
+    } else if logo_config.custom_path.is_empty() {
+        (20, 10)
+    } else {
+        (
+            logo_config.width.unwrap_or(35),
+            logo_config.height.unwrap_or(18),
+        )
+    }
+}
+
+/// Draw the custom logo if one is configured, otherwise the distro logo,
+/// with its top-left corner at cell (x, y)
+pub fn draw_logo(distro: &str, x: u16, y: u16, logo_config: &LogoConfig) {
+    if logo_config.hidden() {
+        return;
+    }
+    if crate::screen::capturing() {
+        let (_, height) = logo_size(logo_config);
+        crate::screen::defer_logo(distro, x, y, height as u16);
+        return;
+    }
+    if logo_config.custom_path.is_empty() {
+        display_logo(distro, x, y, logo_config);
+    } else {
+        display_custom_logo(&expand_home(&logo_config.custom_path), x, y, logo_config);
+    }
+}
+
+fn viuer_config(x: u16, y: u16, logo_config: &LogoConfig) -> ViuerConfig {
+    let (width, height) = logo_size(logo_config);
+    ViuerConfig {
+        width: Some(width),
+        height: Some(height),
+        x,
+        y: y as i16,
+        absolute_offset: true,
+        transparent: true,
+        ..Default::default()
+    }
+}
+
+fn display_logo(distro: &str, x: u16, y: u16, logo_config: &LogoConfig) {
+    // Convert the distro (or fallback) SVG to PNG
+    display_source(&resolve_logo(distro, logo_config), x, y, logo_config);
+}
+
+fn display_source(source: &LogoSource, x: u16, y: u16, logo_config: &LogoConfig) {
+    let conf = viuer_config(x, y, logo_config);
+    if let Some(png_path) = cached_png(source, 400, 400, logo_tint(logo_config)) {
+        show_image(png_path.path(), &conf, logo_config);
+    }
+}
+
+fn display_custom_logo(image_path: &str, x: u16, y: u16, logo_config: &LogoConfig) {
+    let conf = viuer_config(x, y, logo_config);
+
+    // Try to display the custom image
+    let path = PathBuf::from(image_path);
+    if path.exists() {
+        show_image(&path, &conf, logo_config);
+    } else {
+        eprintln!("Warning: Custom logo not found at: {}", image_path);
+    }
+}
+
+/// Frames of an animated GIF custom logo, decoded once and kept as PNGs
+pub struct LogoAnimation {
+    frames: Vec<(TempPath, Duration)>,
+    next: usize,
+}
+
+impl LogoAnimation {
+    /// Decode the custom logo if it is a GIF with more than one frame
+    pub fn load(logo_config: &LogoConfig) -> Option<Self> {
+        let path = PathBuf::from(expand_home(&logo_config.custom_path));
+        let is_gif = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+        if !is_gif {
+            return None;
+        }
+
+        let file = BufReader::new(File::open(&path).ok()?);
+        let decoded = GifDecoder::new(file)
+            .ok()?
+            .into_frames()
+            .collect_frames()
+            .ok()?;
+        if decoded.len() < 2 {
+            return None;
+        }
+
+        let mut frames = Vec::with_capacity(decoded.len());
+        for frame in decoded {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            // Browsers play a delay of 0 at 10fps, and cap very short ones
+            let delay = match numer / denom.max(1) {
+                0 => 100,
+                ms => ms.max(20),
+            };
+
+            let file = tempfile::Builder::new().suffix(".png").tempfile().ok()?;
+            frame
+                .into_buffer()
+                .save_with_format(file.path(), ImageFormat::Png)
+                .ok()?;
+            frames.push((file.into_temp_path(), Duration::from_millis(delay as u64)));
+        }
+
+        Some(Self { frames, next: 0 })
+    }
+
+    /// Keep drawing frames at cell (x, y) until `until` or a key press,
+    /// touching only the cells under the logo
+    pub fn play_until(&mut self, x: u16, y: u16, until: Instant, logo_config: &LogoConfig) {
+        let (width, height) = logo_size(logo_config);
+        let conf = viuer_config(x, y, logo_config);
+        let mut stdout = std::io::stdout();
+
+        while Instant::now() < until {
+            let (path, delay) = &self.frames[self.next];
+            self.next = (self.next + 1) % self.frames.len();
+
+            let _ = execute!(stdout, SavePosition);
+            // Transparent pixels would otherwise show the previous frame
+            if uses_kitty(&logo_config.backend) {
+                let _ = graphics::clear_kitty_at(x, y);
+            }
+            for row in y..y + height as u16 {
+                let _ = execute!(stdout, MoveTo(x, row));
+                print!("{}", " ".repeat(width as usize));
+            }
+            show_image(path, &conf, logo_config);
+            let _ = execute!(stdout, RestorePosition);
+
+            let wait = (*delay).min(until.saturating_duration_since(Instant::now()));
+            if crossterm::event::poll(wait).unwrap_or(false) {
+                return;
+            }
+        }
+    }
+}
+
+fn uses_kitty(backend: &str) -> bool {
+    graphics::resolve(backend) == "kitty"
+}
+
+/// A logo file huginn knows about and where it would be loaded from
+struct LogoEntry {
+    name: String,
+    builtin: bool,
+    user_file: Option<PathBuf>,
+    distros: Vec<String>,
+}
+
+impl LogoEntry {
+    fn source(&self) -> Option<LogoSource> {
+        if let Some(path) = &self.user_file {
+            return Some(LogoSource::File(path.clone()));
+        }
+        BUILTIN_LOGOS
+            .iter()
+            .find(|(n, _)| *n == self.name)
+            .map(|(name, data)| LogoSource::Builtin(name, data))
+    }
+
+    fn origin(&self) -> &'static str {
+        match (self.builtin, self.user_file.is_some()) {
+            (true, true) => "user, overrides built-in",
+            (true, false) => "built-in",
+            (false, true) => "user",
+            (false, false) => "not installed",
+        }
+    }
+}
+
+/// Every built-in logo, SVG in the logo directory and logo file named in the
+/// distro and board tables, sorted by file name
+fn logo_entries(logo_config: &LogoConfig) -> Vec<LogoEntry> {
+    fn entry<'a>(entries: &'a mut BTreeMap<String, LogoEntry>, name: &str) -> &'a mut LogoEntry {
+        entries
+            .entry(name.to_string())
+            .or_insert_with(|| LogoEntry {
+                name: name.to_string(),
+                builtin: false,
+                user_file: None,
+                distros: Vec::new(),
+            })
+    }
+    let mut entries = BTreeMap::new();
+
+    for (name, _) in BUILTIN_LOGOS {
+        entry(&mut entries, name).builtin = true;
+    }
+
+    let user_files = std::fs::read_dir(logo_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "svg"));
+    for path in user_files {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        entry(&mut entries, &name).user_file = Some(path);
+    }
+
+    for (fragment, file) in BOARD_LOGOS.iter().chain(&DISTRO_LOGOS) {
+        entry(&mut entries, file).distros.push(fragment.to_string());
+    }
+    entry(&mut entries, "linux.svg")
+        .distros
+        .push("anything else".to_string());
+    for (fragment, file) in &logo_config.map {
+        let file = expand_home(file);
+        let path = logo_dir().join(&file);
+        let logo = entry(&mut entries, &file);
+        logo.distros.push(format!("{} (logo.map)", fragment));
+        if path.exists() {
+            logo.user_file = Some(path);
+        }
+    }
+
+    entries.into_values().collect()
+}
+
+/// `huginn logos`: list the available logos, optionally drawing each one
+pub fn list_logos(logo_config: &LogoConfig, preview: bool) -> std::io::Result<()> {
+    let entries = logo_entries(logo_config);
+    println!("Logo directory: {}\n", logo_dir().display());
+
+    if !preview {
+        let name_width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+        for entry in &entries {
+            println!(
+                "{:<name_width$}  {:<24}  {}",
+                entry.name,
+                entry.origin(),
+                entry.distros.join(", "),
+            );
+        }
+        return Ok(());
+    }
+
+    // Previews use the distro logo size even when a custom logo is set or
+    // the logo is hidden
+    let preview_config = LogoConfig {
+        custom_path: String::new(),
+        position: "top".to_string(),
+        ..logo_config.clone()
+    };
+    let (_, height) = logo_size(&preview_config);
+    let mut stdout = std::io::stdout();
+
+    for entry in &entries {
+        let Some(source) = entry.source() else {
+            continue;
+        };
+        println!("{} ({})", entry.name, entry.origin());
+
+        // Make room first, so the logo lands below the label even when the
+        // terminal scrolls
+        print!("{}", "\n".repeat(height as usize + 1));
+        stdout.flush()?;
+        let (_, row) = crossterm::cursor::position()?;
+        let top = row.saturating_sub(height as u16 + 1);
+
+        display_source(&source, 2, top, &preview_config);
+        execute!(stdout, MoveTo(0, row))?;
+    }
+
+    Ok(())
+}
+
+/// Where `huginn logos install` downloads logos from
+const LOGO_REPO_URL: &str = "https://codeberg.org/wyfy/huginn/raw/branch/main/logos";
+
+/// `huginn logos install`: download a distro's logo, or the whole set with
+/// "all", from the project repository into the logo directory
+pub fn install_logos(
+    target: &str,
+    logo_config: &LogoConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    crate::network::ensure_online("huginn logos install")?;
+
+    let names: Vec<String> = if target == "all" {
+        // The built-in logos are the ones the repository has
+        BUILTIN_LOGOS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    } else {
+        // Accept both distro names ("Linux Mint") and file stems ("popos")
+        let mut name = logo_name(target, logo_config);
+        // A [logo.map] entry can be a path of its own; fetch the logo it
+        // stands in for instead
+        if !is_plain_file_name(&name) {
+            name = known_logo_name(&target.to_lowercase());
+        }
+        if name == "linux.svg" && !target.eq_ignore_ascii_case("linux") {
+            name = format!("{}.svg", target.to_lowercase());
+        }
+        if !is_plain_file_name(&name) {
+            return Err(format!("{} is not a logo name", target).into());
+        }
+        vec![name]
+    };
+
+    let dir = logo_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let mut failed = 0;
+    for name in &names {
+        match download_logo(name) {
+            Ok(svg) => {
+                let mut file = NamedTempFile::new_in(&dir)?;
+                file.write_all(&svg)?;
+                file.persist(dir.join(name)).map_err(|e| e.error)?;
+                println!("Installed {}", name);
+            }
+            Err(e) => {
+                eprintln!("Could not download {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed == names.len() {
+        return Err("no logos were installed".into());
+    }
+    println!("Logos are in {}", dir.display());
+    Ok(())
+}
+
+fn download_logo(name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let url = format!("{}/{}", LOGO_REPO_URL, name);
+    let svg = ureq::get(&url).call()?.body_mut().read_to_vec()?;
+
+    // Don't save an error page in place of a logo
+    resvg::usvg::Tree::from_data(&svg, &resvg::usvg::Options::default())
+        .map_err(|_| "not a valid SVG")?;
+    Ok(svg)
+}
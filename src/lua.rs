@@ -0,0 +1,82 @@
+// Lua hooks from ~/.config/huginn/huginn.lua, run after collecting:
+//   function on_collect(info)          -- rewrite, add or remove fields
+//   function format_line(label, value) -- return label, value, or nil to hide
+use crate::config::{Config, INFO_FIELDS};
+use crate::fetch::Fetch;
+use mlua::{Function, Lua, Table};
+use std::path::PathBuf;
+
+pub fn script_path() -> PathBuf {
+    let home = crate::config::home_dir().unwrap_or_default();
+    PathBuf::from(home).join(".config/huginn/huginn.lua")
+}
+
+/// Run the script's hooks over the fetch, if there is a script
+/// Labels that format_line renames end up in [info.labels]
+pub fn run_hooks(fetch: &mut Fetch, config: &mut Config) -> Result<(), Box<dyn std::error::Error>> {
+    let path = script_path();
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let lua = Lua::new();
+    lua.load(std::fs::read_to_string(&path)?)
+        .set_name(path.display().to_string())
+        .exec()?;
+    let globals = lua.globals();
+
+    if let Some(on_collect) = globals.get::<_, Option<Function>>("on_collect")? {
+        let info = lua.create_table()?;
+        for (name, value) in fetch.info.fields() {
+            info.set(name, value)?;
+        }
+        for field in &fetch.info.plugins {
+            info.set(field.label.as_str(), field.value.as_str())?;
+        }
+
+        // The hook can change the table in place or return a new one
+        let info = on_collect
+            .call::<_, Option<Table>>(info.clone())?
+            .unwrap_or(info);
+        let mut names: Vec<String> = INFO_FIELDS.iter().map(|name| name.to_string()).collect();
+        names.extend(fetch.info.plugins.iter().map(|field| field.label.clone()));
+        for pair in info.clone().pairs::<String, mlua::Value>() {
+            let (name, _) = pair?;
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        for name in names {
+            fetch
+                .info
+                .set(&name, info.get::<_, Option<String>>(name.as_str())?);
+        }
+    }
+
+    if let Some(format_line) = globals.get::<_, Option<Function>>("format_line")? {
+        let lines: Vec<(String, String)> = fetch
+            .info
+            .to_info_items(true, &config.display)
+            .into_iter()
+            .map(|(field, value)| (field.to_string(), value))
+            .collect();
+        for (field, value) in lines {
+            let label = config.info.label_name(&field).to_string();
+            let (new_label, new_value) =
+                format_line.call::<_, (Option<String>, Option<String>)>((label.as_str(), value))?;
+            // Returning nothing hides the line
+            let Some(new_label) = new_label else {
+                fetch.info.set(&field, None);
+                continue;
+            };
+            if new_label != label {
+                config.info.labels.insert(field.clone(), new_label);
+            }
+            if let Some(new_value) = new_value {
+                fetch.info.set(&field, Some(new_value));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+// Collectors for macOS, replacing the ones that read lspci and the GTK
+// settings on Linux
+use crate::error::{command_output, CollectError};
+use std::path::PathBuf;
+use sysinfo::{ProcessesToUpdate, System};
+
+// Tiling window managers that run on top of Aqua
+const WINDOW_MANAGERS: [(&str, &str); 4] = [
+    ("yabai", "yabai"),
+    ("aerospace", "AeroSpace"),
+    ("amethyst", "Amethyst"),
+    ("rectangle", "Rectangle"),
+];
+
+/// The value after "<key>:" in system_profiler's indented output
+fn profiler_value(data_type: &str, key: &str) -> Option<String> {
+    let output = command_output("system_profiler", &[data_type])?;
+    output.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(key)?.strip_prefix(':')?;
+        Some(value.trim().to_string())
+    })
+}
+
+/// Installed packages by package manager
+pub fn get_package_counts() -> Result<Vec<(String, usize)>, CollectError> {
+    let count_dirs =
+        |dir: PathBuf| std::fs::read_dir(dir).map_or(0, |entries| entries.flatten().count());
+
+    // Homebrew keeps one directory per formula and per cask
+    let brew = std::env::var("HOMEBREW_PREFIX")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            if cfg!(target_arch = "aarch64") {
+                PathBuf::from("/opt/homebrew")
+            } else {
+                PathBuf::from("/usr/local")
+            }
+        });
+    let mut counts = vec![(
+        "brew".to_string(),
+        count_dirs(brew.join("Cellar")) + count_dirs(brew.join("Caskroom")),
+    )];
+
+    // `port -q installed` prints one installed port per line
+    if let Some(ports) = command_output("port", &["-q", "installed"]) {
+        counts.push(("port".to_string(), ports.lines().count()));
+    }
+
+    Ok(counts)
+}
+
+pub fn get_window_manager() -> Result<String, CollectError> {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All);
+
+    let running = |name: &str| {
+        sys.processes()
+            .values()
+            .any(|process| process.name().to_string_lossy().eq_ignore_ascii_case(name))
+    };
+    Ok(WINDOW_MANAGERS
+        .iter()
+        .find(|(process, _)| running(process))
+        .map_or("Aqua".to_string(), |(_, name)| format!("Aqua + {}", name)))
+}
+
+pub fn get_gpu() -> Result<Option<String>, CollectError> {
+    Ok(profiler_value("SPDisplaysDataType", "Chipset Model"))
+}
+
+pub fn get_theme() -> Result<String, CollectError> {
+    // The key only exists while dark mode is on
+    let style = command_output("defaults", &["read", "-g", "AppleInterfaceStyle"]);
+    Ok(style.unwrap_or_else(|| "Light".to_string()))
+}
+
+pub fn get_model() -> Result<String, CollectError> {
+    let identifier = command_output("sysctl", &["-n", "hw.model"]);
+    match (
+        profiler_value("SPHardwareDataType", "Model Name"),
+        identifier,
+    ) {
+        (Some(name), Some(identifier)) => Ok(format!("{} ({})", name, identifier)),
+        (name, identifier) => name.or(identifier).ok_or_else(|| {
+            CollectError::Failed("neither system_profiler nor sysctl report a model".into())
+        }),
+    }
+}
+
+pub fn get_init() -> Option<String> {
+    Some("launchd".to_string())
+}
+
+/// Whether System Integrity Protection is enabled
+pub fn get_sip() -> Option<String> {
+    // "System Integrity Protection status: enabled."
+    let status = command_output("csrutil", &["status"])?;
+    let (_, state) = status.lines().next()?.split_once(':')?;
+    Some(state.trim().trim_end_matches('.').to_string())
+}
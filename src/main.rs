@@ -1,25 +1,99 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     cursor, execute,
-    style::Stylize,
+    style::{Color, Stylize},
     terminal::{Clear, ClearType},
 };
-use std::io;
-use std::path::PathBuf;
-use sysinfo::{Disks, System};
-use viuer::{print_from_file, Config as ViuerConfig};
-
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+mod archive;
+mod backup;
+mod bluetooth;
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+mod bsd;
 mod challenge;
+mod clipboard;
+mod color;
 mod config;
+mod daemon;
+mod dbus;
+mod diff;
+mod dns;
+mod doctor;
+mod dotfiles;
+mod error;
+mod exporter;
+mod fetch;
+mod graphics;
+mod history;
+mod home_size;
+mod html;
+mod i18n;
+mod latency;
+mod layout;
+mod logging;
+mod logo;
+#[cfg(feature = "lua")]
+mod lua;
+#[cfg(target_os = "macos")]
+mod macos;
+mod migrate;
+mod network;
+mod notify;
+mod output;
+mod plugins;
+mod power;
+#[cfg(unix)]
+mod protocol;
+mod qr;
+mod quote;
+mod reload;
+mod remote;
+mod sbc;
+mod screen;
+mod serve;
+mod sessions;
+mod share;
+mod statusline;
+mod streak;
+mod svg;
 mod system_info;
-
-use config::{Config, LogoConfig};
-use system_info::SystemInfo;
+mod taint;
+mod temps;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod termux;
+mod tips;
+mod updates;
+mod uptime_record;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod watch;
+#[cfg(windows)]
+mod windows;
+
+use challenge::ChallengeStatus;
+use color::paint;
+use color::to_rgb;
+use config::{BarsConfig, BoxConfig, ColorbarConfig, Config, InfoConfig};
+use fetch::Fetch;
+use logo::{draw_logo, logo_size};
+use output::OutputFormat;
+use serve::ServeOptions;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "huginn")]
 #[command(about = "A beautiful system information fetcher", long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long)]
     challenge: bool,
     /// Number of years for the challenge
@@ -33,6 +107,215 @@ struct Cli {
     // Generate a default config file at XDG config/huginn/config.toml
     #[arg(long)]
     generate_config: bool,
+
+    /// Disable colored output (the NO_COLOR environment variable is also respected)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Output format (defaults to ansi in a terminal, plain when piped)
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Print a colored, cursor-free banner for /etc/motd (same as --format motd)
+    #[arg(long, conflicts_with_all = ["format", "issue"])]
+    motd: bool,
+
+    /// Print escape-free text for /etc/issue (same as --format issue)
+    #[arg(long, conflicts_with = "format")]
+    issue: bool,
+
+    /// Print every collected field as JSON (same as --format json)
+    #[arg(long, conflicts_with_all = ["format", "motd", "issue"])]
+    json: bool,
+
+    /// Describe everything in plain sentences for screen readers (same as --format accessible)
+    #[arg(long, conflicts_with_all = ["format", "motd", "issue", "json"])]
+    accessible: bool,
+
+    /// Collect on a remote machine over ssh and render the result locally
+    #[arg(long, value_name = "USER@HOST")]
+    remote: Option<String>,
+
+    /// Skip everything that would touch the network
+    #[arg(long, conflicts_with = "remote")]
+    offline: bool,
+
+    /// Only show distro, kernel, uptime and the cpu/ram/disk bars, without a logo
+    #[arg(long, conflicts_with = "field")]
+    minimal: bool,
+
+    /// Show every field regardless of the config, and report failures like --verbose
+    #[arg(long, conflicts_with_all = ["field", "minimal"])]
+    all: bool,
+
+    /// Also copy the fetch to the clipboard as plain text
+    #[arg(long, conflicts_with = "watch")]
+    copy: bool,
+
+    /// Print a QR code of the fetch instead, or of the link with `huginn share`
+    #[arg(long, conflicts_with_all = ["format", "motd", "issue", "json", "accessible", "watch", "notify", "screenshot"])]
+    qr: bool,
+
+    /// Write the fetch to FILE instead of stdout, replacing it in one step
+    /// (plain unless --format says otherwise)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["watch", "notify", "qr", "screenshot", "benchmark"])]
+    output: Option<PathBuf>,
+
+    /// Also save this run as a JSON snapshot for `huginn diff`
+    #[arg(long, value_name = "FILE")]
+    snapshot: Option<PathBuf>,
+
+    /// Only collect and show these info fields (repeatable, or comma-separated)
+    #[arg(long, value_name = "FIELD", value_delimiter = ',')]
+    field: Vec<String>,
+
+    /// Redraw the fetch in place every SECS seconds (default 2)
+    #[arg(
+        long,
+        value_name = "SECS",
+        num_args = 0..=1,
+        default_missing_value = "2",
+        conflicts_with_all = ["format", "motd", "issue", "json", "remote", "screenshot", "snapshot"]
+    )]
+    watch: Option<u64>,
+
+    /// Collect directly even if a `huginn daemon` is running
+    #[arg(long)]
+    no_daemon: bool,
+
+    /// Report which collectors failed and why on stderr
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Log timings and decisions to stderr, or to FILE with --debug=FILE
+    /// (level from HUGINN_LOG)
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-"
+    )]
+    debug: Option<PathBuf>,
+
+    /// Send a short summary as a desktop notification instead of printing it
+    #[arg(long, conflicts_with_all = ["format", "motd", "issue", "json", "watch", "screenshot"])]
+    notify: bool,
+
+    /// Render the fetch into a PNG image instead of printing it
+    #[arg(long, value_name = "FILE")]
+    screenshot: Option<PathBuf>,
+
+    /// Time every collector and print them slowest first
+    #[arg(long, conflicts_with_all = ["format", "motd", "issue", "json", "watch", "notify", "screenshot"])]
+    benchmark: bool,
+
+    /// Serve metrics in Prometheus format over HTTP
+    #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = exporter::DEFAULT_ADDR)]
+    exporter: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the fetch over HTTP at /json, /html and /metrics
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to bind to; 0.0.0.0 serves the LAN
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+
+        /// Seconds between collector refreshes
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+
+    /// Register org.huginn.Fetch on the D-Bus session bus
+    Dbus {
+        /// Seconds between collector refreshes
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+
+    /// Show what changed between two snapshots saved with --snapshot
+    Diff { old: PathBuf, new: PathBuf },
+
+    /// Keep collectors warm and answer other huginn invocations over a Unix socket
+    Daemon {
+        /// Seconds between collector refreshes
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+    },
+
+    /// List the available logos, built-in and from the logo directory
+    Logos {
+        #[command(subcommand)]
+        action: Option<LogosCommand>,
+
+        /// Draw each logo below its name
+        #[arg(long)]
+        preview: bool,
+    },
+
+    /// Chart recorded metrics such as package count and disk usage over time
+    History {
+        /// Number of days to show
+        #[arg(long, default_value_t = 90)]
+        days: i64,
+    },
+
+    /// List past challenges with how long they lasted and how they ended
+    Challenges,
+
+    /// Check the terminal, external tools, logos, config and cache for problems
+    Doctor,
+
+    /// Measure the home directory and cache its size for the home field
+    #[command(hide = true)]
+    CacheHomeSize,
+
+    /// Upload the fetch to a paste service and print the link
+    Share {
+        /// Upload without showing the text and asking first
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Manage the install challenge
+    Challenge {
+        #[command(subcommand)]
+        action: Option<ChallengeCommand>,
+
+        /// Save a shareable progress card as a PNG image
+        #[arg(long, value_name = "PATH")]
+        card: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChallengeCommand {
+    /// Archive the current challenge and start it over from today
+    Reset,
+
+    /// Print the challenge's state and exit with 0 when it is complete, 1
+    /// while it is in progress and 2 when it can't be determined
+    Status {
+        /// Print nothing, only set the exit code
+        #[arg(short, long)]
+        quiet: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum LogosCommand {
+    /// Download logos from the huginn repository into the logo directory
+    Install {
+        /// Distro to fetch the logo for, or "all"
+        #[arg(default_value = "all")]
+        target: String,
+    },
 }
 
 struct DisplayContext {
@@ -47,11 +330,16 @@ impl DisplayContext {
 
         if self.in_box {
             if let Some(r) = row {
-                execute!(io::stdout(), cursor::MoveTo(padding as u16, r))?;
+                execute!(screen::out(), cursor::MoveTo(padding as u16, r))?;
             }
-            print!("{}", text);
+            write!(screen::out(), "{}", text)?;
         } else {
-            println!("{}{}", " ".repeat(padding + self.offset_x), text);
+            writeln!(
+                screen::out(),
+                "{}{}",
+                " ".repeat(padding + self.offset_x),
+                text
+            )?;
         }
         Ok(())
     }
@@ -59,24 +347,129 @@ impl DisplayContext {
     fn print_line(&self, row: Option<u16>, text: &str) -> io::Result<()> {
         if self.in_box {
             if let Some(r) = row {
-                execute!(io::stdout(), cursor::MoveTo(self.offset_x as u16, r))?;
+                execute!(screen::out(), cursor::MoveTo(self.offset_x as u16, r))?;
             }
-            print!("{}", text);
+            write!(screen::out(), "{}", text)?;
         } else {
-            println!("{}{}", " ".repeat(self.offset_x), text);
+            writeln!(screen::out(), "{}{}", " ".repeat(self.offset_x), text)?;
         }
         Ok(())
     }
 }
 
+#[derive(Clone, Copy)]
 enum ProgressColorScheme {
     System,
     Challenge,
 }
 
+/// A command running a user script through the platform's shell
+fn shell_command(script: &str) -> std::process::Command {
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (shell, flag) = ("sh", "-c");
+
+    let mut command = std::process::Command::new(shell);
+    command.arg(flag).arg(script);
+    command
+}
+
+/// Print how long each collector takes, so slow ones can be disabled or
+/// given a shorter timeout
+fn print_benchmark(config: &Config) {
+    let mut sys = sysinfo::System::new_with_specifics(
+        sysinfo::RefreshKind::new().with_cpu(sysinfo::CpuRefreshKind::everything()),
+    );
+    sys.refresh_cpu_usage();
+
+    let timings = system_info::benchmark(config, &sys);
+    let total: Duration = timings.iter().map(|timing| timing.elapsed).sum();
+    for timing in &timings {
+        let ms = format!("{:>9.1} ms", timing.elapsed.as_secs_f64() * 1000.0);
+        // Anything taking over 100ms is noticeable at a shell prompt
+        let ms = if timing.elapsed >= Duration::from_millis(100) {
+            color::paint(ms.yellow().bold())
+        } else {
+            ms
+        };
+        match &timing.failure {
+            Some(reason) => println!("{:<10}{}  ({})", timing.name, ms, reason),
+            None => println!("{:<10}{}", timing.name, ms),
+        }
+    }
+    println!(
+        "{:<10}{}",
+        "total",
+        color::paint(format!("{:>9.1} ms", total.as_secs_f64() * 1000.0).bold())
+    );
+    println!();
+    println!("Collectors run in parallel, so a fetch takes about as long as the slowest one.");
+    println!("Turn slow fields off under [display], or cap them under [collectors.timeouts].");
+}
+
+/// Run a configured script, warning if it can't start or fails
+fn run_script(kind: &str, mut command: std::process::Command) {
+    match command.status() {
+        Ok(status) if !status.success() => {
+            eprintln!("Warning: {} script exited with {}", kind, status)
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: could not run the {} script: {}", kind, e),
+    }
+}
+
+/// What the command line changes in the loaded config, kept so a config
+/// reloaded in watch mode or the daemon gets the same changes
+struct ConfigOverrides {
+    fields: Vec<String>,
+    minimal: bool,
+    all: bool,
+    challenge: bool,
+    offline: bool,
+}
+
+impl ConfigOverrides {
+    fn apply(&self, config: &mut Config) -> Result<(), String> {
+        self.apply_without_mode(config)?;
+        config.logo.use_mode(self.in_challenge_mode(config));
+        Ok(())
+    }
+
+    /// Everything but the logo's mode overrides, for watch mode, which
+    /// switches modes itself
+    fn apply_without_mode(&self, config: &mut Config) -> Result<(), String> {
+        i18n::set_language(&config.locale.language);
+        color::set_depth(&config.display.color_depth);
+        network::set_offline(self.offline || config.network.offline);
+
+        if !self.fields.is_empty() {
+            config.display.show_only(&self.fields)?;
+        }
+        if self.minimal {
+            config.minimal();
+        }
+        if self.all {
+            config.display.show_all();
+        }
+        Ok(())
+    }
+
+    /// Whether the fields to collect differ from the config file's, which
+    /// the daemon's fetch was collected with
+    fn changes_fields(&self) -> bool {
+        !self.fields.is_empty() || self.minimal || self.all || self.offline
+    }
+
+    /// The --challenge flag overrides the config's mode
+    fn in_challenge_mode(&self, config: &Config) -> bool {
+        self.challenge || config.display.mode == "challenge"
+    }
+}
+
 fn expand_home(path: &str) -> String {
     if path.starts_with("~/") {
-        if let Ok(home) = std::env::var("HOME") {
+        if let Some(home) = config::home_dir() {
             return path.replacen("~", &home, 1);
         }
     }
@@ -85,6 +478,31 @@ fn expand_home(path: &str) -> String {
 
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
+    logging::init(cli.debug.as_deref());
+
+    // Piped or redirected output gets a linear, cursor-free rendering
+    let format = if cli.motd {
+        OutputFormat::Motd
+    } else if cli.issue {
+        OutputFormat::Issue
+    } else if cli.json {
+        OutputFormat::Json
+    } else if cli.accessible {
+        OutputFormat::Accessible
+    } else {
+        cli.format
+            .unwrap_or(if cli.output.is_none() && io::stdout().is_terminal() {
+                OutputFormat::Ansi
+            } else {
+                OutputFormat::Plain
+            })
+    };
+
+    tracing::debug!("output format {:?}", format);
+
+    if cli.no_color || color::no_color_env() || !format.is_colored() || cli.screenshot.is_some() {
+        color::set_enabled(false);
+    }
 
     // Handle config generation if requested
     if cli.generate_config {
@@ -98,81 +516,541 @@ fn main() -> io::Result<()> {
     }
 
     // Load configuration
-    let config = Config::load();
+    let mut config = Config::load();
+    let logo_config = config.logo.clone();
+    let overrides = ConfigOverrides {
+        fields: cli.field.clone(),
+        minimal: cli.minimal,
+        all: cli.all,
+        challenge: cli.challenge,
+        offline: cli.offline,
+    };
+    if let Err(e) = overrides.apply(&mut config) {
+        eprintln!("Error: {}", e);
+        return Ok(());
+    }
+    // The daemon collects what the config file asks for, not what the
+    // command line does
+    let use_daemon = !cli.no_daemon && !overrides.changes_fields();
 
     // Determine if we're in challenge mode
     // CLI flag overrides config setting
-    let in_challenge_mode = cli.challenge || config.display.mode == "challenge";
+    let in_challenge_mode = overrides.in_challenge_mode(&config);
 
     // Determine challenge years and months
     // CLI args override config values
     let challenge_years = cli.years.unwrap_or(config.challenge.years);
     let challenge_months = cli.months.unwrap_or(config.challenge.months);
 
+    if let Some(addr) = &cli.exporter {
+        return exporter::serve(addr, &config, challenge_years, challenge_months);
+    }
+
+    if cli.benchmark {
+        print_benchmark(&config);
+        return Ok(());
+    }
+
+    let challenge_target = in_challenge_mode.then_some((challenge_years, challenge_months));
+    match cli.command {
+        Some(Command::Serve {
+            port,
+            bind,
+            interval,
+        }) => {
+            let options = ServeOptions {
+                addr: format!("{}:{}", bind, port),
+                interval: Duration::from_secs(interval.max(1)),
+                challenge: challenge_target,
+            };
+            return serve::serve(options, config);
+        }
+        Some(Command::Dbus { interval }) => {
+            if let Err(e) = dbus::serve(
+                config,
+                challenge_target,
+                Duration::from_secs(interval.max(1)),
+            ) {
+                eprintln!("Error running D-Bus service: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Diff { old, new }) => {
+            if let Err(e) = diff::run_diff(&old, &new) {
+                eprintln!("Error comparing snapshots: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Daemon { interval }) => {
+            let watcher = reload::ConfigWatcher::start(move |config| overrides.apply(config));
+            if let Err(e) = daemon::run(config, Duration::from_secs(interval.max(1)), watcher) {
+                eprintln!("Error running daemon: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Logos {
+            action: Some(LogosCommand::Install { target }),
+            ..
+        }) => {
+            if let Err(e) = logo::install_logos(&target, &config.logo) {
+                eprintln!("Error installing logos: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Logos {
+            action: None,
+            preview,
+        }) => {
+            return logo::list_logos(&config.logo, preview);
+        }
+        Some(Command::History { days }) => {
+            history::show_history(days);
+            return Ok(());
+        }
+        Some(Command::Challenges) => {
+            archive::show_challenges();
+            return Ok(());
+        }
+        Some(Command::Doctor) => {
+            doctor::run(&config);
+            return Ok(());
+        }
+        Some(Command::CacheHomeSize) => {
+            if let Err(e) = home_size::refresh() {
+                eprintln!("Error measuring the home directory: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Share { yes }) => {
+            let fetch = use_daemon
+                .then(daemon::query)
+                .flatten()
+                .unwrap_or_else(|| Fetch::collect(&config));
+            let status = challenge_target.map(|(years, months)| {
+                ChallengeStatus::new(
+                    years,
+                    months,
+                    config.challenge.start_date.as_deref(),
+                    &config,
+                )
+            });
+            match share::run(&fetch, status.as_ref(), &config, yes) {
+                Ok(Some(url)) if cli.qr => match qr::render(&url) {
+                    Ok(code) => println!("{}\n{}", code, url),
+                    Err(e) => eprintln!("Error drawing the QR code: {}", e),
+                },
+                Ok(Some(url)) => println!("{}", url),
+                Ok(None) => {}
+                Err(e) => eprintln!("Error sharing the fetch: {}", e),
+            }
+            return Ok(());
+        }
+        Some(Command::Challenge {
+            action: Some(ChallengeCommand::Reset),
+            ..
+        }) => {
+            if let Err(e) = challenge::reset(&config) {
+                eprintln!("Error resetting the challenge: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Command::Challenge {
+            action: Some(ChallengeCommand::Status { quiet }),
+            ..
+        }) => {
+            let status = ChallengeStatus::determine(
+                challenge_years,
+                challenge_months,
+                config.challenge.start_date.as_deref(),
+                &config,
+            );
+            let code = match status {
+                Ok(status) if status.is_complete() => {
+                    if !quiet {
+                        println!("complete since {}", status.target_dt.format("%Y-%m-%d"));
+                    }
+                    0
+                }
+                Ok(status) => {
+                    if !quiet {
+                        println!(
+                            "in progress, {}% done, {} days left",
+                            status.progress_percentage,
+                            status.remaining().num_days()
+                        );
+                    }
+                    1
+                }
+                Err(e) => {
+                    if !quiet {
+                        eprintln!("Error determining the challenge: {}", e);
+                    }
+                    2
+                }
+            };
+            std::process::exit(code);
+        }
+        Some(Command::Challenge {
+            action: None,
+            card: Some(path),
+        }) => {
+            let fetch = use_daemon
+                .then(daemon::query)
+                .flatten()
+                .unwrap_or_else(|| Fetch::collect(&config));
+            let status = ChallengeStatus::new(
+                challenge_years,
+                challenge_months,
+                config.challenge.start_date.as_deref(),
+                &config,
+            );
+            let svg = svg::render_card(&fetch, &status, &config);
+            match svg::save_png(&svg, &path) {
+                Ok(()) => println!("Saved challenge card to {}", path.display()),
+                Err(e) => eprintln!("Error saving challenge card: {}", e),
+            }
+            return Ok(());
+        }
+        Some(Command::Challenge {
+            action: None,
+            card: None,
+        }) => {
+            eprintln!("Error: huginn challenge needs a subcommand or --card <PATH>");
+            return Ok(());
+        }
+        None => {}
+    }
+
     // Run pre-fetch script if configured
     if !config.scripts.pre_fetch.is_empty() {
-        let _ = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(&config.scripts.pre_fetch)
-            .status();
+        run_script("pre-fetch", shell_command(&config.scripts.pre_fetch));
+    }
+
+    if let Some(secs) = cli.watch {
+        let interval = Duration::from_secs(secs.max(1));
+        // Watch mode applies the logo's mode overrides itself, so the `m` key
+        // can switch between them
+        config.logo = logo_config;
+        let watcher =
+            reload::ConfigWatcher::start(move |config| overrides.apply_without_mode(config));
+        return watch::run(&config, challenge_target, interval, use_daemon, watcher);
+    }
+
+    let (fetch, challenge_status) = if let Some(host) = &cli.remote {
+        match remote::fetch_remote(host, challenge_target) {
+            Ok(snapshot) => (snapshot.fetch, snapshot.challenge),
+            Err(e) => {
+                eprintln!("Error fetching from {}: {}", host, e);
+                return Ok(());
+            }
+        }
+    } else {
+        // A running daemon answers instantly with already collected data
+        let mut fetch = use_daemon
+            .then(daemon::query)
+            .flatten()
+            .unwrap_or_else(|| Fetch::collect(&config));
+        if config.display.streak {
+            match streak::record() {
+                Ok(days) => {
+                    let unit = i18n::tr(if days == 1 { "day" } else { "days" });
+                    fetch.info.streak = Some(format!("{} {}", days, unit));
+                }
+                Err(e) => eprintln!("Warning: could not record the run streak: {}", e),
+            }
+        }
+        if config.display.uptime_record {
+            match uptime_record::update(fetch.uptime_secs) {
+                Ok(record) => fetch.uptime_record = Some(record),
+                Err(e) => eprintln!("Warning: could not record the uptime: {}", e),
+            }
+        }
+        if in_challenge_mode {
+            challenge::record_first_start(&config);
+        }
+        let mut challenge_status = in_challenge_mode.then(|| {
+            ChallengeStatus::new(
+                challenge_years,
+                challenge_months,
+                config.challenge.start_date.as_deref(),
+                &config,
+            )
+        });
+        if let Some(status) = challenge_status.as_mut() {
+            status.check_milestones(&config);
+        }
+
+        if config.history.enabled {
+            if let Err(e) = history::record(&fetch, challenge_status.as_ref(), &config.history) {
+                eprintln!("Warning: could not record history: {}", e);
+            }
+        }
+
+        (fetch, challenge_status)
+    };
+
+    config.info.add_plugin_colors(&fetch.info.plugins);
+    for (field, color) in &fetch.info.alerts {
+        config.info.add_alert_color(field, color);
+    }
+    #[cfg(feature = "lua")]
+    let fetch = {
+        let mut fetch = fetch;
+        if let Err(e) = lua::run_hooks(&mut fetch, &mut config) {
+            eprintln!("Error running Lua hooks: {}", e);
+        }
+        fetch
+    };
+
+    if cli.verbose || cli.all {
+        for (field, reason) in &fetch.info.failures {
+            eprintln!("Warning: {} collector failed: {}", field, reason);
+        }
+    }
+
+    if let Some(path) = &cli.snapshot {
+        let json = output::render_json(&fetch, challenge_status.as_ref());
+        if let Err(e) = std::fs::write(path, json + "\n") {
+            eprintln!("Error saving snapshot to {}: {}", path.display(), e);
+        }
+    }
+
+    if cli.notify {
+        if let Err(e) = notify::send(&fetch, challenge_status.as_ref()) {
+            eprintln!("Error sending notification: {}", e);
+        }
+    } else if cli.qr {
+        let summary = qr::summary_json(&fetch, challenge_status.as_ref(), &config);
+        match qr::render(&summary) {
+            Ok(code) => println!("{}", code),
+            Err(e) => eprintln!("Error drawing the QR code: {}", e),
+        }
+    } else if let Some(path) = &cli.screenshot {
+        let svg = svg::render_svg(&fetch, challenge_status.as_ref(), &config);
+        match svg::save_png(&svg, path) {
+            Ok(()) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => eprintln!("Error saving screenshot: {}", e),
+        }
+    } else if format == OutputFormat::Ansi && cli.output.is_none() {
+        render_ansi(&config, &fetch, challenge_status.as_ref())?;
+    } else {
+        let text = render_format(format, &config, &fetch, challenge_status.as_ref());
+        match &cli.output {
+            Some(path) => {
+                if let Err(e) = write_atomically(path, &text) {
+                    eprintln!("Error writing the fetch to {}: {}", path.display(), e);
+                }
+            }
+            None => print!("{}", text),
+        }
+    }
+
+    if cli.copy {
+        let text = output::render_text(&fetch, challenge_status.as_ref(), &config);
+        match clipboard::copy(&text) {
+            Ok(via) => eprintln!("Copied the fetch to the clipboard ({})", via),
+            Err(e) => eprintln!("Error copying to the clipboard: {}", e),
+        }
+    }
+
+    // Run post-fetch script if configured
+    if !config.scripts.post_fetch.is_empty() {
+        run_script("post-fetch", shell_command(&config.scripts.post_fetch));
+    }
+
+    Ok(())
+}
+
+/// Everything but the live terminal layout as text; the ansi layout is
+/// replayed into lines, with its colors but without cursor moves or images
+fn render_format(
+    format: OutputFormat,
+    config: &Config,
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+) -> String {
+    let line = |text: String| text + "\n";
+    match format {
+        OutputFormat::Plain => output::render_plain(fetch, challenge, config),
+        OutputFormat::Motd => output::render_motd(fetch, challenge, config),
+        OutputFormat::Issue => output::render_issue(fetch, challenge, config),
+        OutputFormat::Markdown => output::render_markdown(fetch, challenge, config),
+        OutputFormat::Html => html::render_html(fetch, challenge, config),
+        OutputFormat::Svg => svg::render_svg(fetch, challenge, config),
+        OutputFormat::Waybar => line(output::render_waybar(fetch, challenge, config)),
+        OutputFormat::Polybar => line(statusline::render_polybar(fetch, challenge, config)),
+        OutputFormat::I3blocks => line(statusline::render_i3blocks(fetch, challenge, config)),
+        OutputFormat::Tmux => line(statusline::render_tmux(fetch, challenge, config)),
+        OutputFormat::Prompt => line(statusline::render_prompt(fetch, challenge, config)),
+        OutputFormat::Json => line(output::render_json(fetch, challenge)),
+        OutputFormat::Csv => output::render_csv(fetch, challenge),
+        OutputFormat::Accessible => output::render_accessible(fetch, challenge, config),
+        OutputFormat::Ansi => {
+            // An image can't go into text, so leave no room for the logo
+            let mut config = config.clone();
+            config.logo.backend = "none".to_string();
+            let (_, frame) = screen::capture(|| render_ansi(&config, fetch, challenge));
+            frame.to_text()
+        }
+    }
+}
+
+/// Write through a temporary file in the same directory and rename it into
+/// place, so whatever reads the file never sees it half written
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents.as_bytes())?;
+    // Temporary files are private; keep the old file's mode, or make a new
+    // one readable like a plain write would, e.g. for a web server
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::metadata(path)
+            .map(|metadata| metadata.permissions())
+            .unwrap_or_else(|_| std::fs::Permissions::from_mode(0o644));
+        file.as_file().set_permissions(permissions)?;
+    }
+    file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Clear the screen and draw the full terminal layout, returning the cell
+/// the logo was drawn at
+fn render_ansi(
+    config: &Config,
+    fetch: &Fetch,
+    challenge_status: Option<&ChallengeStatus>,
+) -> io::Result<(u16, u16)> {
+    // Ask the terminal which graphics it supports before anything is drawn
+    if !config.logo.hidden() {
+        graphics::capabilities();
     }
 
     // Clear screen
-    execute!(io::stdout(), Clear(ClearType::All))?;
-    execute!(io::stdout(), cursor::MoveTo(0, 0))?;
+    execute!(screen::out(), Clear(ClearType::All))?;
+    execute!(screen::out(), cursor::MoveTo(0, 0))?;
+
+    // A layout template replaces the normal view; challenge mode keeps its box
+    if challenge_status.is_none() {
+        if let Some(template) = config.layout.template() {
+            return layout::render(&template, config, fetch);
+        }
+    }
 
     // Run normal fetch (with offset if in box)
-    let (content_height, second_info_row) = run_fetch_internal(in_challenge_mode, &config)?;
+    let layout = run_fetch_internal(challenge_status, config, fetch)?;
 
     // Add challenge box if needed
-    if in_challenge_mode {
+    if let Some(status) = challenge_status {
+        let box_config = &config.outer_box;
         let challenge_end_row = challenge::run_challenge_countdown(
-            challenge_years,
-            challenge_months,
-            second_info_row,
-            &config.display,
+            status,
+            config,
+            layout.countdown_column,
+            layout.second_info_row,
         );
-        let total_height = content_height.max(challenge_end_row) + 1;
-        draw_outer_box(total_height)?;
-        println!();
+        let total_height =
+            layout.content_height.max(challenge_end_row) + 1 + box_config.padding.saturating_sub(1);
+        draw_outer_box(total_height, layout.box_width, box_config)?;
+        writeln!(screen::out())?;
     }
 
-    // Run post-fetch script if configured
-    if !config.scripts.post_fetch.is_empty() {
-        let _ = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(&config.scripts.post_fetch)
-            .status();
-    }
+    Ok(layout.logo_origin)
+}
 
-    Ok(())
+/// Corners, horizontal and vertical glyphs for a box style:
+/// top-left, top-right, bottom-left, bottom-right, horizontal, vertical
+fn box_glyphs(style: &str) -> Option<[char; 6]> {
+    match style {
+        "none" => None,
+        "square" => Some(['┌', '┐', '└', '┘', '─', '│']),
+        "double" => Some(['╔', '╗', '╚', '╝', '═', '║']),
+        "heavy" => Some(['┏', '┓', '┗', '┛', '━', '┃']),
+        "ascii" => Some(['+', '+', '+', '+', '-', '|']),
+        _ => Some(['╭', '╮', '╰', '╯', '─', '│']),
+    }
 }
-fn draw_outer_box(height: u16) -> io::Result<()> {
-    let box_width = 85;
 
-    // Top border
-    execute!(io::stdout(), cursor::MoveTo(2, 1))?;
-    print!("╭{}╮", "─".repeat(box_width));
+fn draw_outer_box(height: u16, width: u16, box_config: &BoxConfig) -> io::Result<()> {
+    let Some([top_left, top_right, bottom_left, bottom_right, horizontal, vertical]) =
+        box_glyphs(&box_config.style)
+    else {
+        return Ok(());
+    };
+    let box_width = width as usize;
+    let line = |len: usize| horizontal.to_string().repeat(len);
+
+    // Top border, with the title set in after the first corner
+    let title: String = box_config
+        .title
+        .chars()
+        .take(box_width.saturating_sub(4))
+        .collect();
+    let top = if title.is_empty() {
+        line(box_width)
+    } else {
+        let used = title.chars().count() + 3;
+        format!(
+            "{} {} {}",
+            horizontal,
+            paint(title.as_str().bold()),
+            line(box_width.saturating_sub(used))
+        )
+    };
+    execute!(screen::out(), cursor::MoveTo(2, 1))?;
+    write!(screen::out(), "{}{}{}", top_left, top, top_right)?;
 
     // Side borders
     for row in 2..=(height + 1) {
-        execute!(io::stdout(), cursor::MoveTo(2, row))?;
-        print!("│");
-        execute!(io::stdout(), cursor::MoveTo((box_width + 3) as u16, row))?;
-        print!("│");
+        execute!(screen::out(), cursor::MoveTo(2, row))?;
+        write!(screen::out(), "{}", vertical)?;
+        execute!(screen::out(), cursor::MoveTo(width + 3, row))?;
+        write!(screen::out(), "{}", vertical)?;
     }
 
     // Bottom border
-    execute!(io::stdout(), cursor::MoveTo(2, height + 2))?;
-    print!("╰{}╯", "─".repeat(box_width));
+    execute!(screen::out(), cursor::MoveTo(2, height + 2))?;
+    write!(
+        screen::out(),
+        "{}{}{}",
+        bottom_left,
+        line(box_width),
+        bottom_right
+    )?;
 
     Ok(())
 }
 
-fn display_greeting(ctx: &DisplayContext, name: &str, row: &mut u16) -> io::Result<()> {
-    let greeting_text = format!("Hi! {}", name);
-    let greeting_width = greeting_text.len();
-    let formatted = format!("{} {}", "Hi!".cyan(), name.green().bold());
+/// Greeting with literal text in one color and values in another, bold,
+/// along with its width
+fn paint_greeting(parts: &[(String, bool)], text: Color, value: Color) -> (String, usize) {
+    let painted = parts
+        .iter()
+        .map(|(part, is_value)| {
+            if *is_value {
+                paint(part.as_str().with(value).bold())
+            } else {
+                paint(part.as_str().with(text))
+            }
+        })
+        .collect();
+    let width = parts.iter().map(|(part, _)| part.chars().count()).sum();
+    (painted, width)
+}
+
+fn display_greeting(
+    ctx: &DisplayContext,
+    greeting: Option<&[(String, bool)]>,
+    row: &mut u16,
+) -> io::Result<()> {
+    let Some(parts) = greeting else {
+        return Ok(());
+    };
+    let (formatted, greeting_width) = paint_greeting(parts, Color::Cyan, Color::Green);
 
     ctx.print_centered(Some(*row), &formatted, greeting_width)?;
     if ctx.in_box {
@@ -181,10 +1059,30 @@ fn display_greeting(ctx: &DisplayContext, name: &str, row: &mut u16) -> io::Resu
     Ok(())
 }
 
-fn display_uptime(ctx: &DisplayContext, uptime: &str, row: &mut u16) -> io::Result<()> {
-    let uptime_text = format!("up {}", uptime);
-    let uptime_width = uptime_text.len();
-    let formatted = format!("{} {}", "up".yellow(), uptime.cyan().bold());
+/// The "up ..." line, with the uptime record after it when there is one
+fn paint_uptime(fetch: &Fetch) -> (String, usize) {
+    let up = i18n::tr("up");
+    let mut painted = format!(
+        "{} {}",
+        paint(up.yellow()),
+        paint(fetch.uptime.as_str().cyan().bold())
+    );
+    let mut width = up.chars().count() + 1 + fetch.uptime.len();
+    if let Some(record) = &fetch.uptime_record {
+        let text = record.text();
+        let record = if record.beaten {
+            paint(text.as_str().yellow().bold())
+        } else {
+            paint(text.as_str().dark_grey())
+        };
+        painted.push_str(&format!("  {}", record));
+        width += 2 + text.len();
+    }
+    (painted, width)
+}
+
+fn display_uptime(ctx: &DisplayContext, fetch: &Fetch, row: &mut u16) -> io::Result<()> {
+    let (formatted, uptime_width) = paint_uptime(fetch);
 
     ctx.print_centered(Some(*row), &formatted, uptime_width)?;
     if ctx.in_box {
@@ -199,68 +1097,159 @@ fn display_progress_bars(
     ram: i32,
     disk: i32,
     dot_position: usize,
+    bars: &BarsConfig,
     row: &mut u16,
 ) -> io::Result<()> {
     let items = vec![("cpu", cpu, "  "), ("ram", ram, "  "), ("disk", disk, " ")];
+    let trends = history::usage_trends(bars.sparkline);
 
-    for (label, value, spacing) in items {
+    for ((label, value, spacing), trend) in items.into_iter().zip(trends) {
         let text = format!(
-            "{}{}{:>2}% {}",
-            label.green(),
+            "{}{}{}{}",
+            paint(label.green()),
             spacing,
-            value,
-            draw_progress(value, 14, ProgressColorScheme::System)
+            draw_progress(value, 2, ProgressColorScheme::System, bars),
+            trend
         );
 
         // Calculate visual width (without ANSI codes)
-        let visual_width = label.len() + spacing.len() + 3 + 14; // label + spacing + "XX% " + bar
+        let mut visual_width = label.len() + spacing.len() + progress_width(2, bars);
+        if bars.sparkline > 0 {
+            visual_width += 1 + bars.sparkline;
+        }
 
         if ctx.in_box {
             // Center the progress bars like the greeting/uptime
             let padding = ctx.visual_center.saturating_sub(visual_width / 2);
-            execute!(io::stdout(), cursor::MoveTo(padding as u16, *row))?;
-            print!("{}", text);
+            execute!(screen::out(), cursor::MoveTo(padding as u16, *row))?;
+            write!(screen::out(), "{}", text)?;
             *row += 1;
         } else {
             // Normal mode: keep left-aligned with dot_position
             let progress_padding = dot_position + 2;
-            println!(
+            writeln!(
+                screen::out(),
                 "{}{}",
                 " ".repeat(ctx.offset_x + progress_padding.saturating_sub(23)),
                 text
-            );
+            )?;
         }
     }
     Ok(())
 }
 
-fn run_fetch_internal(in_box: bool, config: &Config) -> io::Result<(u16, u16)> {
-    let offset_x = if in_box { 4 } else { 0 };
-
-    let mut sys = System::new_all();
-    sys.refresh_all();
+/// Where run_fetch_internal put things, for drawing around it afterwards
+struct FetchLayout {
+    /// Last row of the content in box mode, 0 otherwise
+    content_height: u16,
+    /// Row of the second info line in box mode, where the countdown starts
+    second_info_row: u16,
+    /// Top-left cell of the logo
+    logo_origin: (u16, u16),
+    /// Columns between the borders of the box, in box mode
+    box_width: u16,
+    /// Column the challenge countdown starts at, in box mode
+    countdown_column: u16,
+}
 
-    let name = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
-    let uptime = format_uptime(System::uptime());
+fn run_fetch_internal(
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+    fetch: &Fetch,
+) -> io::Result<FetchLayout> {
+    let in_box = challenge.is_some();
+    let box_config = &config.outer_box;
+    let offset_x = if in_box {
+        3 + box_config.padding as usize
+    } else {
+        0
+    };
+    // Padding beyond one cell also adds blank rows above and below
+    let extra_rows = if in_box {
+        box_config.padding.saturating_sub(1)
+    } else {
+        0
+    };
 
-    // Collect all system info
-    let mut sys_info = SystemInfo::new();
-    sys_info.collect_all(&config.display);
+    let greeting = config.greeting.parts(&fetch.user, &fetch.hostname);
+    let (uptime, uptime_width) = paint_uptime(fetch);
 
     // Convert to info_items, excluding age in box mode
-    let info_items = sys_info.to_info_items(!in_box, &config.display);
+    let info_items = fetch.info.to_info_items(!in_box, &config.display);
 
-    let distro = sys_info
-        .distro
-        .clone()
-        .unwrap_or_else(|| "Unknown".to_string());
+    let logo_subject = fetch.logo_subject(&config.logo);
 
-    let info_lines = format_system_info(info_items);
-    let first_line = &info_lines[0];
-    let dot_position = first_line.find('•').unwrap_or(20);
+    let indent = config.info.indent;
+    let label_width = info_items
+        .iter()
+        .map(|(l, _)| config.info.label_text(l).chars().count())
+        .max()
+        .unwrap_or(0);
+    let info_lines = format_system_info(info_items, indent, &config.info);
+    let celebration = challenge::celebration(config, challenge);
+    // Countdowns line up with the separator of the info lines
+    let mut countdown_items = challenge::countdown_items(config);
+    countdown_items.extend(plugins::bar_items(&fetch.info.plugins, &config.bars));
+    let countdown_label_width = countdown_items
+        .iter()
+        .map(|(l, _)| config.info.label_text(l).chars().count())
+        .max()
+        .unwrap_or(0);
+    let countdown_lines = format_system_info(
+        countdown_items
+            .iter()
+            .map(|(label, value)| (label.as_str(), value.clone()))
+            .collect(),
+        indent + label_width.saturating_sub(countdown_label_width),
+        &config.info,
+    );
+    // Quote and tip, shown under the info lines
+    let mut note_lines = quote::quote_lines(&config.quote);
+    note_lines.extend(tips::tip_lines(&config.tips));
+    // Column of the separator, which the rest of the layout lines up on
+    let dot_position = indent + 1 + label_width + 1;
+
+    // The countdown sits right of the info lines, and the box is sized to
+    // fit everything unless a width is configured
+    let (logo_width, logo_height) = logo_size(&config.logo);
+    let info_width = info_lines
+        .iter()
+        .map(|l| visible_width(l))
+        .max()
+        .unwrap_or(0);
+    let countdown_column = offset_x + info_width + 4;
+    let content_width = [
+        info_width + 4 + challenge.map_or(0, |s| s.countdown_width(config)),
+        logo_width as usize,
+        config.colorbar.width,
+        greeting.as_ref().map_or(0, |parts| {
+            parts.iter().map(|(part, _)| part.chars().count()).sum()
+        }),
+        uptime_width,
+        celebration
+            .as_ref()
+            .map_or(0, |banner| banner.chars().count()),
+        countdown_lines
+            .iter()
+            .map(|l| visible_width(l))
+            .max()
+            .unwrap_or(0),
+        note_lines
+            .iter()
+            .map(|(_, width)| *width)
+            .max()
+            .unwrap_or(0),
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or(0);
+    let box_width = box_config
+        .width
+        .unwrap_or(content_width as u16 + 2 * box_config.padding);
 
     let visual_center = if in_box {
-        44 // box width is 85, and starts at x=2
+        // The box starts at x=2
+        2 + box_width as usize / 2
     } else {
         dot_position
     };
@@ -272,37 +1261,76 @@ fn run_fetch_internal(in_box: bool, config: &Config) -> io::Result<(u16, u16)> {
         visual_center,
     };
 
-    // Use custom logo if configured, otherwise use distro logo
-    let logo_height = if !config.logo.custom_path.is_empty() {
-        let expand_path = expand_home(&config.logo.custom_path);
-        let height = config.logo.height.unwrap_or(18); // Default custom logo height
-        display_custom_logo(&expand_path, visual_center, &config.logo);
-        height
+    // Challenge mode has the countdown on the right, so the logo stays on top
+    let position = if in_box && !config.logo.hidden() {
+        "top"
     } else {
-        display_logo(&distro, visual_center);
-        10 // Default distro logo height
+        config.logo.position.as_str()
     };
+    let shift = |value: usize, offset: i32| (value as i32 + offset).max(0) as u16;
+
+    // Text is shifted right to make room for a logo on the left
+    let text_margin = " ".repeat(if position == "left" {
+        logo_width as usize + 3
+    } else {
+        0
+    });
+
+    let mut logo_origin = (0, 0);
+    if position == "top" {
+        // Custom logos sit one row higher than the distro logos
+        let top_y = if config.logo.custom_path.is_empty() {
+            3
+        } else {
+            2
+        };
+        logo_origin = (
+            shift(
+                visual_center.saturating_sub(logo_width as usize / 2),
+                config.logo.x_offset,
+            ),
+            shift(top_y + extra_rows as usize, config.logo.y_offset),
+        );
+        draw_logo(&logo_subject, logo_origin.0, logo_origin.1, &config.logo);
+    }
 
-    let cpu_usage = sys.global_cpu_usage() as i32;
-    let ram_usage = ((sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0) as i32;
-    let disk_usage = get_disk_usage();
+    let cpu_usage = fetch.cpu_usage;
+    let ram_usage = fetch.ram_usage;
+    let disk_usage = fetch.disk_usage;
 
-    let colorbar = get_colorbar();
-    let colorbar_width = 25;
+    // Confetti takes the colorbar's place on a day worth celebrating
+    let cells = if celebration.is_some() {
+        confetti_cells(&config.colorbar)
+    } else {
+        colorbar_cells(&config.colorbar)
+    };
+    let colorbar_width = cells.len();
+    let colorbar = get_colorbar(cells);
     let colorbar_padding = visual_center.saturating_sub(colorbar_width / 2);
 
     let final_row = if in_box {
         // Use absolute positioning for everything
-        let mut row = 2 + logo_height as u16 + 2;
+        let mut row = 2 + extra_rows + logo_height as u16 + 2;
 
         // Colorbar
-        execute!(io::stdout(), cursor::MoveTo(colorbar_padding as u16, row))?;
-        print!("{}", colorbar);
-        row += 2;
+        if colorbar_width > 0 {
+            execute!(screen::out(), cursor::MoveTo(colorbar_padding as u16, row))?;
+            write!(screen::out(), "{}", colorbar)?;
+            row += 2;
+        }
+
+        if let Some(banner) = &celebration {
+            ctx.print_centered(
+                Some(row),
+                &paint(banner.as_str().yellow().bold()),
+                banner.chars().count(),
+            )?;
+            row += 2;
+        }
 
         // Greeting and uptime
-        display_greeting(&ctx, &name, &mut row)?;
-        display_uptime(&ctx, &uptime, &mut row)?;
+        display_greeting(&ctx, greeting.as_deref(), &mut row)?;
+        display_uptime(&ctx, fetch, &mut row)?;
         row += 1;
 
         // System info
@@ -317,6 +1345,15 @@ fn run_fetch_internal(in_box: bool, config: &Config) -> io::Result<(u16, u16)> {
         }
         row += 1;
 
+        // Quote and tip, centered like the greeting
+        if !note_lines.is_empty() {
+            for (line, width) in &note_lines {
+                ctx.print_centered(Some(row), line, *width)?;
+                row += 1;
+            }
+            row += 1;
+        }
+
         // Progress bars
         display_progress_bars(
             &ctx,
@@ -324,46 +1361,97 @@ fn run_fetch_internal(in_box: bool, config: &Config) -> io::Result<(u16, u16)> {
             ram_usage,
             disk_usage,
             dot_position,
+            &config.bars,
             &mut row,
         )?;
 
+        if !countdown_lines.is_empty() {
+            row += 1;
+            for line in &countdown_lines {
+                ctx.print_line(Some(row), line)?;
+                row += 1;
+            }
+        }
+
         use std::io::Write;
-        std::io::stdout().flush()?;
+        screen::out().flush()?;
         // Keeps progress bar in box hopefully
         let content_end_row = row;
 
         (content_end_row, second_info_row)
     } else {
         // Normal mode: use println!
-        let logo_padding = dot_position.saturating_sub(10);
+        let logo_padding = (dot_position + 2).saturating_sub(colorbar_width / 2);
 
         // Logo (if custom) or distro logo would have been displayed earlier
         // Colorbar aligned with dot position
-        println!("\n{}{}", " ".repeat(logo_padding), colorbar);
-        println!();
+        writeln!(screen::out())?;
+        if colorbar_width > 0 {
+            writeln!(
+                screen::out(),
+                "{}{}{}",
+                text_margin,
+                " ".repeat(logo_padding),
+                colorbar
+            )?;
+            writeln!(screen::out())?;
+        }
+
+        if let Some(banner) = &celebration {
+            let banner_padding = dot_position.saturating_sub(banner.chars().count() / 2);
+            writeln!(
+                screen::out(),
+                "{}{}{}",
+                text_margin,
+                " ".repeat(banner_padding),
+                paint(banner.as_str().yellow().bold())
+            )?;
+            writeln!(screen::out())?;
+        }
 
         // Greeting and uptime - centered around dot position
-        let greeting_visual_width = 4 + name.len();
-        let greeting = format!("{} {}", "Hi!".green(), name.cyan().bold());
-        let greeting_padding = dot_position.saturating_sub(greeting_visual_width / 2);
-        println!("{}{}", " ".repeat(greeting_padding), greeting);
-
-        let uptime_text = format!("up {}", uptime);
-        let uptime_visual_width = uptime_text.len();
-        let uptime_padding = dot_position.saturating_sub(uptime_visual_width / 2);
-        println!(
-            "{}{} {}",
+        if let Some(parts) = &greeting {
+            let (greeting, greeting_visual_width) =
+                paint_greeting(parts, Color::Green, Color::Cyan);
+            let greeting_padding = dot_position.saturating_sub(greeting_visual_width / 2);
+            writeln!(
+                screen::out(),
+                "{}{}{}",
+                text_margin,
+                " ".repeat(greeting_padding),
+                greeting
+            )?;
+        }
+
+        let uptime_padding = dot_position.saturating_sub(uptime_width / 2);
+        writeln!(
+            screen::out(),
+            "{}{}{}",
+            text_margin,
             " ".repeat(uptime_padding),
-            "up".yellow(),
-            uptime.cyan().bold()
-        );
-        println!();
+            uptime
+        )?;
+        writeln!(screen::out())?;
 
         // System info (already aligned with dots)
         for line in &info_lines {
-            println!("{}", line);
+            writeln!(screen::out(), "{}{}", text_margin, line)?;
+        }
+        writeln!(screen::out())?;
+
+        if !note_lines.is_empty() {
+            for (line, width) in &note_lines {
+                let note_padding = dot_position.saturating_sub(width / 2);
+                writeln!(
+                    screen::out(),
+                    "{}{}{}",
+                    text_margin,
+                    " ".repeat(note_padding),
+                    line
+                )?;
+            }
+            writeln!(screen::out())?;
         }
-        println!();
 
         // Progress bars - aligned with dot position
         let items = vec![
@@ -371,53 +1459,193 @@ fn run_fetch_internal(in_box: bool, config: &Config) -> io::Result<(u16, u16)> {
             ("ram", ram_usage, "  "),
             ("disk", disk_usage, " "),
         ];
-        for (label, value, spacing) in items {
+        let trends = history::usage_trends(config.bars.sparkline);
+        for ((label, value, spacing), trend) in items.into_iter().zip(trends) {
             let text = format!(
-                "{}{}{:>2}% {}",
-                label.green(),
+                "{}{}{}{}",
+                paint(label.green()),
                 spacing,
-                value,
-                draw_progress(value, 14, ProgressColorScheme::System)
+                draw_progress(value, 2, ProgressColorScheme::System, &config.bars),
+                trend
             );
             let progress_padding = dot_position.saturating_sub(11); // Adjust for left alignment
-            println!("{}{}", " ".repeat(progress_padding), text);
+            writeln!(
+                screen::out(),
+                "{}{}{}",
+                text_margin,
+                " ".repeat(progress_padding),
+                text
+            )?;
+        }
+
+        if !countdown_lines.is_empty() {
+            writeln!(screen::out())?;
+            for line in &countdown_lines {
+                writeln!(screen::out(), "{}{}", text_margin, line)?;
+            }
+        }
+
+        // Side logos are drawn after the text so the margin doesn't cover them
+        if matches!(position, "left" | "right") {
+            let note_rows = if note_lines.is_empty() {
+                0
+            } else {
+                note_lines.len() + 1
+            };
+            let countdown_rows = if countdown_lines.is_empty() {
+                0
+            } else {
+                countdown_lines.len() + 1
+            };
+            let text_rows = info_lines.len() + note_rows + countdown_rows + 10;
+            let x = if position == "left" {
+                1
+            } else {
+                info_lines
+                    .iter()
+                    .map(|line| visible_width(line))
+                    .max()
+                    .unwrap_or(0)
+                    + 4
+            };
+            logo_origin = (
+                shift(x, config.logo.x_offset),
+                shift(1, config.logo.y_offset),
+            );
+            draw_logo(&logo_subject, logo_origin.0, logo_origin.1, &config.logo);
+
+            let bottom = text_rows.max(1 + logo_height as usize);
+            execute!(screen::out(), cursor::MoveTo(0, bottom as u16))?;
         }
 
         (0, 0) // return for normal mode
     };
 
-    Ok(final_row)
-}
-
-fn draw_progress(percentage: i32, size: usize, scheme: ProgressColorScheme) -> String {
-    let filled = (percentage * size as i32 / 100) as usize;
-    let full = "━".repeat(filled);
-    let empty = "━".repeat(size.saturating_sub(filled));
-
-    let colored_full = match scheme {
-        ProgressColorScheme::System => match percentage {
-            90..=100 => full.dark_red(),
-            70..=89 => full.red(),
-            50..=69 => full.yellow(),
-            30..=49 => full.dark_green(),
-            _ => full.green(),
-        },
-        ProgressColorScheme::Challenge => match percentage {
-            90..=100 => full.green(),
-            70..=89 => full.dark_green(),
-            50..=69 => full.dark_yellow(),
-            30..=49 => full.dark_cyan(),
-            _ => full.cyan(),
-        },
+    let (content_height, second_info_row) = final_row;
+    Ok(FetchLayout {
+        content_height,
+        second_info_row,
+        logo_origin,
+        box_width,
+        countdown_column: countdown_column as u16,
+    })
+}
+
+/// Printed width of a string, skipping ANSI escape sequences
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+            (false, _) => width += 1,
+        }
+    }
+    width
+}
+
+/// A progress bar with its percentage, right-aligned to `percent_width`
+/// digits, placed as configured in [bars]
+fn draw_progress(
+    percentage: i32,
+    percent_width: usize,
+    scheme: ProgressColorScheme,
+    bars: &BarsConfig,
+) -> String {
+    let size = bars.width;
+    let filled = (percentage.clamp(0, 100) as usize * size / 100).min(size);
+    let full = bars.fill.repeat(filled);
+
+    // Without color the filled and empty halves need distinct glyphs
+    let bar = if !color::enabled() {
+        let empty_glyph = if bars.empty == bars.fill {
+            "─"
+        } else {
+            bars.empty.as_str()
+        };
+        format!("{}{}", full, empty_glyph.repeat(size - filled))
+    } else {
+        let empty = bars.empty.repeat(size - filled);
+        let colored_full = full.with(progress_color(percentage, scheme, bars));
+        format!("{}{}", colored_full, empty.dark_grey())
+    };
+
+    let mut brackets = bars.brackets.chars();
+    let bar = match (brackets.next(), brackets.next()) {
+        (Some(open), Some(close)) => format!("{}{}{}", open, bar, close),
+        _ => bar,
+    };
+
+    match bars.percentage.as_str() {
+        "none" => bar,
+        "right" => format!("{} {:>percent_width$}%", bar, percentage),
+        _ => format!("{:>percent_width$}% {}", percentage, bar),
+    }
+}
+
+/// Printed width of what draw_progress returns
+fn progress_width(percent_width: usize, bars: &BarsConfig) -> usize {
+    let brackets = if bars.brackets.chars().count() >= 2 {
+        2
+    } else {
+        0
+    };
+    let percent = if bars.percentage == "none" {
+        0
+    } else {
+        percent_width + 2
+    };
+    bars.width * bars.fill.chars().count().max(1) + brackets + percent
+}
+
+/// Built-in bar colors, each applying from its percentage up
+const SYSTEM_COLORS: [(i32, Color); 5] = [
+    (90, Color::DarkRed),
+    (70, Color::Red),
+    (50, Color::Yellow),
+    (30, Color::DarkGreen),
+    (0, Color::Green),
+];
+
+const CHALLENGE_COLORS: [(i32, Color); 5] = [
+    (90, Color::Green),
+    (70, Color::DarkGreen),
+    (50, Color::DarkYellow),
+    (30, Color::DarkCyan),
+    (0, Color::Cyan),
+];
+
+fn progress_color(percentage: i32, scheme: ProgressColorScheme, bars: &BarsConfig) -> Color {
+    let (configured, builtin) = match scheme {
+        ProgressColorScheme::System => (&bars.system_colors, &SYSTEM_COLORS),
+        ProgressColorScheme::Challenge => (&bars.challenge_colors, &CHALLENGE_COLORS),
     };
 
-    format!("{}{}", colored_full, empty.dark_grey())
+    // The highest configured breakpoint at or below the percentage wins
+    let custom = configured
+        .iter()
+        .filter(|(from, _)| percentage >= *from)
+        .max_by_key(|(from, _)| *from)
+        .and_then(|(_, name)| color::parse_color(name));
+
+    custom.unwrap_or_else(|| {
+        builtin
+            .iter()
+            .find(|(from, _)| percentage >= *from)
+            .map_or(builtin[builtin.len() - 1].1, |(_, color)| *color)
+    })
 }
 
-fn format_system_info(items: Vec<(&str, String)>) -> Vec<String> {
+fn format_system_info(
+    items: Vec<(&str, String)>,
+    indent: usize,
+    info_config: &InfoConfig,
+) -> Vec<String> {
     let max_label_width = items
         .iter()
-        .map(|(label, _)| label.len())
+        .map(|(label, _)| info_config.label_text(label).chars().count())
         .max()
         .unwrap_or(0);
 
@@ -425,200 +1653,124 @@ fn format_system_info(items: Vec<(&str, String)>) -> Vec<String> {
         .iter()
         .map(|(label, value)| {
             format!(
-                "{} {: >width$} {} {}",
-                " ".repeat(10),
-                label,
-                " ".green(),
-                value,
-                width = max_label_width
+                "{} {} {} {}",
+                " ".repeat(indent),
+                info_config.painted_label(label, max_label_width),
+                info_config.painted_separator(),
+                info_config.painted_value(label, value)
             )
         })
         .collect()
 }
 
-fn get_colorbar() -> String {
-    use crossterm::style::Stylize;
-    let first_blocks = ["░", "▒", "▓"];
-    let middle_blocks = ["▓", "▒"];
-    let last_blocks = ["▒", "░"];
-    let mut bar = String::new();
-
-    // Helper macro to add colors with specific block pattern
-    macro_rules! add_colors {
-        (first: $color:ident) => {
-            for block in &first_blocks {
-                bar.push_str(&format!("{}", block.$color()));
-            }
-        };
-        (middle: $color:ident) => {
-            for block in &middle_blocks {
-                bar.push_str(&format!("{}", block.$color()));
-            }
-        };
-        (last: $color:ident) => {
-            for block in &last_blocks {
-                bar.push_str(&format!("{}", block.$color()));
-            }
-        };
-    }
-
-    add_colors!(first: dark_red);
-    add_colors!(middle: red);
-    add_colors!(middle: dark_yellow);
-    add_colors!(middle: yellow);
-    add_colors!(middle: dark_green);
-    add_colors!(middle: green);
-    add_colors!(middle: dark_cyan);
-    add_colors!(middle: cyan);
-    add_colors!(middle: dark_blue);
-    add_colors!(middle: blue);
-    add_colors!(middle: dark_magenta);
-    add_colors!(last: magenta);
-
-    bar
-}
-
-fn get_logo_path(distro: &str) -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let data_dir =
-        std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
-
-    let logo_name = match distro.to_lowercase().as_str() {
-        d if d.contains("arch") => "arch.svg",
-        d if d.contains("debian") => "debian.svg",
-        d if d.contains("endeavour") => "endeavouros.svg",
-        d if d.contains("fedora") => "fedora.svg",
-        d if d.contains("garuda") => "garuda.svg",
-        d if d.contains("gentoo") => "gentoo.svg",
-        d if d.contains("guix") => "guix.svg",
-        d if d.contains("lmde") => "lmde.svg",
-        d if d.contains("macos") => "macos.svg",
-        d if d.contains("manjaro") => "manjaro.svg",
-        d if d.contains("mint") => "mint.svg",
-        d if d.contains("nixos") => "nixos.svg",
-        d if d.contains("obsidian") => "obsidian.svg",
-        d if d.contains("popos") => "popos.svg",
-        d if d.contains("ubuntu") => "ubuntu.svg",
-        d if d.contains("venom") => "venom.svg",
-        d if d.contains("windows") => "windows.svg",
-        _ => "linux.svg",
-    };
-
-    PathBuf::from(format!("{}/huginn/logos/{}", data_dir, logo_name))
-}
-
-fn svg_to_png_temp(svg_path: &PathBuf, width: u32, height: u32) -> Option<PathBuf> {
-    use resvg::usvg;
-
-    let svg_data = std::fs::read(svg_path).ok()?;
-    let options = usvg::Options::default();
-    let tree = usvg::Tree::from_data(&svg_data, &options).ok()?;
-
-    let size = tree.size();
-    let scale_x = width as f32 / size.width();
-    let scale_y = height as f32 / size.height();
-    let scale = scale_x.min(scale_y);
-
-    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)?;
-    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale);
-    resvg::render(&tree, transform, &mut pixmap.as_mut());
-
-    // Save to temp file
-    let temp_png = PathBuf::from("/tmp/huginn_logo.png");
-    pixmap.save_png(&temp_png).ok()?;
-
-    Some(temp_png)
+/// Colors of the colorbar, left to right
+const COLORBAR_COLORS: [Color; 12] = [
+    Color::DarkRed,
+    Color::Red,
+    Color::DarkYellow,
+    Color::Yellow,
+    Color::DarkGreen,
+    Color::Green,
+    Color::DarkCyan,
+    Color::Cyan,
+    Color::DarkBlue,
+    Color::Blue,
+    Color::DarkMagenta,
+    Color::Magenta,
+];
+
+/// Scattered confetti in the colorbar's colors and width
+fn confetti_cells(colorbar: &ColorbarConfig) -> Vec<(&'static str, Color)> {
+    const PIECES: [&str; 6] = ["*", "✦", "•", " ", "✧", "·"];
+    let colors = colorbar_palette(colorbar);
+
+    // Stepping through the pieces out of order scatters them
+    (0..colorbar.width)
+        .map(|i| {
+            let piece = PIECES[(i * 5 + i / PIECES.len()) % PIECES.len()];
+            (piece, colors[i % colors.len()])
+        })
+        .collect()
 }
 
-fn display_logo(distro: &str, dot_position: usize) {
-    let svg_path = get_logo_path(distro);
-    let logo_x = (dot_position as u16).saturating_sub(10);
-
-    let conf = ViuerConfig {
-        width: Some(20),
-        height: Some(10),
-        x: logo_x,
-        y: 3,
-        absolute_offset: true,
-        transparent: true,
-        ..Default::default()
-    };
-
-    // Check if SVG exists and convert to PNG
-    if svg_path.exists() {
-        if let Some(png_path) = svg_to_png_temp(&svg_path, 400, 400) {
-            let _ = print_from_file(&png_path, &conf);
-            let _ = std::fs::remove_file(png_path);
-        }
+/// Glyphs and their colors making up the colorbar, one per column
+fn colorbar_cells(colorbar: &ColorbarConfig) -> Vec<(&'static str, Color)> {
+    let width = if colorbar.style == "none" {
+        0
     } else {
-        let home = std::env::var("HOME").unwrap_or_default();
-        let data_dir =
-            std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
-        let fallback_path = PathBuf::from(format!("{}/huginn/logos/linux.svg", data_dir));
-
-        if fallback_path.exists() {
-            if let Some(png_path) = svg_to_png_temp(&fallback_path, 400, 400) {
-                let _ = print_from_file(&png_path, &conf);
-                let _ = std::fs::remove_file(png_path);
+        colorbar.width
+    };
+    let colors = colorbar_palette(colorbar);
+
+    (0..width)
+        .map(|i| {
+            // Each color gets an equal share of the columns
+            let color = colors[i * colors.len() / width];
+            match colorbar.style.as_str() {
+                "blocks" => ("█", color),
+                "circles" if i % 2 == 0 => ("●", color),
+                "circles" => (" ", color),
+                "gradient" => ("█", gradient_color(&colors, i, width)),
+                // The ends fade in and out, everything between alternates shades
+                _ => {
+                    let block = match i {
+                        0 => "░",
+                        1 => "▒",
+                        2 => "▓",
+                        i if i == width - 1 => "░",
+                        i if i == width - 2 => "▒",
+                        i if i % 2 == 1 => "▓",
+                        _ => "▒",
+                    };
+                    (block, color)
+                }
             }
-        } else {
-            eprintln!("No logo found: {:?}", data_dir);
-            eprintln!("Place logos in: {}/huginn/logos", data_dir);
-        }
-    }
+        })
+        .collect()
 }
 
-fn display_custom_logo(image_path: &str, dot_position: usize, logo_config: &LogoConfig) {
-    let default_width = logo_config.width.unwrap_or(35);
-    let logo_x = (dot_position as u16).saturating_sub((default_width / 2) as u16);
-
-    const DEFAULT_MAX_WIDTH: u32 = 35;
-    const DEFAULT_MAX_HEIGHT: u32 = 18;
-
-    let conf = ViuerConfig {
-        width: Some(logo_config.width.unwrap_or(DEFAULT_MAX_WIDTH)),
-        height: Some(logo_config.height.unwrap_or(DEFAULT_MAX_HEIGHT)),
-        x: logo_x,
-        y: 2,
-        absolute_offset: true,
-        transparent: true,
-        ..Default::default()
-    };
+/// The configured colorbar colors, or the built-in ones if none are valid
+fn colorbar_palette(colorbar: &ColorbarConfig) -> Vec<Color> {
+    let colors: Vec<Color> = colorbar
+        .colors
+        .iter()
+        .filter_map(|name| {
+            let color = color::parse_color(name);
+            if color.is_none() {
+                eprintln!("Warning: Unknown colorbar color: {}", name);
+            }
+            color
+        })
+        .collect();
 
-    // Try to display the custom image
-    let path = PathBuf::from(image_path);
-    if path.exists() {
-        let _ = print_from_file(&path, &conf);
+    if colors.is_empty() {
+        COLORBAR_COLORS.to_vec()
     } else {
-        eprintln!("Warning: Custom logo not found at: {}", image_path);
+        colors
     }
 }
 
-fn format_uptime(seconds: u64) -> String {
-    let days = seconds / 86400;
-    let hours = (seconds % 86400) / 3600;
-    let minutes = (seconds % 3600) / 60;
-
-    if days > 0 {
-        format!("{} days, {} hrs", days, hours)
-    } else if hours > 0 {
-        format!("{} hrs, {} mins", hours, minutes)
-    } else {
-        format!("{} mins", minutes)
+/// Color `i` of `width` steps blended smoothly across the palette
+fn gradient_color(colors: &[Color], i: usize, width: usize) -> Color {
+    if colors.len() < 2 {
+        return colors[0];
+    }
+    let position = i as f32 * (colors.len() - 1) as f32 / (width.max(2) - 1) as f32;
+    let index = (position as usize).min(colors.len() - 2);
+    let t = position - index as f32;
+
+    let (from, to) = (to_rgb(colors[index]), to_rgb(colors[index + 1]));
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::Rgb {
+        r: mix(from.0, to.0),
+        g: mix(from.1, to.1),
+        b: mix(from.2, to.2),
     }
 }
 
-fn get_disk_usage() -> i32 {
-    let disks = Disks::new_with_refreshed_list();
-
-    disks
-        .iter()
-        .find(|d| d.mount_point().to_str() == Some("/"))
-        .map(|d| {
-            let total = d.total_space();
-            let available = d.available_space();
-            let used = total - available;
-            ((used as f64 / total as f64) * 100.0) as i32
-        })
-        .unwrap_or(0)
+fn get_colorbar(cells: Vec<(&str, Color)>) -> String {
+    cells
+        .into_iter()
+        .map(|(block, color)| paint(block.with(color)))
+        .collect()
 }
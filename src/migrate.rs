@@ -0,0 +1,73 @@
+// Upgrading config files written for older versions of huginn, so keys
+// that changed meaning or moved keep working instead of being ignored
+use toml_edit::{DocumentMut, Item};
+
+/// The config layout this build reads, saved as `version` in config.toml
+pub const CONFIG_VERSION: i64 = 1;
+
+/// Each step upgrades a config from the version at its index to the next
+const STEPS: [fn(&mut DocumentMut); CONFIG_VERSION as usize] = [to_v1];
+
+/// The config upgraded to CONFIG_VERSION and the version it was written
+/// for, or None if it is current or isn't valid TOML
+/// Comments and formatting are kept, as toml_edit leaves them alone
+pub fn upgrade(contents: &str) -> Option<(i64, String)> {
+    let mut doc: DocumentMut = contents.parse().ok()?;
+    // Configs from before versioning have no version key
+    let from = doc.get("version").and_then(Item::as_integer).unwrap_or(0);
+    if from > CONFIG_VERSION {
+        eprintln!(
+            "Warning: The config is for a newer huginn (version {}, this one reads {}); keys it doesn't know are ignored",
+            from, CONFIG_VERSION
+        );
+    }
+    if from >= CONFIG_VERSION {
+        return None;
+    }
+
+    for step in &STEPS[from.max(0) as usize..] {
+        step(&mut doc);
+    }
+    doc.insert("version", toml_edit::value(CONFIG_VERSION));
+    Some((from, doc.to_string()))
+}
+
+/// Before the challenge box grew to fit its content, every generated config
+/// pinned its width to the old default of 85 columns
+fn to_v1(doc: &mut DocumentMut) {
+    let Some(outer_box) = doc.get_mut("box").and_then(Item::as_table_like_mut) else {
+        return;
+    };
+    if outer_box.get("width").and_then(Item::as_integer) == Some(85) {
+        outer_box.remove("width");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_version_to_unversioned_config() {
+        let (from, upgraded) = upgrade("[display]\nos = true\n").unwrap();
+        assert_eq!(from, 0);
+        assert!(upgraded.contains("version = 1"));
+        assert!(upgraded.contains("os = true"));
+    }
+
+    #[test]
+    fn drops_pinned_box_width() {
+        let (_, upgraded) = upgrade("[box]\nwidth = 85\nheight = 3\n").unwrap();
+        assert!(!upgraded.contains("width"));
+        assert!(upgraded.contains("height = 3"));
+
+        let (_, upgraded) = upgrade("[box]\nwidth = 100\n").unwrap();
+        assert!(upgraded.contains("width = 100"));
+    }
+
+    #[test]
+    fn leaves_current_and_newer_configs_alone() {
+        assert!(upgrade("version = 1\n").is_none());
+        assert!(upgrade("version = 99\n[box]\nwidth = 85\n").is_none());
+    }
+}
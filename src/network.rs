@@ -0,0 +1,21 @@
+// Offline mode, from --offline or [network] offline, for flaky connections
+// Anything that would go online checks it first and stays local instead
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// An error naming `what`, if offline mode is on
+pub fn ensure_online(what: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if offline() {
+        return Err(format!("{} needs the network, and offline mode is on", what).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::challenge::ChallengeStatus;
+use crate::fetch::Fetch;
+use crate::updates;
+
+/// Send a condensed summary of the fetch as a desktop notification
+pub fn send(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut lines = vec![
+        format!("Up {}", fetch.uptime),
+        format!("Disk {}% used", fetch.disk_usage),
+    ];
+    if let Some(count) = updates::pending() {
+        lines.push(format!("{} updates pending", count));
+    }
+    if let Some(status) = challenge {
+        let days = status.remaining().num_days().max(0);
+        lines.push(format!("{} days left in the challenge", days));
+    }
+
+    notify_rust::Notification::new()
+        .summary(&format!("huginn: {}", fetch.distro()))
+        .body(&lines.join("\n"))
+        .show()?;
+    Ok(())
+}
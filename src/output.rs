@@ -0,0 +1,418 @@
+use crate::challenge::{ChallengeStatus, Countdown};
+use crate::color::paint;
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::{draw_progress, format_system_info, paint_greeting, paint_uptime, ProgressColorScheme};
+use crossterm::style::{Color, Stylize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Full terminal rendering with logo and cursor positioning
+    Ansi,
+    /// Linear text without cursor movement or images
+    Plain,
+    /// Colored linear text for /etc/motd and SSH login banners
+    Motd,
+    /// Escape-free text for /etc/issue
+    Issue,
+    /// Markdown tables for GitHub issues and wikis
+    Markdown,
+    /// Self-contained HTML snippet with inline styles and embedded logo
+    Html,
+    /// Scalable vector image of the fetch with the logo embedded
+    Svg,
+    /// Single-line JSON for a Waybar custom module
+    Waybar,
+    /// One line with polybar color tags
+    Polybar,
+    /// One line of plain text for i3blocks
+    I3blocks,
+    /// Short segment with tmux color directives for status-right
+    Tmux,
+    /// Tiny segment for a shell prompt, such as starship or PS1
+    Prompt,
+    /// Every collected field as JSON
+    Json,
+    /// A header and one row of the numeric metrics, for logging from cron
+    Csv,
+    /// Plain sentences in reading order, for screen readers
+    Accessible,
+}
+
+impl OutputFormat {
+    /// Whether this format carries ANSI color codes
+    pub fn is_colored(self) -> bool {
+        matches!(self, OutputFormat::Ansi | OutputFormat::Motd)
+    }
+}
+
+/// Linear rendering with no cursor movement, images or screen clearing
+/// Used whenever stdout is piped or redirected to a file
+pub fn render_plain(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    let mut out = plain_lines(fetch, challenge, config).join("\n");
+    out.push('\n');
+    out
+}
+
+/// Same layout as the plain output, but keeping colors for terminals that
+/// display /etc/motd or an SSH banner
+pub fn render_motd(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    render_plain(fetch, challenge, config)
+}
+
+/// The plain output without colors, for the clipboard and pastes
+pub fn render_text(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    let colored = crate::color::enabled();
+    crate::color::set_enabled(false);
+    let text = render_motd(fetch, challenge, config);
+    crate::color::set_enabled(colored);
+    text
+}
+
+/// Machine-readable snapshot, also what `--remote` expects from the other end
+pub fn render_json(fetch: &Fetch, challenge: Option<&ChallengeStatus>) -> String {
+    let snapshot = fetch.clone().into_snapshot(challenge.cloned());
+    serde_json::to_string_pretty(&snapshot).expect("snapshot serializes to JSON")
+}
+
+/// Columns of the CSV output, in order
+const CSV_COLUMNS: [&str; 10] = [
+    "timestamp",
+    "unix_time",
+    "cpu_usage",
+    "ram_usage",
+    "disk_usage",
+    "uptime_secs",
+    "packages",
+    "challenge_days",
+    "challenge_total_days",
+    "challenge_progress",
+];
+
+/// The numeric metrics as a CSV header and row; cells are left empty for
+/// what wasn't collected
+pub fn render_csv(fetch: &Fetch, challenge: Option<&ChallengeStatus>) -> String {
+    let now = chrono::Utc::now();
+    let optional = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+    let row = [
+        now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        now.timestamp().to_string(),
+        fetch.cpu_usage.to_string(),
+        fetch.ram_usage.to_string(),
+        fetch.disk_usage.to_string(),
+        fetch.uptime_secs.to_string(),
+        optional(fetch.info.packages.as_deref().and_then(package_total)),
+        optional(challenge.map(|c| c.days_old)),
+        optional(challenge.map(|c| c.total_days)),
+        optional(challenge.map(|c| c.progress_percentage as i64)),
+    ];
+    format!("{}\n{}\n", CSV_COLUMNS.join(","), row.join(","))
+}
+
+/// Total of a packages value, which with the breakdown style looks like
+/// "1432 (pacman), 61 (cargo)"
+fn package_total(packages: &str) -> Option<i64> {
+    packages
+        .split(", ")
+        .map(|part| part.split_whitespace().next()?.parse::<i64>().ok())
+        .sum()
+}
+
+/// Plain text for /etc/issue, where getty treats backslashes as escapes
+pub fn render_issue(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    render_motd(fetch, challenge, config).replace('\\', "\\\\")
+}
+
+/// Spoken names for the info fields, used unless a custom label is set
+const DESCRIPTIVE_LABELS: [(&str, &str); 33] = [
+    ("distro", "Distribution"),
+    ("model", "Model"),
+    ("age", "System age"),
+    ("kernel", "Kernel"),
+    ("packages", "Packages"),
+    ("shell", "Shell"),
+    ("term", "Terminal"),
+    ("wm", "Window manager"),
+    ("cpu", "CPU"),
+    ("gpu", "GPU"),
+    ("theme", "Theme"),
+    ("nix", "Nix generation"),
+    ("guix", "Guix generation"),
+    ("sip", "System Integrity Protection"),
+    ("init", "Init system"),
+    ("home", "Home directory size"),
+    ("dotfiles", "Dotfiles repository"),
+    ("backup", "Last backup"),
+    ("latency", "Network latency"),
+    ("dns", "DNS servers"),
+    ("bluetooth", "Bluetooth devices"),
+    ("ssh", "SSH sessions"),
+    ("users", "Logged-in users"),
+    ("booted", "Boot time"),
+    ("taint", "Kernel taint"),
+    ("security", "Security updates"),
+    ("power", "Power draw"),
+    ("gpu_temp", "GPU temperature"),
+    ("temps", "Temperatures"),
+    ("soc", "SoC temperature"),
+    ("streak", "Run streak"),
+    ("ram", "Memory"),
+    ("disk", "Disk"),
+];
+
+fn descriptive_label<'a>(field: &'a str, config: &'a Config) -> &'a str {
+    if let Some(label) = config.info.labels.get(field) {
+        return label;
+    }
+    DESCRIPTIVE_LABELS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map_or(field, |(_, label)| label)
+}
+
+/// One sentence per line, top to bottom, with no box drawing, bars or colors
+pub fn render_accessible(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(parts) = config.greeting.parts(&fetch.user, &fetch.hostname) {
+        lines.push(parts.into_iter().map(|(part, _)| part).collect::<String>());
+    }
+    lines.push(format!("Uptime: {}", fetch.uptime));
+    if let Some(record) = &fetch.uptime_record {
+        lines.push(format!("Uptime {}", record.text()));
+    }
+
+    for (field, value) in fetch.info.to_info_items(true, &config.display) {
+        lines.push(format!("{}: {}", descriptive_label(field, config), value));
+    }
+
+    let mut note_lines = crate::quote::quote_lines(&config.quote);
+    note_lines.extend(crate::tips::tip_lines(&config.tips));
+    lines.extend(note_lines.into_iter().map(|(line, _)| line));
+
+    for (field, value) in fetch.usage_items() {
+        lines.push(format!(
+            "{} usage: {} percent",
+            descriptive_label(field, config),
+            value
+        ));
+    }
+
+    for countdown in config
+        .countdowns
+        .iter()
+        .filter_map(|countdown| Countdown::new(countdown, &config.display))
+    {
+        lines.push(format!(
+            "{}: {} percent, {}",
+            countdown.name,
+            countdown.progress_percentage,
+            countdown.days_text()
+        ));
+    }
+    for field in &fetch.info.plugins {
+        if let Some(bar) = field.bar {
+            lines.push(format!("{}: {} percent, {}", field.label, bar, field.value));
+        }
+    }
+
+    if let Some(status) = challenge {
+        for (label, value, _) in status.entries(&config.challenge) {
+            lines.push(format!("{}: {}", label, value));
+        }
+        lines.push(format!(
+            "Challenge progress: {} percent",
+            status.progress_percentage
+        ));
+        for (name, extra) in crate::challenge::extra_challenges(config) {
+            lines.push(format!(
+                "{} progress: {} percent",
+                name, extra.progress_percentage
+            ));
+        }
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn plain_lines(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> Vec<String> {
+    let mut lines: Vec<String> = config
+        .greeting
+        .parts(&fetch.user, &fetch.hostname)
+        .map(|parts| paint_greeting(&parts, Color::Green, Color::Cyan).0)
+        .into_iter()
+        .collect();
+    lines.push(paint_uptime(fetch).0);
+    lines.push(String::new());
+
+    let info_items = fetch.info.to_info_items(true, &config.display);
+    lines.extend(format_system_info(info_items, 0, &config.info));
+    lines.push(String::new());
+
+    let mut note_lines = crate::quote::quote_lines(&config.quote);
+    note_lines.extend(crate::tips::tip_lines(&config.tips));
+    if !note_lines.is_empty() {
+        lines.extend(note_lines.into_iter().map(|(line, _)| line));
+        lines.push(String::new());
+    }
+
+    let trends = crate::history::usage_trends(config.bars.sparkline);
+    for ((label, value), trend) in fetch.usage_items().into_iter().zip(trends) {
+        lines.push(format!(
+            "{}{}{}{}",
+            paint(label.green()),
+            " ".repeat(5usize.saturating_sub(label.len())),
+            draw_progress(value, 3, ProgressColorScheme::System, &config.bars),
+            trend
+        ));
+    }
+
+    let mut countdown_items = crate::challenge::countdown_items(config);
+    countdown_items.extend(crate::plugins::bar_items(&fetch.info.plugins, &config.bars));
+    if !countdown_items.is_empty() {
+        let items = countdown_items
+            .iter()
+            .map(|(label, value)| (label.as_str(), value.clone()))
+            .collect();
+        lines.push(String::new());
+        lines.extend(format_system_info(items, 0, &config.info));
+    }
+
+    if let Some(status) = challenge {
+        let mut items = status.info_items(&config.challenge);
+        items.push((
+            "Progress",
+            draw_progress(
+                status.progress_percentage,
+                0,
+                ProgressColorScheme::Challenge,
+                &config.bars,
+            ),
+        ));
+
+        let extra = crate::challenge::extra_challenges(config);
+        items.extend(extra.iter().map(|(name, extra_status)| {
+            (
+                name.as_str(),
+                draw_progress(
+                    extra_status.progress_percentage,
+                    0,
+                    ProgressColorScheme::Challenge,
+                    &config.bars,
+                ),
+            )
+        }));
+
+        lines.push(String::new());
+        lines.extend(format_system_info(items, 0, &config.info));
+    }
+
+    lines
+}
+
+/// Render the fetch as Markdown tables, ready to paste into an issue
+pub fn render_markdown(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    let mut out = format!("### {} on {}\n\n", fetch.user, fetch.distro());
+
+    out.push_str(&markdown_table(&summary_rows(fetch, config)));
+
+    if let Some(status) = challenge {
+        out.push_str("\n#### Challenge\n\n");
+        out.push_str(&markdown_table(&challenge_rows(status, config)));
+    }
+
+    out
+}
+
+/// Waybar custom module JSON: short text, every field in the tooltip,
+/// and a class describing challenge progress for styling
+pub fn render_waybar(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    let mut tooltip_rows = summary_rows(fetch, config);
+    if let Some(status) = challenge {
+        tooltip_rows.extend(challenge_rows(status, config));
+    }
+    let tooltip = tooltip_rows
+        .iter()
+        .map(|(label, value)| format!("{}: {}", label, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let output = match challenge {
+        Some(status) => {
+            let class = if status.is_complete() {
+                "challenge-complete".to_string()
+            } else {
+                // Buckets of 25% so stylesheets only need a handful of rules
+                format!("challenge-{}", status.progress_percentage / 25 * 25)
+            };
+
+            serde_json::json!({
+                "text": format!("{}d {}%", status.days_old, status.progress_percentage),
+                "tooltip": tooltip,
+                "class": class,
+                "percentage": status.progress_percentage,
+            })
+        }
+        None => serde_json::json!({
+            "text": format!("up {}", fetch.uptime),
+            "tooltip": tooltip,
+            "class": "normal",
+        }),
+    };
+
+    output.to_string()
+}
+
+/// Every info field plus uptime and usage, as owned label/value pairs
+/// Shared by the document-style exports
+pub fn summary_rows(fetch: &Fetch, config: &Config) -> Vec<(String, String)> {
+    let mut rows: Vec<(String, String)> = fetch
+        .info
+        .to_info_items(true, &config.display)
+        .into_iter()
+        .map(|(label, value)| (label.to_string(), value))
+        .collect();
+
+    rows.push(("uptime".to_string(), fetch.uptime.clone()));
+    for (label, value) in fetch.usage_items() {
+        rows.push((format!("{} usage", label), format!("{}%", value)));
+    }
+    rows
+}
+
+pub fn challenge_rows(status: &ChallengeStatus, config: &Config) -> Vec<(String, String)> {
+    let mut rows: Vec<(String, String)> = status
+        .info_items(&config.challenge)
+        .into_iter()
+        .map(|(label, value)| (label.to_string(), value))
+        .collect();
+
+    rows.push((
+        crate::i18n::tr("Progress").to_string(),
+        format!(
+            "{}% ({} of {} days)",
+            status.progress_percentage, status.days_old, status.total_days
+        ),
+    ));
+    rows
+}
+
+fn markdown_table(rows: &[(String, String)]) -> String {
+    let mut table = String::from("| Field | Value |\n| --- | --- |\n");
+    for (label, value) in rows {
+        table.push_str(&format!("| {} | {} |\n", label, value.replace('|', "\\|")));
+    }
+    table
+}
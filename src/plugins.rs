@@ -0,0 +1,153 @@
+// Executables in ~/.config/huginn/plugins that add info fields
+// Each one prints a JSON object, or an array of them, to stdout:
+//   {"label": "weather", "value": "12°C", "order": 3, "color": "cyan"}
+// .wasm modules there are run sandboxed instead, see wasm.rs
+use crate::config::BarsConfig;
+use crate::error::CollectError;
+use crate::{draw_progress, ProgressColorScheme};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// An info field from a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginField {
+    pub label: String,
+    pub value: String,
+    /// Position in the info list, or after the built-in fields if unset
+    #[serde(default)]
+    pub order: Option<usize>,
+    /// Value color, unless [info.value_colors] sets one
+    #[serde(default)]
+    pub color: Option<String>,
+    /// A percentage to draw as a bar below the info lines instead
+    #[serde(default)]
+    pub bar: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Output {
+    One(PluginField),
+    Many(Vec<PluginField>),
+}
+
+pub fn plugin_dir() -> PathBuf {
+    let home = crate::config::home_dir().unwrap_or_default();
+    PathBuf::from(home).join(".config/huginn/plugins")
+}
+
+/// Plugin executables and .wasm modules, by file name
+pub fn discover() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(plugin_dir()) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_wasm(path) || is_executable(path))
+        .collect();
+    plugins.sort();
+    plugins
+}
+
+fn is_wasm(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "wasm")
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Name a plugin goes by in [collectors.timeouts] and --verbose output
+pub fn name(path: &Path) -> String {
+    path.file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Run a plugin and parse the fields it prints, killing it if it runs past
+/// `timeout` so a hung plugin doesn't outlive huginn
+pub fn run_plugin(path: &Path, timeout: Duration) -> Result<Vec<PluginField>, CollectError> {
+    if is_wasm(path) {
+        #[cfg(feature = "wasm")]
+        return crate::wasm::run(path);
+        #[cfg(not(feature = "wasm"))]
+        return Err(CollectError::Failed(
+            "huginn was built without the wasm feature".to_string(),
+        ));
+    }
+
+    // Plugins that fetch something, like the weather, can skip it offline
+    let mut command = Command::new(path);
+    if crate::network::offline() {
+        command.env("HUGINN_OFFLINE", "1");
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| CollectError::spawn(&path.to_string_lossy(), e))?;
+
+    // Read on another thread so a plugin printing more than the pipe holds
+    // doesn't block before it exits
+    let mut stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_end(&mut buffer);
+        }
+        buffer
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CollectError::Timeout(timeout.as_millis() as u64));
+            }
+            Err(e) => return Err(CollectError::Failed(e.to_string())),
+        }
+    };
+    if !status.success() {
+        return Err(CollectError::Failed(format!("exited with {}", status)));
+    }
+    let stdout = reader
+        .join()
+        .map_err(|_| CollectError::Failed("could not read its output".to_string()))?;
+    parse(&stdout)
+}
+
+/// Fields from a plugin's JSON output
+pub fn parse(json: &[u8]) -> Result<Vec<PluginField>, CollectError> {
+    match serde_json::from_slice(json) {
+        Ok(Output::One(field)) => Ok(vec![field]),
+        Ok(Output::Many(fields)) => Ok(fields),
+        Err(e) => Err(CollectError::Failed(format!("invalid JSON: {}", e))),
+    }
+}
+
+/// Label and bar for every plugin field with a bar, laid out like countdowns
+pub fn bar_items(fields: &[PluginField], bars: &BarsConfig) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let bar = draw_progress(field.bar?, 3, ProgressColorScheme::System, bars);
+            Some((field.label.clone(), format!("{} {}", bar, field.value)))
+        })
+        .collect()
+}
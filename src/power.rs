@@ -0,0 +1,99 @@
+// The `power` field: how many watts the machine draws right now
+// On battery the kernel reports the discharge rate directly; otherwise the
+// CPU package's energy counter from RAPL is sampled twice
+use crate::error::CollectError;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POWER_SUPPLIES: &str = "/sys/class/power_supply";
+const POWERCAP: &str = "/sys/class/powercap";
+/// Between the two RAPL readings
+const SAMPLE: Duration = Duration::from_millis(250);
+
+/// Watts like "12.3 W", and the color for them under `thresholds`
+pub fn status(thresholds: &[f64]) -> Result<(String, Option<String>), CollectError> {
+    let watts = battery_draw()
+        .or_else(rapl_draw)
+        .ok_or_else(|| CollectError::Failed("no battery discharging and no RAPL".to_string()))?;
+    Ok((format!("{:.1} W", watts), color(watts, thresholds)))
+}
+
+/// Green below the first threshold, yellow below the second, red above
+fn color(watts: f64, thresholds: &[f64]) -> Option<String> {
+    if thresholds.is_empty() {
+        return None;
+    }
+    let level = thresholds.iter().filter(|limit| watts >= **limit).count();
+    let color = ["green", "yellow", "red"][level.min(2)];
+    Some(color.to_string())
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Draw of the batteries that are discharging, in watts
+fn battery_draw() -> Option<f64> {
+    let mut total = None;
+    for entry in fs::read_dir(POWER_SUPPLIES).ok()?.flatten() {
+        let dir = entry.path();
+        let is_battery = fs::read_to_string(dir.join("type")).is_ok_and(|t| t.trim() == "Battery");
+        let discharging =
+            fs::read_to_string(dir.join("status")).is_ok_and(|s| s.trim() == "Discharging");
+        if !is_battery || !discharging {
+            continue;
+        }
+        // Microwatts, or microamps and microvolts on some batteries
+        let microwatts = read_number(&dir.join("power_now")).or_else(|| {
+            let amps = read_number(&dir.join("current_now"))?;
+            let volts = read_number(&dir.join("voltage_now"))?;
+            Some(amps * volts / 1e6)
+        })?;
+        *total.get_or_insert(0.0) += microwatts / 1e6;
+    }
+    total
+}
+
+/// Package power from the RAPL energy counters, in watts
+fn rapl_draw() -> Option<f64> {
+    // The top-level zones, intel-rapl:0 and so on, are the CPU packages;
+    // AMD CPUs report through the same interface
+    let zones: Vec<_> = fs::read_dir(POWERCAP)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("intel-rapl:") && name.matches(':').count() == 1
+                })
+        })
+        .collect();
+    // Reading the counters needs root on most kernels
+    let read = || -> Option<Vec<f64>> {
+        zones
+            .iter()
+            .map(|zone| read_number(&zone.join("energy_uj")))
+            .collect()
+    };
+
+    let before = read().filter(|energy| !energy.is_empty())?;
+    let start = Instant::now();
+    thread::sleep(SAMPLE);
+    let after = read()?;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let mut microjoules = 0.0;
+    for ((zone, before), after) in zones.iter().zip(before).zip(after) {
+        // The counter wraps around at max_energy_range_uj
+        microjoules += if after >= before {
+            after - before
+        } else {
+            read_number(&zone.join("max_energy_range_uj"))? - before + after
+        };
+    }
+    Some(microjoules / 1e6 / elapsed)
+}
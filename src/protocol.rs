@@ -0,0 +1,51 @@
+use crate::fetch::Fetch;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+// Messages between `huginn daemon` and its clients are single lines of JSON
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    Fetch,
+    Ping,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+pub enum Response {
+    Fetch { fetch: Box<Fetch> },
+    Pong,
+    Error { message: String },
+}
+
+/// Per-user socket, in XDG_RUNTIME_DIR when available
+pub fn socket_path() -> PathBuf {
+    match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("huginn.sock"),
+        _ => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+            std::env::temp_dir().join(format!("huginn-{}.sock", user))
+        }
+    }
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed",
+        ));
+    }
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
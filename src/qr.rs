@@ -0,0 +1,33 @@
+// --qr: the fetch, or a `huginn share` link, as a QR code for a phone camera
+use crate::challenge::ChallengeStatus;
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::output;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::{EcLevel, QrCode};
+
+/// Every field as one flat JSON object, compact enough to fit in a QR code
+pub fn summary_json(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    let mut rows = output::summary_rows(fetch, config);
+    if let Some(status) = challenge {
+        rows.extend(output::challenge_rows(status, config));
+    }
+    let summary: serde_json::Map<String, serde_json::Value> = rows
+        .into_iter()
+        .map(|(label, value)| (label, value.into()))
+        .collect();
+    serde_json::Value::Object(summary).to_string()
+}
+
+/// `data` as a QR code in half-block characters, two modules per cell
+pub fn render(data: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // Low error correction leaves the most room for data
+    let code = QrCode::with_error_correction_level(data, EcLevel::L)
+        .map_err(|e| format!("{} ({} bytes)", e, data.len()))?;
+    // Drawn light on dark, which scans fine on dark terminal backgrounds
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build())
+}
@@ -0,0 +1,77 @@
+use crate::color::paint;
+use crate::config::QuoteConfig;
+use crate::error::command_output;
+use crate::expand_home;
+use crossterm::style::Stylize;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A random quote wrapped to the configured width, painted and with the
+/// width of each line, or nothing when the block is off or no quote could
+/// be found
+/// The quote is picked once per process, so watch mode keeps showing it
+pub fn quote_lines(config: &QuoteConfig) -> Vec<(String, usize)> {
+    static QUOTE: OnceLock<Option<String>> = OnceLock::new();
+    if !config.enabled {
+        return Vec::new();
+    }
+    let Some(quote) = QUOTE.get_or_init(|| pick(config)) else {
+        return Vec::new();
+    };
+    wrap(quote, config.width)
+        .into_iter()
+        .map(|line| {
+            let width = line.chars().count();
+            (paint(line.italic()), width)
+        })
+        .collect()
+}
+
+/// A random line of the quotes file, or the output of `fortune` without one
+fn pick(config: &QuoteConfig) -> Option<String> {
+    if config.file.is_empty() {
+        return command_output("fortune", &["-s"]);
+    }
+
+    let path = expand_home(&config.file);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Warning: Failed to read quotes file {}: {}", path, e);
+            return None;
+        }
+    };
+    let quotes: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if quotes.is_empty() {
+        return None;
+    }
+
+    // Good enough randomness for picking a quote
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos() as usize);
+    Some(quotes[seed % quotes.len()].to_string())
+}
+
+/// Break text into lines of at most `width` columns at word boundaries
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
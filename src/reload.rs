@@ -0,0 +1,107 @@
+// Noticing edits to config.toml while watch mode or the daemon runs, so
+// theme and field changes show up without a restart
+use crate::config::Config;
+use ::notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Editors write a file in several steps; wait for them to settle
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Command-line changes applied to every loaded config
+type Overrides = Box<dyn Fn(&mut Config) -> Result<(), String> + Send>;
+
+pub struct ConfigWatcher {
+    path: PathBuf,
+    changed: Arc<AtomicBool>,
+    overrides: Overrides,
+    // Watching stops when it is dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Watch the config file, if there is one; `overrides` is applied to
+    /// every reloaded config, like the command line was to the first one
+    pub fn start(
+        overrides: impl Fn(&mut Config) -> Result<(), String> + Send + 'static,
+    ) -> Option<Self> {
+        let path = Config::find_config_file()?;
+        let name = path.file_name()?.to_owned();
+        let changed = Arc::new(AtomicBool::new(false));
+
+        let flag = Arc::clone(&changed);
+        let watcher = ::notify::recommended_watcher(move |event: ::notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !event.kind.is_access()
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == Some(name.as_os_str()))
+            {
+                flag.store(true, Ordering::Relaxed);
+            }
+        });
+        // Editors often save by renaming a new file over the old one, which
+        // a watch on the file itself would lose track of
+        let watching = watcher.and_then(|mut watcher| {
+            let dir = path.parent().unwrap_or(&path);
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        match watching {
+            Ok(watcher) => Some(Self {
+                path,
+                changed,
+                overrides: Box::new(overrides),
+                _watcher: watcher,
+            }),
+            Err(e) => {
+                eprintln!("Warning: could not watch the config for changes: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether the config file changed since the last call
+    pub fn changed(&self) -> bool {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return false;
+        }
+        thread::sleep(SETTLE);
+        self.changed.store(false, Ordering::Relaxed);
+        true
+    }
+
+    /// Read the config file again; a file with errors is reported rather
+    /// than replaced by the defaults, so a half-finished edit changes nothing
+    pub fn reload(&self) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut config = Config::parse(&fs::read_to_string(&self.path)?)?;
+        (self.overrides)(&mut config)?;
+        Ok(config)
+    }
+
+    /// Sleep for `duration`, or until the config changes; true if it did
+    pub fn sleep(watcher: Option<&Self>, duration: Duration) -> bool {
+        let Some(watcher) = watcher else {
+            thread::sleep(duration);
+            return false;
+        };
+        let until = Instant::now() + duration;
+        loop {
+            if watcher.changed() {
+                return true;
+            }
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            thread::sleep(left.min(SETTLE));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::fetch::Snapshot;
+use std::process::Command;
+
+/// Run huginn on another machine over ssh and parse its JSON snapshot
+/// The remote host needs huginn on its PATH; rendering happens locally
+pub fn fetch_remote(
+    host: &str,
+    challenge: Option<(i64, i64)>,
+) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    crate::network::ensure_online("--remote")?;
+
+    let mut remote_args = vec!["huginn".to_string(), "--json".to_string()];
+    if let Some((years, months)) = challenge {
+        remote_args.extend([
+            "--challenge".to_string(),
+            "--years".to_string(),
+            years.to_string(),
+            "--months".to_string(),
+            months.to_string(),
+        ]);
+    }
+
+    let output = Command::new("ssh")
+        // "--" keeps a host like "-oProxyCommand=..." from being read as an option
+        .args(["-o", "BatchMode=yes", "--", host])
+        .args(&remote_args)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ssh {} failed: {}", host, stderr.trim()).into());
+    }
+
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
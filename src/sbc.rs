@@ -0,0 +1,86 @@
+// Single-board computers like the Raspberry Pi: the board's name from the
+// device tree, and the `soc` field with the SoC's temperature and whether
+// the firmware is throttling it
+use crate::error::{self, CollectError};
+use std::fs;
+
+/// The SoC's sensor on most ARM boards, in millidegrees
+const SOC_THERMAL_ZONE: &str = "/sys/class/thermal/thermal_zone0/temp";
+
+/// Bits of `vcgencmd get_throttled` for what is happening now; the same
+/// bits 16 places up say it happened since boot
+const THROTTLE_REASONS: [(u32, &str); 4] = [
+    (0x1, "under-voltage"),
+    (0x2, "frequency capped"),
+    (0x4, "throttled"),
+    (0x8, "soft temperature limit"),
+];
+
+/// The board's model from the device tree, like "Raspberry Pi 4 Model B
+/// Rev 1.4"; boards have no DMI tables to read it from
+pub fn board() -> Option<String> {
+    let model = fs::read("/proc/device-tree/model").ok()?;
+    // The property is NUL-terminated
+    let model = String::from_utf8_lossy(&model)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string();
+    (!model.is_empty()).then_some(model)
+}
+
+/// The SoC temperature and what the firmware is throttling it for, like
+/// "71°C, frequency capped", and the color for it if it is or was
+pub fn status() -> Result<(String, Option<String>), CollectError> {
+    let celsius = celsius()
+        .ok_or_else(|| CollectError::Failed("no SoC temperature sensor found".to_string()))?;
+    let temperature = format!("{:.0}°C", celsius);
+
+    // Only Raspberry Pi firmware reports throttling
+    let flags = vcgencmd("get_throttled")
+        .and_then(|value| u32::from_str_radix(value.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(0);
+    let reasons = |flags: u32| {
+        THROTTLE_REASONS
+            .iter()
+            .filter(|(bit, _)| flags & bit != 0)
+            .map(|(_, reason)| *reason)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let (now, earlier) = (reasons(flags), reasons(flags >> 16));
+    Ok(if !now.is_empty() {
+        (format!("{}, {}", temperature, now), Some("red".to_string()))
+    } else if !earlier.is_empty() {
+        (
+            format!("{}, {} since boot", temperature, earlier),
+            Some("yellow".to_string()),
+        )
+    } else {
+        (temperature, None)
+    })
+}
+
+fn celsius() -> Option<f32> {
+    vcgencmd("measure_temp")
+        .and_then(|value| value.trim_end_matches("'C").parse().ok())
+        .or_else(|| {
+            let millidegrees: f32 = fs::read_to_string(SOC_THERMAL_ZONE)
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(millidegrees / 1000.0)
+        })
+}
+
+/// The value of a Raspberry Pi firmware query, which answers like
+/// "temp=48.3'C"
+fn vcgencmd(command: &str) -> Option<String> {
+    let output = error::run("vcgencmd", &[command]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, value) = stdout.trim().split_once('=')?;
+    Some(value.to_string())
+}
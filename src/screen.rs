@@ -0,0 +1,307 @@
+// Where the terminal layout is written: straight to stdout, or into a buffer
+// that watch mode turns into a grid of cells and compares with the last
+// frame, so only the cells that changed are rewritten
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+/// A logo the layout asked for while being captured, drawn separately
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingLogo {
+    pub distro: String,
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Default)]
+struct Capture {
+    bytes: Vec<u8>,
+    logos: Vec<PendingLogo>,
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Capture>> = const { RefCell::new(None) };
+}
+
+/// Writer for everything the layout prints
+pub struct Out;
+
+pub fn out() -> Out {
+    Out
+}
+
+impl Write for Out {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let captured = CAPTURE.with_borrow_mut(|capture| match capture {
+            Some(capture) => {
+                capture.bytes.extend_from_slice(buf);
+                true
+            }
+            None => false,
+        });
+        if captured {
+            Ok(buf.len())
+        } else {
+            io::stdout().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if capturing() {
+            return Ok(());
+        }
+        io::stdout().flush()
+    }
+}
+
+pub fn capturing() -> bool {
+    CAPTURE.with_borrow(Option::is_some)
+}
+
+/// Instead of drawing a logo while capturing, note it down and move the
+/// cursor below it, where the image would have left it
+pub fn defer_logo(distro: &str, x: u16, y: u16, height: u16) {
+    CAPTURE.with_borrow_mut(|capture| {
+        if let Some(capture) = capture {
+            capture.logos.push(PendingLogo {
+                distro: distro.to_string(),
+                x,
+                y,
+            });
+            let _ = queue!(capture.bytes, MoveTo(0, y.saturating_add(height)));
+        }
+    });
+}
+
+/// Run `draw` with its output captured into a frame
+pub fn capture<T>(draw: impl FnOnce() -> T) -> (T, Frame) {
+    CAPTURE.set(Some(Capture::default()));
+    let result = draw();
+    let capture = CAPTURE.take().unwrap_or_default();
+    (result, Frame::parse(&capture.bytes, capture.logos))
+}
+
+/// Colors and attributes set by SGR sequences
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Style {
+    fg: Option<String>,
+    bg: Option<String>,
+    /// Bold, dim, italic and so on, by their SGR number
+    attributes: Vec<u8>,
+}
+
+impl Style {
+    /// Apply the parameters of one SGR sequence
+    fn apply(&mut self, params: &str) {
+        let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Style::default(),
+                // 22 ends bold and dim, 23 italic, and so on
+                21..=29 => {
+                    let ended: &[u8] = if param == 22 { &[1, 2] } else { &[param - 20] };
+                    self.attributes.retain(|a| !ended.contains(a));
+                }
+                1..=9 if !self.attributes.contains(&param) => self.attributes.push(param),
+                30..=37 | 90..=97 => self.fg = Some(param.to_string()),
+                40..=47 | 100..=107 => self.bg = Some(param.to_string()),
+                38 | 48 => {
+                    // 5;n for 256 colors, 2;r;g;b for true color
+                    let count = if params.next() == Some(5) { 1 } else { 3 };
+                    let values: Vec<String> =
+                        params.by_ref().take(count).map(|v| v.to_string()).collect();
+                    let kind = if count == 1 { "5" } else { "2" };
+                    let color = Some(format!("{};{};{}", param, kind, values.join(";")));
+                    if param == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                39 => self.fg = None,
+                49 => self.bg = None,
+                _ => {}
+            }
+        }
+    }
+
+    /// One SGR sequence setting this style from scratch
+    fn sgr(&self) -> String {
+        let params: Vec<String> = std::iter::once("0".to_string())
+            .chain(self.attributes.iter().map(u8::to_string))
+            .chain(self.fg.clone())
+            .chain(self.bg.clone())
+            .collect();
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// A character and the style it is drawn in
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    style: Style,
+    ch: char,
+}
+
+/// What a captured layout puts on screen, cell by cell
+#[derive(Default)]
+pub struct Frame {
+    rows: Vec<Vec<Option<Cell>>>,
+    pub logos: Vec<PendingLogo>,
+}
+
+impl Frame {
+    /// Replay the output on a blank screen, following cursor moves, line
+    /// breaks and colors; other escape sequences are skipped
+    fn parse(bytes: &[u8], logos: Vec<PendingLogo>) -> Self {
+        let text = String::from_utf8_lossy(bytes);
+        let mut frame = Frame {
+            rows: Vec::new(),
+            logos,
+        };
+        let (mut row, mut col) = (0usize, 0usize);
+        let mut style = Style::default();
+
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => match chars.next() {
+                    Some('[') => {
+                        let mut params = String::new();
+                        let mut end = None;
+                        for c in chars.by_ref() {
+                            if ('\x40'..='\x7e').contains(&c) {
+                                end = Some(c);
+                                break;
+                            }
+                            params.push(c);
+                        }
+                        match end {
+                            Some('m') => style.apply(&params),
+                            Some('H') => {
+                                let mut position = params
+                                    .split(';')
+                                    .map(|n| n.parse::<usize>().unwrap_or(1).max(1) - 1);
+                                row = position.next().unwrap_or(0);
+                                col = position.next().unwrap_or(0);
+                            }
+                            Some('J') if params == "2" => frame.rows.clear(),
+                            _ => {}
+                        }
+                    }
+                    // OSC, ended by BEL or ST
+                    Some(']') => {
+                        while let Some(c) = chars.next() {
+                            if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                '\n' => {
+                    row += 1;
+                    col = 0;
+                }
+                '\r' => col = 0,
+                c if c.is_control() => {}
+                c => {
+                    frame.put(
+                        row,
+                        col,
+                        Cell {
+                            style: style.clone(),
+                            ch: c,
+                        },
+                    );
+                    col += 1;
+                }
+            }
+        }
+        frame
+    }
+
+    fn put(&mut self, row: usize, col: usize, cell: Cell) {
+        if self.rows.len() <= row {
+            self.rows.resize(row + 1, Vec::new());
+        }
+        let line = &mut self.rows[row];
+        if line.len() <= col {
+            line.resize(col + 1, None);
+        }
+        line[col] = Some(cell);
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Option<&Cell> {
+        self.rows.get(row)?.get(col)?.as_ref()
+    }
+
+    /// Rows from the top of the screen to the last one drawn on
+    pub fn height(&self) -> u16 {
+        self.rows.len() as u16
+    }
+
+    /// The frame as lines of text, keeping colors but not cursor moves, for
+    /// writing the terminal layout to a file
+    pub fn to_text(&self) -> String {
+        let plain = Style::default();
+        let mut out = String::new();
+        for line in &self.rows {
+            let mut style = &plain;
+            for cell in line {
+                let (cell_style, ch) = cell
+                    .as_ref()
+                    .map_or((&plain, ' '), |cell| (&cell.style, cell.ch));
+                if cell_style != style {
+                    out.push_str(&cell_style.sgr());
+                    style = cell_style;
+                }
+                out.push(ch);
+            }
+            if *style != plain {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Rewrite the cells that differ from `previous`, one span per row, and
+    /// blank the ones that are gone
+    pub fn draw_changes(&self, previous: &Frame) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for row in 0..self.rows.len().max(previous.rows.len()) {
+            let width = [&self.rows, &previous.rows]
+                .iter()
+                .filter_map(|rows| rows.get(row).map(Vec::len))
+                .max()
+                .unwrap_or(0);
+            let changed = |col: &usize| self.cell(row, *col) != previous.cell(row, *col);
+            let Some(first) = (0..width).find(changed) else {
+                continue;
+            };
+            let last = (0..width).rev().find(changed).unwrap_or(first);
+
+            queue!(stdout, MoveTo(first as u16, row as u16))?;
+            let plain = Style::default();
+            let mut style = &plain;
+            for col in first..=last {
+                let (cell_style, ch) = self
+                    .cell(row, col)
+                    .map_or((&plain, ' '), |cell| (&cell.style, cell.ch));
+                if cell_style != style {
+                    write!(stdout, "{}", cell_style.sgr())?;
+                    style = cell_style;
+                }
+                write!(stdout, "{}", ch)?;
+            }
+            if *style != plain {
+                write!(stdout, "\x1b[0m")?;
+            }
+        }
+        // Leave the cursor below everything, like a full draw does
+        queue!(stdout, MoveTo(0, self.rows.len() as u16))?;
+        stdout.flush()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::config::Config;
+use crate::exporter::render_metrics;
+use crate::fetch::Snapshot;
+use crate::html::render_page;
+use std::io;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Response, Server};
+
+/// Settings for the HTTP server subcommand
+pub struct ServeOptions {
+    pub addr: String,
+    pub interval: Duration,
+    pub challenge: Option<(i64, i64)>,
+}
+
+/// Serve the latest fetch as JSON, HTML and Prometheus metrics
+/// Collectors run on a background thread so requests never wait on them
+pub fn serve(options: ServeOptions, config: Config) -> io::Result<()> {
+    let server = Server::http(&options.addr).map_err(io::Error::other)?;
+    let config = Arc::new(config);
+    let snapshot = Arc::new(RwLock::new(Snapshot::collect(&config, options.challenge)));
+
+    {
+        let config = Arc::clone(&config);
+        let snapshot = Arc::clone(&snapshot);
+        thread::spawn(move || loop {
+            thread::sleep(options.interval);
+            let fresh = Snapshot::collect(&config, options.challenge);
+            if let Ok(mut current) = snapshot.write() {
+                *current = fresh;
+            }
+        });
+    }
+
+    println!("Serving huginn on http://{}/", options.addr);
+
+    for request in server.incoming_requests() {
+        let current = match snapshot.read() {
+            Ok(current) => current.clone(),
+            Err(_) => break,
+        };
+        let challenge = current.challenge.as_ref();
+
+        let (body, content_type) = match request.url() {
+            "/json" => (
+                serde_json::to_string_pretty(&current).map_err(io::Error::other)?,
+                "application/json",
+            ),
+            "/" | "/html" => (
+                render_page(&current.fetch, challenge, &config, options.interval),
+                "text/html; charset=utf-8",
+            ),
+            "/metrics" => (
+                render_metrics(&current.fetch, challenge),
+                "text/plain; version=0.0.4; charset=utf-8",
+            ),
+            _ => {
+                let _ = request.respond(
+                    Response::from_string("Not found: try /json, /html or /metrics\n")
+                        .with_status_code(404),
+                );
+                continue;
+            }
+        };
+
+        let header =
+            Header::from_bytes("Content-Type", content_type).expect("static header is valid");
+        let _ = request.respond(Response::from_string(body).with_header(header));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// Login sessions from `who`, which reads utmp, for the ssh and users fields
+use crate::error::{self, CollectError};
+
+/// One line of `who`: "alice  pts/0  2024-05-01 12:00 (203.0.113.7)"
+struct Session {
+    user: String,
+    host: Option<String>,
+}
+
+impl Session {
+    /// Logged in from another machine; local X displays show up as ":0" and
+    /// tmux and screen windows as "tmux(1234).%0" or ":pts/1:S.0"
+    fn is_remote(&self) -> bool {
+        self.host.as_deref().is_some_and(|host| {
+            !host.starts_with(':') && !host.starts_with("tmux(") && !host.is_empty()
+        })
+    }
+}
+
+fn sessions() -> Result<Vec<Session>, CollectError> {
+    let output = error::run("who", &[])?;
+    if !output.status.success() {
+        return Err(CollectError::Failed(format!(
+            "who exited with {}",
+            output.status
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Session {
+            user: line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            host: line
+                .trim_end()
+                .split_once('(')
+                .and_then(|(_, host)| host.strip_suffix(')'))
+                .map(str::to_string),
+        })
+        .collect())
+}
+
+/// How many users are logged in over SSH, like "2 sessions"
+pub fn ssh() -> Result<String, CollectError> {
+    let count = sessions()?.iter().filter(|s| s.is_remote()).count();
+    Ok(match count {
+        0 => "none".to_string(),
+        1 => "1 session".to_string(),
+        n => format!("{} sessions", n),
+    })
+}
+
+/// Logged-in users and how many terminals each has open, like
+/// "alice (3 ttys), bob (1 tty)"
+pub fn users() -> Result<String, CollectError> {
+    let mut users: Vec<(String, usize)> = Vec::new();
+    for session in sessions()? {
+        match users.iter_mut().find(|(user, _)| *user == session.user) {
+            Some((_, count)) => *count += 1,
+            None => users.push((session.user, 1)),
+        }
+    }
+    if users.is_empty() {
+        return Ok("none".to_string());
+    }
+    Ok(users
+        .iter()
+        .map(|(user, count)| {
+            let unit = if *count == 1 { "tty" } else { "ttys" };
+            format!("{} ({} {})", user, count, unit)
+        })
+        .collect::<Vec<_>>()
+        .join(", "))
+}
@@ -0,0 +1,92 @@
+// `huginn share`: upload the fetch to a paste service and print the link
+use crate::challenge::ChallengeStatus;
+use crate::config::{Config, ShareConfig};
+use crate::fetch::Fetch;
+use crate::output;
+use std::io::{self, IsTerminal, Write};
+
+/// Boundary between the parts of a multipart upload
+const BOUNDARY: &str = "huginn-share-7f3a9c";
+
+/// Upload the fetch, after asking unless `yes` or [share] confirm = false,
+/// and return the link, or None if the upload was declined
+pub fn run(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+    yes: bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    crate::network::ensure_online("huginn share")?;
+
+    let text = match config.share.format.as_str() {
+        "plain" => output::render_text(fetch, challenge, config),
+        "json" => output::render_json(fetch, challenge) + "\n",
+        other => {
+            return Err(
+                format!("unknown [share] format {:?}, expected plain or json", other).into(),
+            )
+        }
+    };
+
+    if config.share.confirm && !yes && !confirm(&text, &config.share.endpoint)? {
+        println!("Nothing was uploaded.");
+        return Ok(None);
+    }
+
+    upload(&text, &config.share).map(Some)
+}
+
+/// Show what would be uploaded and ask for a yes
+fn confirm(text: &str, endpoint: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    if !io::stdin().is_terminal() {
+        return Err(
+            "can't ask for confirmation without a terminal; pass --yes to upload anyway".into(),
+        );
+    }
+
+    print!("{}\nUpload this to {}? [y/N] ", text, endpoint);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Upload `text` and return the link the service answers with
+pub fn upload(text: &str, share: &ShareConfig) -> Result<String, Box<dyn std::error::Error>> {
+    crate::network::ensure_online("huginn share")?;
+
+    let mut response = if share.form_field.is_empty() {
+        ureq::post(&share.endpoint)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .send(text)?
+    } else {
+        let extension = if share.format == "json" {
+            "json"
+        } else {
+            "txt"
+        };
+        let body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"huginn.{}\"\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n--{boundary}--\r\n",
+            share.form_field,
+            extension,
+            text,
+            boundary = BOUNDARY
+        );
+        ureq::post(&share.endpoint)
+            .header(
+                "Content-Type",
+                &format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .send(&body)?
+    };
+
+    let url = response.body_mut().read_to_string()?.trim().to_string();
+    if !url.starts_with("http") {
+        return Err(format!(
+            "{} answered with {:?} instead of a link",
+            share.endpoint, url
+        )
+        .into());
+    }
+    Ok(url)
+}
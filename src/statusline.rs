@@ -0,0 +1,176 @@
+use crate::challenge::ChallengeStatus;
+use crate::color::to_hex;
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::{progress_color, ProgressColorScheme};
+use crossterm::style::Color;
+
+/// One field of a status line: short label, value and optional highlight
+struct Segment {
+    label: String,
+    value: String,
+    color: Option<Color>,
+}
+
+/// Resolve `fields`, skipping any that have no value; `compact` shortens
+/// the challenge to the days left
+fn segments(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+    fields: &[String],
+    compact: bool,
+) -> Vec<Segment> {
+    let info_items = fetch.info.to_info_items(true, &config.display);
+    let usage = |label: &str, value: i32| Segment {
+        label: label.to_string(),
+        value: format!("{}%", value),
+        color: Some(progress_color(
+            value,
+            ProgressColorScheme::System,
+            &config.bars,
+        )),
+    };
+
+    fields
+        .iter()
+        .filter_map(|field| match field.as_str() {
+            "uptime" => Some(Segment {
+                label: "up".to_string(),
+                value: fetch.uptime.clone(),
+                color: None,
+            }),
+            "cpu_usage" => Some(usage("cpu", fetch.cpu_usage)),
+            "ram_usage" => Some(usage("ram", fetch.ram_usage)),
+            "disk_usage" => Some(usage("disk", fetch.disk_usage)),
+            "challenge" => challenge.map(|status| Segment {
+                label: "challenge".to_string(),
+                value: if status.is_complete() {
+                    "done".to_string()
+                } else if compact {
+                    format!("{}d", status.remaining().num_days())
+                } else {
+                    format!("{}d left", status.remaining().num_days())
+                },
+                color: Some(progress_color(
+                    status.progress_percentage,
+                    ProgressColorScheme::Challenge,
+                    &config.bars,
+                )),
+            }),
+            name => info_items
+                .iter()
+                .find(|(label, _)| *label == name)
+                .map(|(label, value)| Segment {
+                    label: label.to_string(),
+                    value: value.clone(),
+                    color: None,
+                }),
+        })
+        .collect()
+}
+
+/// Polybar line: labels dimmed and values colored with %{F} tags
+pub fn render_polybar(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    segments(fetch, challenge, config, &config.status.fields, false)
+        .into_iter()
+        .map(|segment| {
+            let value = match segment.color {
+                Some(color) => format!("%{{F{}}}{}%{{F-}}", to_hex(color), segment.value),
+                None => segment.value,
+            };
+            format!(
+                "%{{F{}}}{}%{{F-}} {}",
+                to_hex(Color::DarkGrey),
+                segment.label,
+                value
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(&config.status.separator)
+}
+
+/// Plain text line for i3blocks and similar bars
+pub fn render_i3blocks(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    segments(fetch, challenge, config, &config.status.fields, false)
+        .into_iter()
+        .map(|segment| format!("{} {}", segment.label, segment.value))
+        .collect::<Vec<_>>()
+        .join(&config.status.separator)
+}
+
+/// tmux status-right segment using #[fg=...] style directives
+pub fn render_tmux(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    // A literal '#' would start a tmux format sequence
+    let escape = |text: &str| text.replace('#', "##");
+
+    segments(fetch, challenge, config, &config.status.fields, false)
+        .into_iter()
+        .map(|segment| {
+            let value = match segment.color {
+                Some(color) => format!(
+                    "#[fg={}]{}#[fg=default]",
+                    to_hex(color),
+                    escape(&segment.value)
+                ),
+                None => escape(&segment.value),
+            };
+            format!(
+                "#[fg={}]{}#[fg=default] {}",
+                to_hex(Color::DarkGrey),
+                escape(&segment.label),
+                value
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(&escape(&config.status.separator))
+}
+
+/// A short segment for a shell prompt, such as a starship custom module or
+/// PS1, escaped so the shell shows it as is
+pub fn render_prompt(
+    fetch: &Fetch,
+    challenge: Option<&ChallengeStatus>,
+    config: &Config,
+) -> String {
+    let prompt = &config.prompt;
+    let text = prompt
+        .fields
+        .iter()
+        .flat_map(|field| {
+            let icon = prompt.icons.get(field).map_or("", String::as_str);
+            segments(fetch, challenge, config, std::slice::from_ref(field), true)
+                .into_iter()
+                .map(move |segment| format!("{}{}", icon, segment.value))
+        })
+        .collect::<Vec<_>>()
+        .join(&prompt.separator);
+
+    // Escape sequences in a value would garble the prompt
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    match prompt.escape.as_str() {
+        "bash" => escape_shell(&text),
+        "zsh" => escape_shell(&text).replace('%', "%%"),
+        _ => text,
+    }
+}
+
+/// Backslash what PS1 would otherwise expand as a command or variable
+fn escape_shell(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
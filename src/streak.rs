@@ -0,0 +1,35 @@
+use crate::config::data_dir;
+use chrono::{Local, NaiveDate};
+use std::fs;
+use std::path::PathBuf;
+
+/// Last day huginn ran on this machine and the streak as of that day
+fn streak_path() -> PathBuf {
+    data_dir().join("streak")
+}
+
+/// Count today's run and return the number of consecutive days huginn has run
+pub fn record() -> Result<u32, Box<dyn std::error::Error>> {
+    let today = Local::now().date_naive();
+    let path = streak_path();
+
+    // Stored as "<date> <days>"
+    let last = fs::read_to_string(&path).ok().and_then(|state| {
+        let (date, days) = state.trim().split_once(' ')?;
+        Some((
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+            days.parse::<u32>().ok()?,
+        ))
+    });
+
+    let days = match last {
+        Some((date, days)) if date == today => days,
+        Some((date, days)) if date.succ_opt() == Some(today) => days + 1,
+        // First run, or the streak was broken
+        _ => 1,
+    };
+
+    fs::create_dir_all(data_dir())?;
+    fs::write(&path, format!("{} {}\n", today.format("%Y-%m-%d"), days))?;
+    Ok(days)
+}
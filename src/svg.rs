@@ -0,0 +1,404 @@
+use crate::challenge::ChallengeStatus;
+use crate::color::to_hex;
+use crate::config::Config;
+use crate::fetch::Fetch;
+use crate::logo::logo_image_data;
+use crate::{colorbar_cells, progress_color, ProgressColorScheme};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Local;
+use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+use std::path::Path;
+
+// Bundled so rendered images look the same regardless of installed fonts
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+const FONT_FAMILY: &str = "DejaVu Sans Mono";
+const FONT_SIZE: f32 = 16.0;
+
+// One terminal cell in pixels; the width is the font's advance at FONT_SIZE
+const CELL_WIDTH: f32 = 9.633;
+const CELL_HEIGHT: f32 = 22.0;
+
+// Margin around the content, in cells
+const PADDING: f32 = 2.0;
+
+const BACKGROUND: &str = "#1e1e2e";
+const FOREGROUND: &str = "#e5e5e5";
+
+/// SVG elements positioned on a grid of terminal-sized cells
+struct Canvas {
+    elements: Vec<String>,
+    min_col: f32,
+    max_col: f32,
+    rows: f32,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Self {
+            elements: Vec::new(),
+            min_col: 0.0,
+            max_col: 0.0,
+            rows: 0.0,
+        }
+    }
+
+    fn cover(&mut self, col: f32, width: f32, row: f32) {
+        self.min_col = self.min_col.min(col);
+        self.max_col = self.max_col.max(col + width);
+        self.rows = self.rows.max(row + 1.0);
+    }
+
+    fn text(&mut self, col: f32, row: f32, text: &str, style: ContentStyle) {
+        let fill = style
+            .foreground_color
+            .map(to_hex)
+            .unwrap_or_else(|| FOREGROUND.to_string());
+        let weight = if style.attributes.has(Attribute::Bold) {
+            " font-weight=\"bold\""
+        } else {
+            ""
+        };
+
+        self.elements.push(format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\"{}>{}</text>",
+            col * CELL_WIDTH,
+            (row + 0.75) * CELL_HEIGHT,
+            fill,
+            weight,
+            escape(text)
+        ));
+        self.cover(col, text.chars().count() as f32, row);
+    }
+
+    fn bar(&mut self, col: f32, row: f32, cells: f32, percentage: i32, color: Color) {
+        let x = col * CELL_WIDTH;
+        let y = (row + 0.4) * CELL_HEIGHT;
+        let width = cells * CELL_WIDTH;
+        let filled = width * percentage.clamp(0, 100) as f32 / 100.0;
+
+        self.elements.push(format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"4\" rx=\"2\" fill=\"{}\"/>",
+            x,
+            y,
+            width,
+            to_hex(Color::DarkGrey)
+        ));
+        self.elements.push(format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"4\" rx=\"2\" fill=\"{}\"/>",
+            x,
+            y,
+            filled,
+            to_hex(color)
+        ));
+        self.cover(col, cells, row);
+    }
+
+    fn image(&mut self, col: f32, row: f32, cols: f32, rows: f32, data: &[u8], mime: &str) {
+        self.elements.push(format!(
+            "<image x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" href=\"data:{};base64,{}\"/>",
+            col * CELL_WIDTH,
+            row * CELL_HEIGHT,
+            cols * CELL_WIDTH,
+            rows * CELL_HEIGHT,
+            mime,
+            STANDARD.encode(data)
+        ));
+        self.cover(col, cols, row + rows - 1.0);
+    }
+
+    fn finish(self, boxed: bool) -> String {
+        let content_width = (self.max_col - self.min_col) * CELL_WIDTH;
+        let content_height = self.rows * CELL_HEIGHT;
+        let margin_x = PADDING * CELL_WIDTH;
+        let margin_y = PADDING * CELL_HEIGHT;
+        let width = content_width + 2.0 * margin_x;
+        let height = content_height + 2.0 * margin_y;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\">\n",
+            width, height, width, height
+        );
+        svg.push_str(&format!(
+            "<rect width=\"100%\" height=\"100%\" rx=\"12\" fill=\"{}\"/>\n",
+            BACKGROUND
+        ));
+
+        if boxed {
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"8\" fill=\"none\" stroke=\"{}\"/>\n",
+                margin_x / 2.0,
+                margin_y / 2.0,
+                width - margin_x,
+                height - margin_y,
+                FOREGROUND
+            ));
+        }
+
+        svg.push_str(&format!(
+            "<g transform=\"translate({:.1} {:.1})\" font-family=\"{}, monospace\" font-size=\"{}\" xml:space=\"preserve\">\n",
+            margin_x - self.min_col * CELL_WIDTH,
+            margin_y,
+            FONT_FAMILY,
+            FONT_SIZE
+        ));
+        for element in self.elements {
+            svg.push_str(&element);
+            svg.push('\n');
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// Lay the fetch out like the terminal view and describe it as an SVG document
+pub fn render_svg(fetch: &Fetch, challenge: Option<&ChallengeStatus>, config: &Config) -> String {
+    let mut canvas = Canvas::new();
+
+    // Age moves into the challenge block when it is shown
+    let info_items = fetch
+        .info
+        .to_info_items(challenge.is_none(), &config.display);
+    let label_width = info_items
+        .iter()
+        .map(|(label, _)| config.info.label_name(label).chars().count())
+        .max()
+        .unwrap_or(0) as f32;
+    let value_width = info_items
+        .iter()
+        .map(|(_, value)| value.chars().count())
+        .max()
+        .unwrap_or(0) as f32;
+
+    // Everything is centered on the separator column, like the terminal layout
+    let center = label_width + 1.0;
+    let mut row = 0.0;
+
+    if let Some((data, mime)) = logo_image_data(&fetch.logo_subject(&config.logo), &config.logo) {
+        canvas.image(center - 10.0, row, 20.0, 10.0, &data, mime);
+        row += 11.0;
+    }
+
+    let cells = colorbar_cells(&config.colorbar);
+    if !cells.is_empty() {
+        let mut col = center - (cells.len() / 2) as f32;
+        for (block, color) in cells {
+            canvas.text(col, row, block, ContentStyle::new().with(color));
+            col += 1.0;
+        }
+        row += 2.0;
+    }
+
+    if let Some(parts) = config.greeting.parts(&fetch.user, &fetch.hostname) {
+        let greeting_width: usize = parts.iter().map(|(part, _)| part.chars().count()).sum();
+        let mut col = center - (greeting_width / 2) as f32;
+        for (part, is_value) in &parts {
+            let style = if *is_value {
+                ContentStyle::new().cyan().bold()
+            } else {
+                ContentStyle::new().green()
+            };
+            canvas.text(col, row, part, style);
+            col += part.chars().count() as f32;
+        }
+        row += 1.0;
+    }
+
+    let uptime_width = 3 + fetch.uptime.chars().count();
+    let uptime_col = center - (uptime_width / 2) as f32;
+    canvas.text(uptime_col, row, "up", ContentStyle::new().yellow());
+    canvas.text(
+        uptime_col + 3.0,
+        row,
+        &fetch.uptime,
+        ContentStyle::new().cyan().bold(),
+    );
+    row += 2.0;
+
+    let info_row = row;
+    for (label, value) in &info_items {
+        let style = |color: Option<Color>| {
+            color.map_or(ContentStyle::new(), |color| ContentStyle::new().with(color))
+        };
+        info_line(
+            &mut canvas,
+            center,
+            row,
+            (
+                config.info.label_name(label),
+                style(config.info.label_color(label)),
+            ),
+            value,
+            style(config.info.value_color(label)),
+        );
+        row += 1.0;
+    }
+    row += 1.0;
+
+    for (label, value) in fetch.usage_items() {
+        let col = center - 11.0;
+        canvas.text(col, row, label, ContentStyle::new().green());
+        canvas.text(
+            col + 5.0,
+            row,
+            &format!("{:>2}%", value),
+            ContentStyle::new(),
+        );
+        canvas.bar(
+            col + 9.0,
+            row,
+            14.0,
+            value,
+            progress_color(value, ProgressColorScheme::System, &config.bars),
+        );
+        row += 1.0;
+    }
+
+    if let Some(status) = challenge {
+        let entries = status.entries(&config.challenge);
+        let challenge_label_width = entries
+            .iter()
+            .map(|(label, _, _)| label.chars().count())
+            .max()
+            .unwrap_or(0) as f32;
+        let challenge_center = center + 2.0 + value_width + 4.0 + challenge_label_width + 1.0;
+
+        let mut row = info_row;
+        for (label, value, style) in &entries {
+            info_line(
+                &mut canvas,
+                challenge_center,
+                row,
+                (label, ContentStyle::new()),
+                value,
+                *style,
+            );
+            row += 1.0;
+        }
+
+        let percentage = status.progress_percentage;
+        canvas.text(
+            challenge_center - 8.0,
+            row,
+            &format!("{:>3}%", percentage),
+            ContentStyle::new(),
+        );
+        canvas.bar(
+            challenge_center - 3.0,
+            row,
+            14.0,
+            percentage,
+            progress_color(percentage, ProgressColorScheme::Challenge, &config.bars),
+        );
+    }
+
+    canvas.finish(challenge.is_some())
+}
+
+/// A compact card with the challenge progress, sized for sharing
+pub fn render_card(fetch: &Fetch, status: &ChallengeStatus, config: &Config) -> String {
+    let mut canvas = Canvas::new();
+
+    // Text sits to the right of the logo when there is one
+    let col = match logo_image_data(&fetch.logo_subject(&config.logo), &config.logo) {
+        Some((data, mime)) => {
+            canvas.image(0.0, 0.0, 12.0, 6.0, &data, mime);
+            14.0
+        }
+        None => 0.0,
+    };
+
+    canvas.text(col, 0.0, &fetch.distro(), ContentStyle::new().cyan().bold());
+    canvas.text(
+        col,
+        1.5,
+        &format!("{} days survived", status.days_old),
+        ContentStyle::new().bold(),
+    );
+
+    let percentage = status.progress_percentage;
+    canvas.bar(
+        col,
+        2.5,
+        24.0,
+        percentage,
+        progress_color(percentage, ProgressColorScheme::Challenge, &config.bars),
+    );
+    canvas.text(
+        col + 25.0,
+        2.5,
+        &format!("{}%", percentage),
+        ContentStyle::new(),
+    );
+
+    canvas.text(
+        col,
+        4.0,
+        &format!(
+            "Target {}",
+            status.target_dt.with_timezone(&Local).format("%Y-%m-%d")
+        ),
+        ContentStyle::new().yellow(),
+    );
+    canvas.text(
+        col,
+        5.0,
+        "huginn install challenge",
+        ContentStyle::new().dark_grey(),
+    );
+
+    canvas.finish(false)
+}
+
+/// Right-aligned label, separator dot and value, centered on the separator
+fn info_line(
+    canvas: &mut Canvas,
+    center: f32,
+    row: f32,
+    (label, label_style): (&str, ContentStyle),
+    value: &str,
+    style: ContentStyle,
+) {
+    canvas.text(
+        center - 1.0 - label.chars().count() as f32,
+        row,
+        label,
+        label_style,
+    );
+    canvas.text(center, row, "•", ContentStyle::new().green());
+    canvas.text(center + 2.0, row, value, style);
+}
+
+/// Rasterize an SVG document produced by `render_svg` into a PNG file
+pub fn save_png(svg: &str, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_font_data(FONT.to_vec());
+    options.font_family = FONT_FAMILY.to_string();
+
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    // Render at twice the size so text stays crisp on high-dpi screens
+    let scale = 2.0;
+    let size = tree.size();
+    let mut pixmap = tiny_skia::Pixmap::new(
+        (size.width() * scale).ceil() as u32,
+        (size.height() * scale).ceil() as u32,
+    )
+    .ok_or("image dimensions are invalid")?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    pixmap.save_png(path)?;
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,12 +1,55 @@
-use crate::config::DisplayConfig;
+use crate::backup;
+use crate::bluetooth;
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+use crate::bsd::get_init;
+use crate::config::{
+    BackupConfig, CollectorsConfig, Config, DisplayConfig, PerformanceConfig, INFO_FIELDS,
+};
+use crate::dns;
+use crate::dotfiles;
+use crate::error::CollectError;
+use crate::home_size;
+use crate::i18n::tr;
+use crate::latency;
+#[cfg(target_os = "macos")]
+use crate::macos::{
+    get_gpu, get_init, get_model, get_package_counts, get_sip, get_theme, get_window_manager,
+};
+use crate::plugins::{self, PluginField};
+use crate::power;
+use crate::sbc;
+use crate::sessions;
+use crate::taint;
+use crate::temps;
+use crate::updates;
+#[cfg(windows)]
+use crate::windows::{
+    get_gpu, get_init, get_model, get_os_name, get_package_counts, get_shell, get_system_age,
+    get_terminal, get_theme, get_window_manager,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::fs;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
+use std::time::{Duration, Instant};
 use sysinfo::System;
+use tracing::debug;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub distro: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
     pub age: Option<String>,
     pub kernel: Option<String>,
     pub packages: Option<String>,
@@ -17,12 +60,64 @@ pub struct SystemInfo {
     pub gpu: Option<String>,
     pub theme: Option<String>,
     pub nix: Option<String>,
+    #[serde(default)]
+    pub guix: Option<String>,
+    #[serde(default)]
+    pub sip: Option<String>,
+    #[serde(default)]
+    pub init: Option<String>,
+    #[serde(default)]
+    pub home: Option<String>,
+    #[serde(default)]
+    pub dotfiles: Option<String>,
+    #[serde(default)]
+    pub backup: Option<String>,
+    #[serde(default)]
+    pub latency: Option<String>,
+    #[serde(default)]
+    pub dns: Option<String>,
+    #[serde(default)]
+    pub bluetooth: Option<String>,
+    #[serde(default)]
+    pub ssh: Option<String>,
+    #[serde(default)]
+    pub users: Option<String>,
+    #[serde(default)]
+    pub booted: Option<String>,
+    #[serde(default)]
+    pub taint: Option<String>,
+    #[serde(default)]
+    pub security: Option<String>,
+    #[serde(default)]
+    pub power: Option<String>,
+    #[serde(default)]
+    pub gpu_temp: Option<String>,
+    #[serde(default)]
+    pub temps: Option<String>,
+    #[serde(default)]
+    pub soc: Option<String>,
+    /// Model of a single-board computer, which can pick the logo
+    #[serde(default)]
+    pub board: Option<String>,
+    /// Consecutive days huginn has run, filled in per invocation
+    #[serde(default)]
+    pub streak: Option<String>,
+    #[serde(default)]
+    pub plugins: Vec<PluginField>,
+    /// Collectors that came back empty, and why
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failures: BTreeMap<String, String>,
+    /// Fields whose value needs attention, like a stale backup, and the color
+    /// to paint it in
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alerts: BTreeMap<String, String>,
 }
 
 impl SystemInfo {
     pub fn new() -> Self {
         Self {
             distro: None,
+            model: None,
             age: None,
             kernel: None,
             packages: None,
@@ -33,37 +128,364 @@ impl SystemInfo {
             gpu: None,
             theme: None,
             nix: None,
+            guix: None,
+            sip: None,
+            init: None,
+            home: None,
+            dotfiles: None,
+            backup: None,
+            latency: None,
+            dns: None,
+            bluetooth: None,
+            ssh: None,
+            users: None,
+            booted: None,
+            taint: None,
+            security: None,
+            power: None,
+            gpu_temp: None,
+            temps: None,
+            soc: None,
+            board: None,
+            streak: None,
+            plugins: Vec::new(),
+            failures: BTreeMap::new(),
+            alerts: BTreeMap::new(),
         }
     }
 
-    pub fn collect_all(&mut self, display_config: &DisplayConfig) {
-        let pkg_handle = thread::spawn(|| get_package_count());
-        let gpu_handle = thread::spawn(|| get_gpu());
-        let theme_handle = thread::spawn(|| get_theme());
-        let term_handle = thread::spawn(|| get_terminal());
-        let nix_handle = thread::spawn(|| get_nix_generation());
+    /// Run the enabled collectors in parallel, each bounded by its configured timeout
+    /// A collector that fails or panics leaves its fallback value instead, one
+    /// that times out the configured placeholder, and either the reason in `failures`
+    /// Returns how long each collector took
+    pub fn collect_all(
+        &mut self,
+        display_config: &DisplayConfig,
+        collectors: &CollectorsConfig,
+        backup_config: &BackupConfig,
+        performance: &PerformanceConfig,
+        sys: &System,
+    ) -> Vec<Timing> {
+        let start = Instant::now();
+        let mut pool = Pool::default();
 
-        self.distro = Some(get_os_name());
+        // Distro is always collected since it also picks the logo
+        let distro = pool.spawn("distro", get_os_name);
 
-        // Use custom install date if provided, otherwise use filesystem
-        self.age = if let Some(ref custom_date) = display_config.custom_install_date {
-            calculate_days_from_date(custom_date)
-                .ok()
-                .map(|days| format!("{} days", days))
-                .or_else(|| Some(get_system_age()))
+        let custom_date = display_config.custom_install_date.clone();
+        let age = display_config.age.then(|| {
+            pool.spawn("age", move || {
+                // Use custom install date if provided, otherwise use filesystem
+                Ok(custom_date
+                    .and_then(|date| calculate_days_from_date(&date).ok())
+                    .map(|days| format!("{} {}", days, tr("days")))
+                    .unwrap_or_else(get_system_age))
+            })
+        });
+        let model = display_config.model.then(|| pool.spawn("model", get_model));
+        let kernel = display_config.kernel.then(|| {
+            pool.spawn("kernel", || {
+                System::kernel_version()
+                    .ok_or_else(|| CollectError::Failed("no kernel version reported".into()))
+            })
+        });
+        let packages_style = display_config.packages_style.clone();
+        let packages = display_config
+            .packages
+            .then(|| pool.spawn("packages", move || get_packages(&packages_style)));
+        let shell = display_config.shell.then(|| pool.spawn("shell", get_shell));
+        let term = display_config
+            .term
+            .then(|| pool.spawn("term", get_terminal));
+        let wm = display_config
+            .wm
+            .then(|| pool.spawn("wm", get_window_manager));
+        let gpu = display_config.gpu.then(|| pool.spawn("gpu", get_gpu));
+        let theme = display_config.theme.then(|| pool.spawn("theme", get_theme));
+        let nix = display_config
+            .nix
+            .then(|| pool.spawn("nix", get_nix_generation));
+        let guix = display_config
+            .guix
+            .then(|| pool.spawn("guix", || Ok(get_guix_generation())));
+        let sip = display_config
+            .sip
+            .then(|| pool.spawn("sip", || Ok(get_sip())));
+        let init = display_config
+            .init
+            .then(|| pool.spawn("init", || Ok(get_init())));
+        let home_ttl = Duration::from_millis(collectors.home_ttl.0);
+        let home = display_config
+            .home
+            .then(|| pool.spawn("home", move || Ok(home_size::get(home_ttl))));
+        let dotfiles_repo = crate::expand_home(&display_config.dotfiles_repo);
+        let dotfiles = display_config
+            .dotfiles
+            .then(|| pool.spawn("dotfiles", move || dotfiles::status(&dotfiles_repo)));
+        let backup_config = backup_config.clone();
+        let backup = display_config
+            .backup
+            .then(|| pool.spawn("backup", move || backup::status(&backup_config)));
+        let latency_host = display_config.latency_host.clone();
+        let latency = display_config
+            .latency
+            .then(|| pool.spawn("latency", move || latency::measure(&latency_host)));
+        let dns = display_config.dns.then(|| pool.spawn("dns", dns::servers));
+        let bluetooth = display_config
+            .bluetooth
+            .then(|| pool.spawn("bluetooth", bluetooth::connected));
+        let ssh = display_config.ssh.then(|| pool.spawn("ssh", sessions::ssh));
+        let users = display_config
+            .users
+            .then(|| pool.spawn("users", sessions::users));
+        let boot_time_format = display_config.boot_time_format.clone();
+        let booted = display_config
+            .booted
+            .then(|| pool.spawn("booted", move || get_boot_time(&boot_time_format)));
+        let taint = display_config
+            .taint
+            .then(|| pool.spawn("taint", taint::status));
+        let security = display_config
+            .security
+            .then(|| pool.spawn("security", updates::security));
+        let power_thresholds = display_config.power_thresholds.clone();
+        let power = display_config
+            .power
+            .then(|| pool.spawn("power", move || power::status(&power_thresholds)));
+        let gpu_temp = display_config
+            .gpu_temp
+            .then(|| pool.spawn("gpu_temp", temps::gpu));
+        let temps = display_config
+            .temps
+            .then(|| pool.spawn("temps", temps::summary));
+        let soc = display_config.soc.then(|| pool.spawn("soc", sbc::status));
+        let plugin_runs: Vec<_> = if display_config.plugins {
+            plugins::discover()
+                .into_iter()
+                .map(|path| {
+                    let name = plugins::name(&path);
+                    let timeout = Duration::from_millis(collectors.timeout_for(&name));
+                    pool.spawn(&name, move || plugins::run_plugin(&path, timeout))
+                })
+                .collect()
         } else {
-            Some(get_system_age())
+            Vec::new()
         };
 
-        self.kernel = System::kernel_version();
-        self.packages = Some(pkg_handle.join().unwrap());
-        self.shell = Some(get_shell());
-        self.term = Some(term_handle.join().unwrap());
-        self.wm = Some(get_window_manager());
-        self.cpu = get_cpu_model();
-        self.gpu = gpu_handle.join().unwrap();
-        self.theme = theme_handle.join().unwrap();
-        self.nix = nix_handle.join().unwrap();
+        let queued: Vec<String> = pool.jobs.iter().map(|(name, _)| name.clone()).collect();
+        let finished = pool.start(performance.worker_count(), &performance.priority);
+
+        let unknown = || "Unknown".to_string();
+        let mut failures = BTreeMap::new();
+        let f = &mut failures;
+        self.distro = Some(distro.wait(start, collectors, f).unwrap_or_else(unknown));
+        self.model = model.and_then(|c| c.wait(start, collectors, f));
+        self.age = age.and_then(|c| c.wait(start, collectors, f));
+        self.kernel = kernel.and_then(|c| c.wait(start, collectors, f));
+        self.packages = packages.and_then(|c| c.wait(start, collectors, f));
+        self.shell = shell.map(|c| c.wait(start, collectors, f).unwrap_or_else(unknown));
+        self.term = term.map(|c| c.wait(start, collectors, f).unwrap_or_else(unknown));
+        self.wm = wm.map(|c| c.wait(start, collectors, f).unwrap_or_else(unknown));
+        // Already known from the shared System, no thread needed
+        let cpu_start = Instant::now();
+        self.cpu = display_config.cpu.then(|| get_cpu_model(sys)).flatten();
+        let cpu_elapsed = cpu_start.elapsed();
+        self.gpu = gpu.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.theme = theme.and_then(|c| c.wait(start, collectors, f));
+        self.nix = nix.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.guix = guix.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.sip = sip.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.init = init.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.home = home.and_then(|c| c.wait(start, collectors, f)).flatten();
+        self.dotfiles = dotfiles.and_then(|c| c.wait(start, collectors, f));
+        self.backup = self.flag(
+            "backup",
+            backup.and_then(|c| c.wait(start, collectors, f)),
+            "red",
+        );
+        self.latency = latency.and_then(|c| c.wait(start, collectors, f));
+        self.dns = dns.and_then(|c| c.wait(start, collectors, f));
+        self.bluetooth = bluetooth
+            .and_then(|c| c.wait(start, collectors, f))
+            .flatten();
+        self.ssh = ssh.and_then(|c| c.wait(start, collectors, f));
+        self.users = users.and_then(|c| c.wait(start, collectors, f));
+        self.booted = booted.and_then(|c| c.wait(start, collectors, f));
+        self.taint = self.flag(
+            "taint",
+            taint.and_then(|c| c.wait(start, collectors, f)),
+            "yellow",
+        );
+        self.security = self.flag(
+            "security",
+            security.and_then(|c| c.wait(start, collectors, f)),
+            "red",
+        );
+        if let Some((watts, color)) = power.and_then(|c| c.wait(start, collectors, f)) {
+            self.power = Some(watts);
+            if let Some(color) = color {
+                self.alerts.insert("power".to_string(), color);
+            }
+        }
+        self.gpu_temp = gpu_temp.and_then(|c| c.wait(start, collectors, f));
+        self.temps = temps.and_then(|c| c.wait(start, collectors, f));
+        if let Some((value, color)) = soc.and_then(|c| c.wait(start, collectors, f)) {
+            self.soc = Some(value);
+            if let Some(color) = color {
+                self.alerts.insert("soc".to_string(), color);
+            }
+        }
+        // Read whether or not the model is shown, for the logo
+        self.board = sbc::board();
+        self.plugins = plugin_runs
+            .into_iter()
+            .filter_map(|c| c.wait(start, collectors, f))
+            .flatten()
+            .collect();
+        if !collectors.placeholder.is_empty() {
+            for (name, error) in &failures {
+                if matches!(error, CollectError::Timeout(_)) {
+                    self.set(name, Some(collectors.placeholder.clone()));
+                }
+            }
+        }
+
+        let finished = finished.lock().map(|f| f.clone()).unwrap_or_default();
+        let mut timings: Vec<Timing> = queued
+            .into_iter()
+            .map(|name| {
+                // Those still running when they timed out took at least that long
+                let elapsed = finished
+                    .get(&name)
+                    .copied()
+                    .unwrap_or_else(|| Duration::from_millis(collectors.timeout_for(&name)));
+                let failure = failures.get(&name).map(|e| e.to_string());
+                Timing {
+                    name,
+                    elapsed,
+                    failure,
+                }
+            })
+            .collect();
+        if display_config.cpu {
+            timings.push(Timing {
+                name: "cpu".to_string(),
+                elapsed: cpu_elapsed,
+                failure: None,
+            });
+        }
+
+        self.failures = failures
+            .into_iter()
+            .map(|(name, error)| (name, error.to_string()))
+            .collect();
+        timings
+    }
+
+    /// The value from a collector that also says whether it needs attention,
+    /// noting it in `alerts` if so
+    fn flag(&mut self, field: &str, result: Option<(String, bool)>, color: &str) -> Option<String> {
+        let (value, alert) = result?;
+        if alert {
+            self.alerts.insert(field.to_string(), color.to_string());
+        }
+        Some(value)
+    }
+
+    /// Every collected field by name, regardless of display settings
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("distro", &self.distro),
+            ("model", &self.model),
+            ("age", &self.age),
+            ("kernel", &self.kernel),
+            ("packages", &self.packages),
+            ("shell", &self.shell),
+            ("term", &self.term),
+            ("wm", &self.wm),
+            ("cpu", &self.cpu),
+            ("gpu", &self.gpu),
+            ("theme", &self.theme),
+            ("nix", &self.nix),
+            ("guix", &self.guix),
+            ("sip", &self.sip),
+            ("init", &self.init),
+            ("home", &self.home),
+            ("dotfiles", &self.dotfiles),
+            ("backup", &self.backup),
+            ("latency", &self.latency),
+            ("dns", &self.dns),
+            ("bluetooth", &self.bluetooth),
+            ("ssh", &self.ssh),
+            ("users", &self.users),
+            ("booted", &self.booted),
+            ("taint", &self.taint),
+            ("security", &self.security),
+            ("power", &self.power),
+            ("gpu_temp", &self.gpu_temp),
+            ("temps", &self.temps),
+            ("soc", &self.soc),
+            ("streak", &self.streak),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_deref().map(|v| (name, v)))
+        .collect()
+    }
+
+    /// Set a field by name, built-in or from a plugin; None removes it
+    pub fn set(&mut self, name: &str, value: Option<String>) {
+        let field = match name {
+            "distro" => &mut self.distro,
+            "model" => &mut self.model,
+            "age" => &mut self.age,
+            "kernel" => &mut self.kernel,
+            "packages" => &mut self.packages,
+            "shell" => &mut self.shell,
+            "term" => &mut self.term,
+            "wm" => &mut self.wm,
+            "cpu" => &mut self.cpu,
+            "gpu" => &mut self.gpu,
+            "theme" => &mut self.theme,
+            "nix" => &mut self.nix,
+            "guix" => &mut self.guix,
+            "sip" => &mut self.sip,
+            "init" => &mut self.init,
+            "home" => &mut self.home,
+            "dotfiles" => &mut self.dotfiles,
+            "backup" => &mut self.backup,
+            "latency" => &mut self.latency,
+            "dns" => &mut self.dns,
+            "bluetooth" => &mut self.bluetooth,
+            "ssh" => &mut self.ssh,
+            "users" => &mut self.users,
+            "booted" => &mut self.booted,
+            "taint" => &mut self.taint,
+            "security" => &mut self.security,
+            "power" => &mut self.power,
+            "gpu_temp" => &mut self.gpu_temp,
+            "temps" => &mut self.temps,
+            "soc" => &mut self.soc,
+            "streak" => &mut self.streak,
+            _ => {
+                let index = self.plugins.iter().position(|field| field.label == name);
+                match (index, value) {
+                    (Some(index), Some(value)) => self.plugins[index].value = value,
+                    (Some(index), None) => {
+                        self.plugins.remove(index);
+                    }
+                    (None, Some(value)) => self.plugins.push(PluginField {
+                        label: name.to_string(),
+                        value,
+                        order: None,
+                        color: None,
+                        bar: None,
+                    }),
+                    (None, None) => {}
+                }
+                return;
+            }
+        };
+        *field = value;
     }
 
     // Helper to convert to vec of tuples for display
@@ -77,11 +499,8 @@ impl SystemInfo {
 
         // Helper to truncate long strings
         fn truncate(s: &str, max_len: usize) -> String {
-            if s.len() > max_len {
-                s[..max_len].to_string()
-            } else {
-                s.to_string()
-            }
+            // Cut at a character, as slicing inside one would panic
+            s.chars().take(max_len).collect()
         }
 
         // Macro to conditionally add fields based on config
@@ -97,6 +516,7 @@ impl SystemInfo {
 
         // Add all fields using the macro
         add_if_enabled!(self.distro, "distro", display_config.distro, 50);
+        add_if_enabled!(self.model, "model", display_config.model, 50);
 
         // Age is special - only include if requested
         if include_age {
@@ -112,22 +532,225 @@ impl SystemInfo {
         add_if_enabled!(self.gpu, "gpu", display_config.gpu, 55);
         add_if_enabled!(self.theme, "theme", display_config.theme, 50);
         add_if_enabled!(self.nix, "nix", display_config.nix, 50);
+        add_if_enabled!(self.guix, "guix", display_config.guix, 50);
+        add_if_enabled!(self.sip, "sip", display_config.sip, 50);
+        add_if_enabled!(self.init, "init", display_config.init, 50);
+        add_if_enabled!(self.home, "home", display_config.home, 50);
+        add_if_enabled!(self.dotfiles, "dotfiles", display_config.dotfiles, 50);
+        add_if_enabled!(self.backup, "backup", display_config.backup, 50);
+        add_if_enabled!(self.latency, "latency", display_config.latency, 50);
+        add_if_enabled!(self.dns, "dns", display_config.dns, 50);
+        add_if_enabled!(self.bluetooth, "bluetooth", display_config.bluetooth, 50);
+        add_if_enabled!(self.ssh, "ssh", display_config.ssh, 50);
+        add_if_enabled!(self.users, "users", display_config.users, 50);
+        add_if_enabled!(self.booted, "booted", display_config.booted, 50);
+        add_if_enabled!(self.taint, "taint", display_config.taint, 50);
+        add_if_enabled!(self.security, "security", display_config.security, 50);
+        add_if_enabled!(self.power, "power", display_config.power, 50);
+        add_if_enabled!(self.gpu_temp, "gpu_temp", display_config.gpu_temp, 50);
+        add_if_enabled!(self.temps, "temps", display_config.temps, 50);
+        add_if_enabled!(self.soc, "soc", display_config.soc, 50);
+        add_if_enabled!(self.streak, "streak", display_config.streak, 50);
+
+        // Plugin fields go where they ask to, lowest order first
+        if display_config.plugins {
+            // Fields with a bar are drawn with the countdowns instead
+            let mut placed: Vec<&PluginField> = self
+                .plugins
+                .iter()
+                .filter(|field| field.bar.is_none())
+                .collect();
+            placed.sort_by_key(|field| field.order.unwrap_or(usize::MAX));
+            for field in placed {
+                let at = field.order.unwrap_or(items.len()).min(items.len());
+                items.insert(at, (field.label.as_str(), truncate(&field.value, 50)));
+            }
+        }
 
         items
     }
 }
 
+type Job = (String, Box<dyn FnOnce() + Send>);
+
+/// How long each collector that finished took, by name
+type Finished = Arc<Mutex<BTreeMap<String, Duration>>>;
+
+/// Collectors queued for a fixed number of worker threads, so slow machines
+/// aren't hit with every subprocess at once
+#[derive(Default)]
+struct Pool {
+    jobs: Vec<Job>,
+    finished: Finished,
+}
+
+impl Pool {
+    /// Queue a collector; it runs once the pool is started
+    fn spawn<T: Send + 'static>(
+        &mut self,
+        name: &str,
+        collect: impl FnOnce() -> Result<T, CollectError> + Send + 'static,
+    ) -> Collector<T> {
+        let (tx, rx) = mpsc::channel();
+        let started = Arc::new(OnceLock::new());
+        let thread_name = name.to_string();
+        let job_started = Arc::clone(&started);
+        let finished = Arc::clone(&self.finished);
+        let job = move || {
+            let started = *job_started.get_or_init(Instant::now);
+            let result = panic::catch_unwind(AssertUnwindSafe(collect));
+            let elapsed = started.elapsed();
+            if let Ok(mut finished) = finished.lock() {
+                finished.insert(thread_name.clone(), elapsed);
+            }
+            // A panic drops the sender, which `wait` reports
+            let Ok(result) = result else {
+                return;
+            };
+            debug!(
+                "{} collector took {:.1}ms",
+                thread_name,
+                elapsed.as_secs_f64() * 1000.0
+            );
+            // The receiver is gone if the collector already timed out
+            let _ = tx.send(result);
+        };
+        self.jobs.push((name.to_string(), Box::new(job)));
+        Collector {
+            name: name.to_string(),
+            rx,
+            started,
+        }
+    }
+
+    /// Run the queued collectors on `threads` workers, those named in
+    /// `priority` first and the rest in the order they were queued, returning
+    /// how long each took as they finish
+    fn start(mut self, threads: usize, priority: &[String]) -> Finished {
+        let rank = |name: &str| priority.iter().position(|p| p == name);
+        self.jobs
+            .sort_by_key(|(name, _)| rank(name).unwrap_or(usize::MAX));
+        let threads = threads.clamp(1, self.jobs.len().max(1));
+        let queue = Arc::new(Mutex::new(VecDeque::from(self.jobs)));
+        for _ in 0..threads {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || loop {
+                let job = queue.lock().ok().and_then(|mut jobs| jobs.pop_front());
+                let Some((_, job)) = job else {
+                    return;
+                };
+                job();
+            });
+        }
+        self.finished
+    }
+}
+
+/// A collector queued in a `Pool`
+struct Collector<T> {
+    name: String,
+    rx: Receiver<Result<T, CollectError>>,
+    /// When a worker picked it up
+    started: Arc<OnceLock<Instant>>,
+}
+
+impl<T: Send + 'static> Collector<T> {
+    /// The collected value, or None with the reason recorded in `failures`
+    /// The timeout counts from when a worker picked the collector up, or
+    /// from `start` while it is still queued
+    fn wait(
+        self,
+        start: Instant,
+        collectors: &CollectorsConfig,
+        failures: &mut BTreeMap<String, CollectError>,
+    ) -> Option<T> {
+        let timeout = collectors.timeout_for(&self.name);
+        let deadline = || *self.started.get().unwrap_or(&start) + Duration::from_millis(timeout);
+        let result = loop {
+            match self
+                .rx
+                .recv_timeout(deadline().saturating_duration_since(Instant::now()))
+            {
+                Ok(result) => break result,
+                // Picked up while waiting, so it has time left
+                Err(RecvTimeoutError::Timeout) if deadline() > Instant::now() => continue,
+                Err(RecvTimeoutError::Timeout) => break Err(CollectError::Timeout(timeout)),
+                Err(RecvTimeoutError::Disconnected) => break Err(CollectError::Panicked),
+            }
+        };
+        result
+            .map_err(|e| {
+                debug!("{} collector failed: {}", self.name, e);
+                failures.insert(self.name, e)
+            })
+            .ok()
+    }
+}
+
+/// How long one collector took, and why it failed if it did
+pub struct Timing {
+    pub name: String,
+    pub elapsed: Duration,
+    pub failure: Option<String>,
+}
+
+/// Run every collector one at a time, regardless of display settings, and
+/// time each one, slowest first
+/// Each pass is a fetch with only one field shown, so the collectors are
+/// the ones `collect_all` runs; distro, which every fetch collects, is timed
+/// in the first
+pub fn benchmark(config: &Config, sys: &System) -> Vec<Timing> {
+    let mut timings: Vec<Timing> = Vec::new();
+    let passes = INFO_FIELDS.iter().map(|field| (Some(*field), false));
+    for (field, plugins) in passes.chain([(None, true)]) {
+        let mut display = config.display.clone();
+        let shown: Vec<String> = field.map(str::to_string).into_iter().collect();
+        if display.show_only(&shown).is_err() {
+            continue;
+        }
+        display.plugins = plugins;
+        let pass = SystemInfo::new().collect_all(
+            &display,
+            &config.collectors,
+            &config.backup,
+            &config.performance,
+            sys,
+        );
+        for timing in pass {
+            if !timings.iter().any(|seen| seen.name == timing.name) {
+                timings.push(timing);
+            }
+        }
+    }
+
+    timings.sort_by_key(|timing| std::cmp::Reverse(timing.elapsed));
+    timings
+}
+
 // Helper functions
 
-fn get_os_name() -> String {
+#[cfg(not(windows))]
+fn get_os_name() -> Result<String, CollectError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return Ok(crate::termux::os_name());
+    }
+
     use libmacchina::{traits::GeneralReadout as _, GeneralReadout};
     let general = GeneralReadout::new();
     general
         .distribution()
-        .unwrap_or_else(|_| general.os_name().unwrap_or_else(|_| "Unknown".to_string()))
+        .or_else(|_| general.os_name())
+        .map_err(|e| CollectError::Failed(e.to_string()))
 }
 
+#[cfg(not(windows))]
 fn get_system_age() -> String {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return crate::termux::age();
+    }
+
     use std::path::Path;
 
     // Find age for atomic systems
@@ -146,7 +769,22 @@ fn get_system_age() -> String {
     let duration = now.duration_since(install_time).unwrap_or_default();
     let days = duration.as_secs() / 86400;
 
-    format!("{} days", days)
+    format!("{} {}", days, tr("days"))
+}
+
+/// When the system booted, in a strftime `format`
+fn get_boot_time(format: &str) -> Result<String, CollectError> {
+    use chrono::{Local, TimeZone};
+    use std::fmt::Write;
+
+    let booted = Local
+        .timestamp_opt(System::boot_time() as i64, 0)
+        .single()
+        .ok_or_else(|| CollectError::Failed("no boot time reported".into()))?;
+    let mut text = String::new();
+    write!(text, "{}", booted.format(format))
+        .map_err(|_| CollectError::Failed(format!("invalid boot_time_format {:?}", format)))?;
+    Ok(text)
 }
 
 fn calculate_days_from_date(date_str: &str) -> Result<i64, Box<dyn std::error::Error>> {
@@ -158,14 +796,60 @@ fn calculate_days_from_date(date_str: &str) -> Result<i64, Box<dyn std::error::E
     Ok((today - install_date).num_days())
 }
 
-fn get_package_count() -> String {
+/// The package count, or with `style` "breakdown" the count of every package
+/// manager, most packages first, like "1432 (pacman), 61 (cargo)"
+fn get_packages(style: &str) -> Result<String, CollectError> {
+    let mut counts: Vec<(String, usize)> = get_package_counts()?
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    if style != "breakdown" || counts.is_empty() {
+        return Ok(counts
+            .iter()
+            .map(|(_, count)| count)
+            .sum::<usize>()
+            .to_string());
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    Ok(counts
+        .iter()
+        .map(|(manager, count)| format!("{} ({})", count, manager))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+/// Installed packages by package manager
+#[cfg(not(any(windows, target_os = "macos")))]
+fn get_package_counts() -> Result<Vec<(String, usize)>, CollectError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Some(count) = crate::termux::is_termux()
+        .then(crate::termux::package_count)
+        .flatten()
+    {
+        return Ok(vec![("dpkg".to_string(), count)]);
+    }
+
     use libmacchina::{traits::PackageReadout as _, PackageReadout};
     let packages = PackageReadout::new();
     let pkg_counts = packages.count_pkgs();
 
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly"
+    ))]
+    if pkg_counts.is_empty() {
+        if let Some(count) = crate::bsd::package_count() {
+            return Ok(vec![("pkg".to_string(), count)]);
+        }
+    }
+
     if !pkg_counts.is_empty() {
-        let total: usize = pkg_counts.iter().map(|(_, count)| count).sum();
-        return total.to_string();
+        return Ok(pkg_counts
+            .into_iter()
+            .map(|(manager, count)| (manager.to_string(), count))
+            .collect());
     }
 
     let package_managers = [
@@ -179,16 +863,19 @@ fn get_package_count() -> String {
             if let Ok(output) = result {
                 let count = String::from_utf8_lossy(&output.stdout).lines().count();
                 if count > 0 {
-                    return count.to_string();
+                    return Ok(vec![(manager.to_string(), count)]);
                 }
             }
         }
     }
 
-    "0".to_string()
+    Err(CollectError::Failed(
+        "no supported package manager found".to_string(),
+    ))
 }
 
-fn get_shell() -> String {
+#[cfg(not(windows))]
+fn get_shell() -> Result<String, CollectError> {
     use libmacchina::{
         traits::{GeneralReadout as _, ShellFormat, ShellKind},
         GeneralReadout,
@@ -196,106 +883,180 @@ fn get_shell() -> String {
     let general = GeneralReadout::new();
     general
         .shell(ShellFormat::Relative, ShellKind::Default)
-        .unwrap_or_else(|_| "Unknown".to_string())
+        .map_err(|e| CollectError::Failed(e.to_string()))
 }
 
-fn get_terminal() -> String {
-    std::env::var("TERMINAL")
-        .ok()
-        .or_else(|| {
-            let output = Command::new("ps")
-                .arg("-p")
-                .arg(std::process::id().to_string())
-                .output()
-                .ok()?;
-            let stdout_str = String::from_utf8_lossy(&output.stdout);
-            let line = stdout_str.lines().nth(1)?;
-            Some(
-                line.split_once(' ')
-                    .map(|_| "unknown")
-                    .unwrap_or_default()
-                    .to_string(),
-            )
-        })
-        .unwrap_or("unknown".to_string())
+#[cfg(not(windows))]
+fn get_terminal() -> Result<String, CollectError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return Ok("Termux".to_string());
+    }
+
+    std::env::var("TERMINAL").map_err(|_| CollectError::Failed("TERMINAL is not set".to_string()))
 }
 
-fn get_window_manager() -> String {
+#[cfg(not(any(windows, target_os = "macos")))]
+fn get_window_manager() -> Result<String, CollectError> {
     if let Ok(wm_env) = std::env::var("XDG_CURRENT_DESKTOP") {
-        return match wm_env.to_lowercase().as_str() {
+        return Ok(match wm_env.to_lowercase().as_str() {
             "hyprland" => "Hyprland".to_string(),
             "sway" => "Sway".to_string(),
             _ => wm_env,
-        };
+        });
     }
 
     use libmacchina::{traits::GeneralReadout as _, GeneralReadout};
     let general = GeneralReadout::new();
     general
         .window_manager()
-        .unwrap_or_else(|_| "Unknown".to_string())
+        .map_err(|e| CollectError::Failed(e.to_string()))
 }
 
-fn get_cpu_model() -> Option<String> {
-    let sys = System::new_all();
-    sys.cpus().first().map(|cpu| {
-        let brand = cpu.brand().trim();
-        brand
-            .replace("(R)", "")
-            .replace("(TM)", "")
-            .replace("  ", " ")
-            .trim()
-            .to_string()
-    })
+fn get_cpu_model(sys: &System) -> Option<String> {
+    let model = sys
+        .cpus()
+        .first()
+        .map(|cpu| {
+            let brand = cpu.brand().trim();
+            brand
+                .replace("(R)", "")
+                .replace("(TM)", "")
+                .replace("  ", " ")
+                .trim()
+                .to_string()
+        })
+        .filter(|model| !model.is_empty());
+
+    // sysinfo doesn't read the CPU on every BSD
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly"
+    ))]
+    let model = model.or_else(crate::bsd::cpu_model);
+
+    model
 }
 
-fn get_gpu() -> Option<String> {
-    if let Ok(output) = Command::new("lspci").output() {
-        let lspci_output = String::from_utf8_lossy(&output.stdout);
-        for line in lspci_output.lines() {
-            if line.contains("VGA compatible controller") || line.contains("3D controller") {
-                if let Some(gpu_part) = line.split(':').nth(2) {
-                    let gpu = gpu_part.trim();
-                    let cleaned = gpu
-                        .replace("NVIDIA Corporation", "NVIDIA")
-                        .replace("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD")
-                        .replace("Advanced Micro Devices, Inc.", "AMD")
-                        .replace("Intel Corporation", "Intel")
-                        .replace("[AMD/ATI]", "")
-                        .trim()
-                        .to_string();
-                    return Some(cleaned);
-                }
+#[cfg(not(any(windows, target_os = "macos")))]
+fn get_gpu() -> Result<Option<String>, CollectError> {
+    // Needs root or /sys, neither of which Termux has
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return Ok(None);
+    }
+
+    let output = crate::error::run("lspci", &[])?;
+    let lspci_output = String::from_utf8_lossy(&output.stdout);
+    for line in lspci_output.lines() {
+        if line.contains("VGA compatible controller") || line.contains("3D controller") {
+            if let Some(gpu_part) = line.split(':').nth(2) {
+                let gpu = gpu_part.trim();
+                let cleaned = gpu
+                    .replace("NVIDIA Corporation", "NVIDIA")
+                    .replace("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD")
+                    .replace("Advanced Micro Devices, Inc.", "AMD")
+                    .replace("Intel Corporation", "Intel")
+                    .replace("[AMD/ATI]", "")
+                    .trim()
+                    .to_string();
+                return Ok(Some(cleaned));
             }
         }
     }
-    None
+    Ok(None)
 }
 
-fn get_theme() -> Option<String> {
+#[cfg(not(any(windows, target_os = "macos")))]
+fn get_theme() -> Result<String, CollectError> {
     if let Ok(theme) = std::env::var("GTK_THEME") {
-        return Some(theme);
+        return Ok(theme);
     }
 
-    let home = std::env::var("HOME").unwrap_or_default();
+    let home = crate::config::home_dir().unwrap_or_default();
     let gtk3_config = format!("{}/.config/gtk-3.0/settings.ini", home);
 
-    if let Ok(contents) = fs::read_to_string(&gtk3_config) {
-        for line in contents.lines() {
-            if line.starts_with("gtk-theme-name") {
-                if let Some(theme) = line.split('=').nth(1) {
-                    return Some(theme.trim().to_string());
-                }
+    let contents = fs::read_to_string(&gtk3_config).map_err(|e| {
+        CollectError::Failed(format!("GTK_THEME is not set and {}: {}", gtk3_config, e))
+    })?;
+    for line in contents.lines() {
+        if line.starts_with("gtk-theme-name") {
+            if let Some(theme) = line.split('=').nth(1) {
+                return Ok(theme.trim().to_string());
             }
         }
     }
 
+    Err(CollectError::Failed(format!(
+        "no gtk-theme-name in {}",
+        gtk3_config
+    )))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn get_model() -> Result<String, CollectError> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return crate::termux::model()
+            .ok_or_else(|| CollectError::Failed("getprop reports no model".to_string()));
+    }
+
+    let read = |name: &str| {
+        fs::read_to_string(format!("/sys/devices/virtual/dmi/id/{}", name))
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty() && value != "To Be Filled By O.E.M.")
+    };
+    let dmi = match (read("product_name"), read("product_version")) {
+        (Some(name), Some(version)) if !name.contains(&version) => {
+            Some(format!("{} {}", name, version))
+        }
+        (name, _) => name,
+    };
+    dmi.or_else(sbc::board).ok_or_else(|| {
+        CollectError::Failed("no model in the DMI tables or the device tree".to_string())
+    })
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+fn get_init() -> Option<String> {
+    // Needs root or /sys, neither of which Termux has
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if crate::termux::is_termux() {
+        return None;
+    }
+
+    let init = fs::read_to_string("/proc/1/comm").ok()?;
+    let init = match init.trim() {
+        "runit" | "runit-init" => "runit",
+        "s6-svscan" => "s6",
+        "shepherd" => "GNU Shepherd",
+        // Both OpenRC and plain SysVinit run as init
+        "init" if PathBuf::from("/run/openrc").exists() => "OpenRC",
+        "init" => "SysVinit",
+        other => other,
+    };
+    Some(init.to_string())
+}
+
+/// System Integrity Protection only exists on macOS
+#[cfg(not(target_os = "macos"))]
+fn get_sip() -> Option<String> {
     None
 }
 
-fn get_nix_generation() -> Option<String> {
+fn get_nix_generation() -> Result<Option<String>, CollectError> {
     if !PathBuf::from("/etc/NIXOS").exists() && !PathBuf::from("/run/current-system").exists() {
-        return None;
+        return Ok(None);
     }
 
     // Helper function to extract generation number from path like "system-123-link"
@@ -313,7 +1074,7 @@ fn get_nix_generation() -> Option<String> {
     if let Ok(link) = fs::read_link("/nix/var/nix/profiles/system") {
         if let Some(link_str) = link.to_str() {
             if let Some(gen) = extract_generation(link_str) {
-                return Some(gen);
+                return Ok(Some(gen));
             }
         }
     }
@@ -321,10 +1082,55 @@ fn get_nix_generation() -> Option<String> {
     if let Ok(link) = fs::read_link("/run/current-system") {
         if let Some(link_str) = link.to_str() {
             if let Some(gen) = extract_generation(link_str) {
-                return Some(gen);
+                return Ok(Some(gen));
             }
         }
     }
 
-    None
+    Err(CollectError::Failed(
+        "no generation number in the system profile's link".to_string(),
+    ))
+}
+
+/// Current Guix System and Guix Home generations and when they were built,
+/// like "system 42 (2025-05-01), home 7 (2025-05-10)", or the user profile's
+/// on a foreign distro
+fn get_guix_generation() -> Option<String> {
+    let profiles = PathBuf::from("/var/guix/profiles");
+    if !profiles.exists() {
+        return None;
+    }
+
+    // A profile links to its current generation, like "system-42-link"
+    fn generation(profile: PathBuf) -> Option<String> {
+        let link = fs::read_link(&profile).ok()?;
+        let name = link.file_name()?.to_str()?.strip_suffix("-link")?;
+        let (_, number) = name.rsplit_once('-')?;
+        let built = profile
+            .with_file_name(&link)
+            .symlink_metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(|time| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d"));
+        Some(match built {
+            Some(date) => format!("{} ({})", number, date),
+            None => number.to_string(),
+        })
+    }
+
+    let user = std::env::var("USER").unwrap_or_default();
+    let per_user = profiles.join("per-user").join(user);
+    let mut parts: Vec<String> = [
+        ("system", profiles.join("system")),
+        ("home", per_user.join("guix-home")),
+    ]
+    .into_iter()
+    .filter_map(|(name, profile)| Some(format!("{} {}", name, generation(profile)?)))
+    .collect();
+    if parts.is_empty() {
+        parts.extend(
+            generation(per_user.join("guix-profile")).map(|gen| format!("profile {}", gen)),
+        );
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
 }
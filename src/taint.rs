@@ -0,0 +1,57 @@
+// The `taint` field: why the kernel is tainted, decoded from the bits in
+// /proc/sys/kernel/tainted, see the kernel's admin-guide/tainted-kernels
+use crate::error::CollectError;
+
+/// What each bit means, lowest first
+const FLAGS: [&str; 20] = [
+    "proprietary module",
+    "module force loaded",
+    "out of spec system",
+    "module force unloaded",
+    "machine check",
+    "bad page",
+    "user request",
+    "oops occurred",
+    "ACPI table overridden",
+    "kernel warning",
+    "staging driver",
+    "firmware workaround",
+    "out-of-tree module",
+    "unsigned module",
+    "soft lockup",
+    "live patched",
+    "auxiliary taint",
+    "randstruct kernel",
+    "in-kernel test",
+    "fwctl debug operation",
+];
+
+/// The causes, and whether the kernel is tainted at all
+#[cfg(target_os = "linux")]
+pub fn status() -> Result<(String, bool), CollectError> {
+    let value = std::fs::read_to_string("/proc/sys/kernel/tainted")
+        .map_err(|e| CollectError::Failed(format!("/proc/sys/kernel/tainted: {}", e)))?;
+    let bits: u64 = value
+        .trim()
+        .parse()
+        .map_err(|_| CollectError::Failed(format!("unexpected taint value {:?}", value.trim())))?;
+    if bits == 0 {
+        return Ok(("not tainted".to_string(), false));
+    }
+
+    let causes: Vec<String> = (0..64)
+        .filter(|bit| bits & (1 << bit) != 0)
+        .map(|bit| match FLAGS.get(bit) {
+            Some(flag) => flag.to_string(),
+            None => format!("flag {}", bit),
+        })
+        .collect();
+    Ok((causes.join(", "), true))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn status() -> Result<(String, bool), CollectError> {
+    Err(CollectError::Failed(
+        "only Linux reports kernel taint".to_string(),
+    ))
+}
@@ -0,0 +1,92 @@
+// The `gpu_temp` and `temps` fields: GPU temperature from the kernel's hwmon
+// sensors (amdgpu, nouveau, i915) or nvidia-smi, and a summary line with the
+// CPU's next to it
+use crate::error::{self, CollectError};
+use std::fs;
+use sysinfo::Components;
+
+/// Sensor labels of CPU packages, as sysinfo reports them
+const CPU_SENSORS: [&str; 4] = ["Tctl", "Package id 0", "cpu_thermal", "CPU"];
+
+/// The GPU temperature, like "61°C"
+pub fn gpu() -> Result<String, CollectError> {
+    gpu_celsius()
+        .map(format_celsius)
+        .ok_or_else(|| CollectError::Failed("no GPU temperature sensor found".to_string()))
+}
+
+/// CPU and GPU temperatures, like "cpu 54°C, gpu 61°C"
+pub fn summary() -> Result<String, CollectError> {
+    let parts: Vec<String> = [("cpu", cpu_celsius()), ("gpu", gpu_celsius())]
+        .into_iter()
+        .filter_map(|(name, celsius)| Some(format!("{} {}", name, format_celsius(celsius?))))
+        .collect();
+    if parts.is_empty() {
+        return Err(CollectError::Failed(
+            "no temperature sensors found".to_string(),
+        ));
+    }
+    Ok(parts.join(", "))
+}
+
+fn format_celsius(celsius: f32) -> String {
+    format!("{:.0}°C", celsius)
+}
+
+fn cpu_celsius() -> Option<f32> {
+    let components = Components::new_with_refreshed_list();
+    CPU_SENSORS.iter().find_map(|sensor| {
+        components
+            .iter()
+            .find(|component| component.label().contains(sensor))
+            .map(|component| component.temperature())
+            .filter(|celsius| !celsius.is_nan())
+    })
+}
+
+/// The first GPU's temperature: temp1_input of its hwmon device is the edge
+/// or die sensor, in millidegrees
+fn gpu_celsius() -> Option<f32> {
+    let from_hwmon = fs::read_dir("/sys/class/drm")
+        .ok()
+        .into_iter()
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // card0, not connectors like card0-DP-1
+            name.starts_with("card") && !name.contains('-')
+        })
+        .filter_map(|card| fs::read_dir(card.path().join("device/hwmon")).ok())
+        .flat_map(|entries| entries.flatten())
+        .find_map(|hwmon| {
+            let millidegrees: f32 = fs::read_to_string(hwmon.path().join("temp1_input"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(millidegrees / 1000.0)
+        });
+    from_hwmon.or_else(nvidia_celsius)
+}
+
+/// The proprietary NVIDIA driver has no hwmon sensor
+fn nvidia_celsius() -> Option<f32> {
+    let output = error::run(
+        "nvidia-smi",
+        &[
+            "--query-gpu=temperature.gpu",
+            "--format=csv,noheader,nounits",
+        ],
+    )
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -0,0 +1,64 @@
+// Termux on Android: no root, no /sys, and packages under $PREFIX
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Whether huginn runs inside the Termux app
+pub fn is_termux() -> bool {
+    std::env::var_os("TERMUX_VERSION").is_some() || Path::new("/data/data/com.termux").exists()
+}
+
+/// Termux's usr directory, where its packages are installed
+fn prefix() -> PathBuf {
+    std::env::var("PREFIX")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/data/data/com.termux/files/usr"))
+}
+
+/// An Android system property
+fn getprop(name: &str) -> Option<String> {
+    let output = Command::new("getprop").arg(name).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+pub fn os_name() -> String {
+    let android = match getprop("ro.build.version.release") {
+        Some(version) => format!("Android {}", version),
+        None => "Android".to_string(),
+    };
+    match std::env::var("TERMUX_VERSION") {
+        Ok(termux) => format!("{} (Termux {})", android, termux),
+        Err(_) => format!("{} (Termux)", android),
+    }
+}
+
+pub fn model() -> Option<String> {
+    let model = getprop("ro.product.model")?;
+    match getprop("ro.product.manufacturer") {
+        Some(maker) if !model.to_lowercase().starts_with(&maker.to_lowercase()) => {
+            Some(format!("{} {}", maker, model))
+        }
+        _ => Some(model),
+    }
+}
+
+/// Packages installed with pkg or apt, from dpkg's status file
+pub fn package_count() -> Option<usize> {
+    let status = std::fs::read_to_string(prefix().join("var/lib/dpkg/status")).ok()?;
+    Some(
+        status
+            .lines()
+            .filter(|line| line.starts_with("Package: "))
+            .count(),
+    )
+}
+
+/// Days since Termux was set up, as the root filesystem can't be read
+pub fn age() -> String {
+    let days = std::fs::metadata(prefix())
+        .and_then(|metadata| metadata.created().or_else(|_| metadata.modified()))
+        .ok()
+        .and_then(|time| time.elapsed().ok())
+        .map_or(0, |elapsed| elapsed.as_secs() / 86400);
+    format!("{} {}", days, crate::i18n::tr("days"))
+}
@@ -0,0 +1,99 @@
+use crate::color::paint;
+use crate::config::{data_dir, TipsConfig};
+use crate::expand_home;
+use crate::quote::wrap;
+use crossterm::style::Stylize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const BUILTIN_TIPS: [&str; 8] = [
+    "huginn --watch keeps the fetch on screen and redraws it every 2 seconds.",
+    "huginn logos --preview shows every logo you can use.",
+    "Set icon_style = \"nerd\" under [info] for icons before the labels.",
+    "huginn --format motd prints a login banner without colors or images.",
+    "Run huginn daemon in the background and every fetch answers instantly.",
+    "huginn history charts package count and disk usage over time.",
+    "A [layout] template lets you arrange the fetch any way you like.",
+    "Add your own tips, like shell aliases to remember, to the file in [tips].",
+];
+
+/// Runs counted so far, so tips show every few runs and rotate in order
+fn counter_path() -> PathBuf {
+    data_dir().join("tip-counter")
+}
+
+/// The next tip wrapped to the configured width, painted and with the width
+/// of each line, or nothing on runs that skip it
+pub fn tip_lines(config: &TipsConfig) -> Vec<(String, usize)> {
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let runs = runs();
+    let every = config.every.max(1);
+    if !runs.is_multiple_of(every) {
+        return Vec::new();
+    }
+
+    let tips = all_tips(config);
+    let Some(tip) = tips.get(runs / every % tips.len().max(1)) else {
+        return Vec::new();
+    };
+
+    wrap(&format!("Tip: {}", tip), config.width)
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let width = line.chars().count();
+            // Only the first line starts with the "Tip:" marker
+            let painted = match line.strip_prefix("Tip:") {
+                Some(rest) if i == 0 => format!("{}{}", paint("Tip:".yellow()), rest),
+                _ => line,
+            };
+            (painted, width)
+        })
+        .collect()
+}
+
+/// Runs before this one, counted once per process so watch mode's redraws
+/// don't each count as a run
+fn runs() -> usize {
+    static RUNS: OnceLock<usize> = OnceLock::new();
+    *RUNS.get_or_init(|| {
+        let path = counter_path();
+        let runs = fs::read_to_string(&path)
+            .ok()
+            .and_then(|count| count.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(&path, (runs + 1).to_string());
+        runs
+    })
+}
+
+/// Built-in tips, if wanted, followed by the lines of the user's tips file
+fn all_tips(config: &TipsConfig) -> Vec<String> {
+    let mut tips: Vec<String> = if config.builtin {
+        BUILTIN_TIPS.iter().map(|tip| tip.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+
+    if !config.file.is_empty() {
+        let path = expand_home(&config.file);
+        match fs::read_to_string(&path) {
+            Ok(contents) => tips.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            ),
+            Err(e) => eprintln!("Warning: Failed to read tips file {}: {}", path, e),
+        }
+    }
+    tips
+}
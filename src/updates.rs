@@ -0,0 +1,90 @@
+// Pending package updates, for --notify and the security field
+use crate::error::{self, CollectError};
+use std::process::Command;
+
+/// A package manager command and a filter for the lines that stand for one update
+type Checker = (&'static str, &'static [&'static str], fn(&str) -> bool);
+
+/// Number of upgradable packages, from the first package manager that can tell
+/// Offline, only the package databases already on disk are read
+pub fn pending() -> Option<usize> {
+    let offline = crate::network::offline();
+    let checkers: [Checker; 3] = [
+        (
+            "checkupdates",
+            if offline { &["--nosync"] } else { &[] },
+            |line| !line.trim().is_empty(),
+        ),
+        ("apt-get", &["-s", "upgrade"], |line| {
+            line.starts_with("Inst ")
+        }),
+        (
+            "dnf",
+            if offline {
+                &["-q", "-C", "check-update"]
+            } else {
+                &["-q", "check-update"]
+            },
+            |line| line.split_whitespace().count() == 3,
+        ),
+    ];
+
+    for (manager, args, is_update) in checkers {
+        if which::which(manager).is_err() {
+            continue;
+        }
+        if let Ok(output) = Command::new(manager).args(args).output() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Some(stdout.lines().filter(|line| is_update(line)).count());
+        }
+    }
+    None
+}
+
+/// Pending updates that fix security issues, like "3 pending", and whether
+/// there are any
+/// arch-audit downloads the Arch security tracker's list, so offline it is
+/// skipped; apt and dnf read what they already know
+pub fn security() -> Result<(String, bool), CollectError> {
+    let offline = crate::network::offline();
+    let checkers: [Checker; 3] = [
+        ("arch-audit", &["--upgradable", "--quiet"], |line| {
+            !line.trim().is_empty()
+        }),
+        // "Inst openssl [3.0.11-1] (3.0.13-1 Debian-Security:12/stable-security [amd64])"
+        ("apt-get", &["-s", "upgrade"], |line| {
+            line.starts_with("Inst ") && line.to_lowercase().contains("security")
+        }),
+        // "FEDORA-2024-1a2b3c4d5e Important/Sec. openssl-3.1.4-3.fc39.x86_64"
+        (
+            "dnf",
+            if offline {
+                &["-q", "-C", "updateinfo", "list", "--security"]
+            } else {
+                &["-q", "updateinfo", "list", "--security"]
+            },
+            |line| line.split_whitespace().count() == 3,
+        ),
+    ];
+
+    let (manager, args, is_update) = checkers
+        .into_iter()
+        .filter(|(manager, _, _)| !(offline && *manager == "arch-audit"))
+        .find(|(manager, _, _)| which::which(manager).is_ok())
+        .ok_or_else(|| CollectError::Failed("no arch-audit, apt-get or dnf to ask".to_string()))?;
+    let output = error::run(manager, args)?;
+    if !output.status.success() {
+        return Err(CollectError::Failed(format!(
+            "{} exited with {}",
+            manager, output.status
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let count = stdout.lines().filter(|line| is_update(line)).count();
+    let text = if count == 0 {
+        "none".to_string()
+    } else {
+        format!("{} pending", count)
+    };
+    Ok((text, count > 0))
+}
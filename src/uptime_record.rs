@@ -0,0 +1,83 @@
+use crate::config::data_dir;
+use crate::fetch::format_uptime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// The longest uptime seen on this machine, and whether this boot beat it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeRecord {
+    pub secs: u64,
+    pub beaten: bool,
+}
+
+impl UptimeRecord {
+    /// Text shown next to the uptime line
+    pub fn text(&self) -> String {
+        if self.beaten {
+            "new record!".to_string()
+        } else if self.secs >= 86400 {
+            format!("record: {} days", self.secs / 86400)
+        } else {
+            format!("record: {}", format_uptime(self.secs))
+        }
+    }
+}
+
+/// Longest uptime of every boot, keyed by the kernel's boot id
+fn records_path() -> PathBuf {
+    data_dir().join("uptime-records.json")
+}
+
+/// Identifies the current boot, so records survive huginn restarts
+#[cfg(target_os = "linux")]
+fn boot_id() -> std::io::Result<String> {
+    Ok(fs::read_to_string("/proc/sys/kernel/random/boot_id")?
+        .trim()
+        .to_string())
+}
+
+/// Elsewhere the boot time, which stays put until the next boot
+#[cfg(not(target_os = "linux"))]
+fn boot_id() -> std::io::Result<String> {
+    Ok(sysinfo::System::boot_time().to_string())
+}
+
+/// Save the current uptime for this boot and compare it with earlier boots
+pub fn update(uptime_secs: u64) -> Result<UptimeRecord, Box<dyn std::error::Error>> {
+    let boot_id = boot_id()?;
+    let path = records_path();
+    let mut records: BTreeMap<String, u64> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let previous = records
+        .iter()
+        .filter(|(id, _)| **id != boot_id)
+        .map(|(_, secs)| *secs)
+        .max();
+    let current = records.entry(boot_id).or_insert(0);
+    *current = (*current).max(uptime_secs);
+    let current = *current;
+
+    fs::create_dir_all(data_dir())?;
+    fs::write(&path, serde_json::to_string_pretty(&records)?)?;
+
+    // The first boot huginn sees has nothing to beat
+    Ok(match previous {
+        Some(previous) if current > previous => UptimeRecord {
+            secs: current,
+            beaten: true,
+        },
+        Some(previous) => UptimeRecord {
+            secs: previous,
+            beaten: false,
+        },
+        None => UptimeRecord {
+            secs: current,
+            beaten: false,
+        },
+    })
+}
@@ -0,0 +1,47 @@
+// WebAssembly plugins, a sandboxed alternative to executable plugins
+// The module imports nothing, so it can't touch files, the network or other
+// processes. It exports its `memory` and a `huginn_fields` function taking no
+// arguments and returning the address of its JSON output in the high 32
+// bits and the length in the low 32 bits. The JSON is the same as for
+// executable plugins.
+use crate::error::CollectError;
+use crate::plugins::{self, PluginField};
+use std::path::Path;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Enough for any sensible plugin; an endless loop runs out instead of spinning
+const FUEL: u64 = 1_000_000_000;
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+fn failed(error: wasmtime::Error) -> CollectError {
+    CollectError::Failed(error.to_string())
+}
+
+/// Instantiate the module and parse the fields it returns
+pub fn run(path: &Path) -> Result<Vec<PluginField>, CollectError> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).map_err(failed)?;
+    let module = Module::from_file(&engine, path).map_err(failed)?;
+
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+    let mut store: Store<StoreLimits> = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(FUEL).map_err(failed)?;
+
+    let instance = Instance::new(&mut store, &module, &[]).map_err(failed)?;
+    let fields = instance
+        .get_typed_func::<(), u64>(&mut store, "huginn_fields")
+        .map_err(failed)?;
+    let packed = fields.call(&mut store, ()).map_err(failed)?;
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| CollectError::Failed("the module exports no memory".to_string()))?;
+    let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    let json = memory
+        .data(&store)
+        .get(ptr..ptr.saturating_add(len))
+        .ok_or_else(|| CollectError::Failed("output is outside the module's memory".to_string()))?;
+    plugins::parse(json)
+}
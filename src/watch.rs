@@ -0,0 +1,324 @@
+use crate::challenge::ChallengeStatus;
+use crate::config::{Config, LogoConfig};
+use crate::daemon;
+use crate::fetch::Fetch;
+use crate::logo::{draw_logo, LogoAnimation};
+use crate::output;
+use crate::reload::ConfigWatcher;
+use crate::screen::{self, Frame};
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, Clear, ClearType};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+const COLORBAR_STYLES: [&str; 5] = ["shaded", "blocks", "circles", "gradient", "none"];
+const ICON_STYLES: [&str; 3] = ["none", "nerd", "ascii"];
+/// How often the config file is checked for changes between refreshes
+const RELOAD_CHECK: Duration = Duration::from_millis(250);
+
+/// Raw mode for the keybindings, left again however the loop ends
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Option<Self> {
+        terminal::enable_raw_mode().ok().map(|_| RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Why waiting ended
+enum Wake {
+    Key(KeyEvent),
+    Refresh,
+    Resize,
+    ConfigChanged,
+}
+
+/// What a key press asks of the loop
+enum Action {
+    Quit,
+    /// Draw again from the fetch on screen
+    Redraw,
+    /// Collect first, for a field that was hidden until now
+    Refresh,
+    Nothing,
+}
+
+/// Settings the keybindings change while watching
+struct State {
+    config: Config,
+    /// The logo as configured, before `[logo.normal]` or `[logo.challenge]`
+    logo: LogoConfig,
+    challenge: Option<(i64, i64)>,
+    /// Challenge length `m` switches to
+    target: (i64, i64),
+    /// Fields the number keys toggle: those shown at the start, at most nine
+    fields: Vec<&'static str>,
+    paused: bool,
+    notice: Option<String>,
+    /// Off once a field is toggled, as the daemon collects what the config
+    /// file asks for
+    use_daemon: bool,
+}
+
+impl State {
+    fn handle(&mut self, key: KeyEvent, fetch: &Fetch, status: Option<&ChallengeStatus>) -> Action {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char('p') | KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+                self.notice = self.paused.then(|| "paused, p to resume".to_string());
+                Action::Redraw
+            }
+            KeyCode::Char(digit @ '1'..='9') => {
+                let index = digit as usize - '1' as usize;
+                let Some(field) = self.fields.get(index) else {
+                    return Action::Nothing;
+                };
+                let shown = self.config.display.toggle(field).unwrap_or(false);
+                self.use_daemon = false;
+                let state = if shown { "shown" } else { "hidden" };
+                self.notice = Some(format!("{} {}", field, state));
+                if shown {
+                    Action::Refresh
+                } else {
+                    Action::Redraw
+                }
+            }
+            KeyCode::Char('c') => {
+                let style = next(&COLORBAR_STYLES, &self.config.colorbar.style);
+                self.notice = Some(format!("colorbar {}", style));
+                self.config.colorbar.style = style.to_string();
+                Action::Redraw
+            }
+            KeyCode::Char('i') => {
+                let style = next(&ICON_STYLES, &self.config.info.icon_style);
+                self.notice = Some(format!("icons {}", style));
+                self.config.info.icon_style = style.to_string();
+                Action::Redraw
+            }
+            KeyCode::Char('m') => {
+                self.challenge = match self.challenge {
+                    Some(_) => None,
+                    None => Some(self.target),
+                };
+                let mode = if self.challenge.is_some() {
+                    "challenge"
+                } else {
+                    "normal"
+                };
+                self.notice = Some(format!("{} mode", mode));
+                self.use_mode();
+                Action::Redraw
+            }
+            KeyCode::Char('s') => {
+                let path = format!(
+                    "huginn-{}.json",
+                    chrono::Local::now().format("%Y%m%d-%H%M%S")
+                );
+                let json = output::render_json(fetch, status);
+                self.notice = Some(match std::fs::write(&path, json + "\n") {
+                    Ok(()) => format!("saved {}", path),
+                    Err(e) => format!("could not save {}: {}", path, e),
+                });
+                Action::Redraw
+            }
+            _ => Action::Nothing,
+        }
+    }
+
+    /// Apply the current mode's logo overrides to the logo as configured, so
+    /// switching back and forth doesn't pile them up
+    fn use_mode(&mut self) {
+        let mut logo = self.logo.clone();
+        logo.use_mode(self.challenge.is_some());
+        self.config.logo = logo;
+    }
+
+    /// Take a reloaded config, keeping the mode switched to with `m`
+    fn reload(&mut self, mut config: Config) {
+        self.fields = config.display.shown_fields().into_iter().take(9).collect();
+        self.logo = config.logo.clone();
+        self.config = config;
+        self.use_mode();
+    }
+}
+
+/// The entry after `current`, wrapping around
+fn next<'a>(styles: &[&'a str], current: &str) -> &'a str {
+    let index = styles.iter().position(|style| *style == current);
+    styles[index.map_or(0, |i| (i + 1) % styles.len())]
+}
+
+/// Redraw the fetch every `interval` until `q` is pressed, animating a GIF
+/// custom logo in between refreshes
+/// Only the cells that changed since the last refresh are rewritten, so
+/// the bars and uptime update without the screen flickering. Keys toggle
+/// fields, cycle the colorbar and icons, switch modes, pause and save
+/// snapshots, and edits to the config file apply right away
+pub fn run(
+    config: &Config,
+    challenge: Option<(i64, i64)>,
+    interval: Duration,
+    use_daemon: bool,
+    watcher: Option<ConfigWatcher>,
+) -> io::Result<()> {
+    let mut config = config.clone();
+    // What is on screen, and the terminal size it was drawn for
+    let mut shown: Option<(Frame, Option<(u16, u16)>)> = None;
+
+    // The graphics probe reads the terminal's reply in raw mode itself
+    if !config.logo.hidden() {
+        crate::graphics::capabilities();
+    }
+    let _raw_mode = RawMode::enable();
+
+    let mut state = State {
+        fields: config.display.shown_fields().into_iter().take(9).collect(),
+        target: challenge.unwrap_or((config.challenge.years, config.challenge.months)),
+        challenge,
+        logo: config.logo.clone(),
+        config,
+        paused: false,
+        notice: None,
+        use_daemon,
+    };
+    state.use_mode();
+    // Frames are decoded once, not on every refresh
+    let mut animation = LogoAnimation::load(&state.config.logo);
+    let mut current = None;
+
+    loop {
+        let fetch = current.get_or_insert_with(|| {
+            state
+                .use_daemon
+                .then(daemon::query)
+                .flatten()
+                .unwrap_or_else(|| Fetch::collect(&state.config))
+        });
+        let config = &state.config;
+        let status = state.challenge.map(|(years, months)| {
+            ChallengeStatus::new(
+                years,
+                months,
+                config.challenge.start_date.as_deref(),
+                config,
+            )
+        });
+
+        let (origin, frame) =
+            screen::capture(|| crate::render_ansi(config, fetch, status.as_ref()));
+        let (x, y) = origin?;
+        let size = terminal::size().ok();
+        match &shown {
+            // The logo stays put as long as it and the terminal size do
+            Some((last, last_size)) if last.logos == frame.logos && *last_size == size => {
+                frame.draw_changes(last)?;
+            }
+            _ => {
+                execute!(io::stdout(), Clear(ClearType::All))?;
+                frame.draw_changes(&Frame::default())?;
+                for logo in &frame.logos {
+                    draw_logo(&logo.distro, logo.x, logo.y, &config.logo);
+                }
+            }
+        }
+        let notice_row = frame.height() + 1;
+        draw_notice(notice_row, state.notice.as_deref())?;
+        shown = Some((frame, size));
+
+        let next_refresh = (!state.paused).then(|| Instant::now() + interval);
+        let wake = wait(
+            next_refresh,
+            animation.as_mut(),
+            (x, y),
+            config,
+            watcher.as_ref(),
+        )?;
+        let action = match wake {
+            Wake::Key(key) => state.handle(key, fetch, status.as_ref()),
+            Wake::Resize if state.paused => Action::Redraw,
+            Wake::Refresh | Wake::Resize => {
+                state.notice = None;
+                Action::Refresh
+            }
+            Wake::ConfigChanged => match watcher.as_ref().map(ConfigWatcher::reload) {
+                Some(Ok(reloaded)) => {
+                    state.reload(reloaded);
+                    animation = LogoAnimation::load(&state.config.logo);
+                    // The logo may look different now
+                    shown = None;
+                    state.notice = Some("reloaded the config".to_string());
+                    Action::Refresh
+                }
+                Some(Err(e)) => {
+                    // toml's errors go on to quote the line
+                    let error = e.to_string();
+                    let first_line = error.lines().next().unwrap_or_default();
+                    state.notice = Some(format!("config not reloaded: {}", first_line));
+                    Action::Redraw
+                }
+                None => Action::Nothing,
+            },
+        };
+        match action {
+            // Leave the shell prompt where the notice was
+            Action::Quit => return draw_notice(notice_row, None),
+            // Collected again at the top of the loop
+            Action::Refresh => current = None,
+            Action::Redraw | Action::Nothing => {}
+        }
+    }
+}
+
+/// One line under the fetch for what the last key did
+fn draw_notice(row: u16, notice: Option<&str>) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    execute!(stdout, MoveTo(0, row), Clear(ClearType::CurrentLine))?;
+    if let Some(notice) = notice {
+        write!(stdout, "{}", notice)?;
+    }
+    stdout.flush()
+}
+
+/// Wait until `until`, or for a key press or config change when paused,
+/// animating the logo meanwhile
+fn wait(
+    until: Option<Instant>,
+    mut animation: Option<&mut LogoAnimation>,
+    (x, y): (u16, u16),
+    config: &Config,
+    watcher: Option<&ConfigWatcher>,
+) -> io::Result<Wake> {
+    loop {
+        if watcher.is_some_and(ConfigWatcher::changed) {
+            return Ok(Wake::ConfigChanged);
+        }
+        let step = Instant::now() + RELOAD_CHECK;
+        let step = until.map_or(step, |until| until.min(step));
+        match animation.as_deref_mut() {
+            Some(animation) => animation.play_until(x, y, step, &config.logo),
+            None => {
+                event::poll(step.saturating_duration_since(Instant::now()))?;
+            }
+        }
+        if event::poll(Duration::ZERO)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => return Ok(Wake::Key(key)),
+                Event::Resize(..) => return Ok(Wake::Resize),
+                _ => continue,
+            }
+        }
+        if until.is_some_and(|until| Instant::now() >= until) {
+            return Ok(Wake::Refresh);
+        }
+    }
+}
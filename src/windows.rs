@@ -0,0 +1,221 @@
+// Collectors for Windows, replacing the ones that read /proc, /sys, lspci
+// and the GTK settings on Unix
+use crate::error::CollectError;
+use std::path::PathBuf;
+use std::process::Command;
+use sysinfo::{ProcessesToUpdate, System};
+use windows::Win32::Graphics::Dxgi::{
+    CreateDXGIFactory1, IDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE, DXGI_ERROR_NOT_FOUND,
+};
+
+const CURRENT_VERSION_KEY: &str = r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+const PERSONALIZE_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
+
+// Processes that sit between huginn and the terminal window
+const SHELLS: [&str; 5] = ["cmd", "powershell", "pwsh", "nu", "bash"];
+
+/// A value from the registry, read through reg.exe
+fn reg_value(key: &str, name: &str) -> Option<String> {
+    let output = Command::new("reg")
+        .args(["query", key, "/v", name])
+        .output()
+        .ok()?;
+    // Lines look like "    ProductName    REG_SZ    Windows 10 Pro"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let rest = line.trim().strip_prefix(name)?.trim_start();
+            let (_kind, value) = rest.split_once(char::is_whitespace)?;
+            Some(value.trim().to_string())
+        })
+}
+
+/// A REG_DWORD value, which reg.exe prints in hex
+fn reg_dword(key: &str, name: &str) -> Option<u64> {
+    let value = reg_value(key, name)?;
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+pub fn get_os_name() -> Result<String, CollectError> {
+    let Some(product) = reg_value(CURRENT_VERSION_KEY, "ProductName") else {
+        return Ok("Windows".to_string());
+    };
+    // Windows 11 still calls itself Windows 10 here; the build number tells
+    let build = reg_value(CURRENT_VERSION_KEY, "CurrentBuild")
+        .and_then(|build| build.parse::<u32>().ok())
+        .unwrap_or(0);
+    let product = if build >= 22000 {
+        product.replacen("Windows 10", "Windows 11", 1)
+    } else {
+        product
+    };
+
+    Ok(match reg_value(CURRENT_VERSION_KEY, "DisplayVersion") {
+        Some(version) => format!("{} {}", product, version),
+        None => product,
+    })
+}
+
+pub fn get_system_age() -> String {
+    // Seconds since the epoch, rewritten by feature updates
+    let installed = reg_dword(CURRENT_VERSION_KEY, "InstallDate").unwrap_or(0);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!(
+        "{} {}",
+        now.saturating_sub(installed) / 86400,
+        crate::i18n::tr("days")
+    )
+}
+
+/// Installed packages by package manager
+pub fn get_package_counts() -> Result<Vec<(String, usize)>, CollectError> {
+    let mut counts = Vec::new();
+
+    // winget lists everything under Apps & features, below a line of dashes
+    if let Ok(output) = Command::new("winget")
+        .args([
+            "list",
+            "--accept-source-agreements",
+            "--disable-interactivity",
+        ])
+        .output()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let winget = stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("---"))
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        counts.push(("winget".to_string(), winget));
+    }
+
+    // Scoop and Chocolatey keep one directory per installed package
+    let count_dirs = |dir: PathBuf| {
+        std::fs::read_dir(dir).map_or(0, |entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .count()
+        })
+    };
+    let scoop = std::env::var("SCOOP")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(crate::config::home_dir().unwrap_or_default()).join("scoop")
+        });
+    // Scoop lists itself among its apps
+    let scoop_apps = count_dirs(scoop.join("apps")).saturating_sub(1);
+    counts.push(("scoop".to_string(), scoop_apps));
+    let choco = std::env::var("ChocolateyInstall")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(r"C:\ProgramData\chocolatey"));
+    counts.push(("choco".to_string(), count_dirs(choco.join("lib"))));
+
+    Ok(counts)
+}
+
+/// Names of huginn's parent processes, nearest first, without ".exe"
+fn ancestors() -> Vec<String> {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All);
+
+    let mut names = Vec::new();
+    let mut pid = sysinfo::get_current_pid()
+        .ok()
+        .and_then(|pid| sys.process(pid)?.parent());
+    while let Some(current) = pid {
+        let Some(process) = sys.process(current) else {
+            break;
+        };
+        let name = process.name().to_string_lossy().to_lowercase();
+        names.push(name.trim_end_matches(".exe").to_string());
+        pid = process.parent();
+    }
+    names
+}
+
+pub fn get_shell() -> Result<String, CollectError> {
+    ancestors()
+        .into_iter()
+        .find(|name| SHELLS.contains(&name.as_str()))
+        .ok_or_else(|| CollectError::Failed("no known shell among parent processes".into()))
+}
+
+pub fn get_terminal() -> Result<String, CollectError> {
+    if std::env::var("WT_SESSION").is_ok() {
+        return Ok("Windows Terminal".to_string());
+    }
+    // The first parent that isn't a shell owns the window
+    ancestors()
+        .into_iter()
+        .find(|name| !SHELLS.contains(&name.as_str()))
+        .map(|name| match name.as_str() {
+            "windowsterminal" => "Windows Terminal".to_string(),
+            "conhost" | "openconsole" => "Console Host".to_string(),
+            _ => name,
+        })
+        .ok_or_else(|| CollectError::Failed("no terminal among parent processes".into()))
+}
+
+pub fn get_window_manager() -> Result<String, CollectError> {
+    Ok("DWM".to_string())
+}
+
+/// The first hardware adapter DXGI lists, which is the one driving the
+/// main display; asking DXGI directly is much faster than starting PowerShell
+pub fn get_gpu() -> Result<Option<String>, CollectError> {
+    let failed = |e: windows::core::Error| CollectError::Failed(e.to_string());
+    // SAFETY: plain COM calls with no pointers of ours; the interfaces are
+    // released when dropped
+    let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1() }.map_err(failed)?;
+    for index in 0.. {
+        let adapter = match unsafe { factory.EnumAdapters1(index) } {
+            Ok(adapter) => adapter,
+            Err(e) if e.code() == DXGI_ERROR_NOT_FOUND => break,
+            Err(e) => return Err(failed(e)),
+        };
+        let desc = unsafe { adapter.GetDesc1() }.map_err(failed)?;
+        // Skip the Microsoft Basic Render Driver
+        if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+            continue;
+        }
+        let len = desc
+            .Description
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(desc.Description.len());
+        let gpu = String::from_utf16_lossy(&desc.Description[..len]);
+        return Ok(Some(
+            gpu.replace("NVIDIA Corporation", "NVIDIA")
+                .replace("(R)", "")
+                .replace("(TM)", "")
+                .trim()
+                .to_string(),
+        ));
+    }
+    Ok(None)
+}
+
+pub fn get_model() -> Result<String, CollectError> {
+    let key = r"HKLM\HARDWARE\DESCRIPTION\System\BIOS";
+    let product = reg_value(key, "SystemProductName")
+        .ok_or_else(|| CollectError::Failed("no SystemProductName in the registry".into()))?;
+    Ok(match reg_value(key, "SystemManufacturer") {
+        Some(maker) if !product.starts_with(&maker) => format!("{} {}", maker, product),
+        _ => product,
+    })
+}
+
+/// Windows services aren't started by an init system
+pub fn get_init() -> Option<String> {
+    None
+}
+
+pub fn get_theme() -> Result<String, CollectError> {
+    let light = reg_dword(PERSONALIZE_KEY, "AppsUseLightTheme")
+        .ok_or_else(|| CollectError::Failed("no AppsUseLightTheme in the registry".into()))?;
+    Ok(if light == 0 { "Dark" } else { "Light" }.to_string())
+}